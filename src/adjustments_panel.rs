@@ -0,0 +1,163 @@
+use std::rc::Rc;
+
+use gtk::glib;
+use gtk::prelude::{BoxExt, ButtonExt, GridExt, GtkWindowExt, IsA, RangeExt, ScaleExt, WidgetExt};
+use gtk4 as gtk;
+
+use crate::image_filter::{self, ImageAdjustments};
+
+fn create_scale(min: f64, max: f64, value: f64) -> gtk::Scale {
+    let scale = gtk::Scale::with_range(gtk::Orientation::Horizontal, min, max, 0.01);
+    scale.set_value(value);
+    scale.set_digits(2);
+    scale.set_draw_value(true);
+    scale.set_hexpand(true);
+    scale.set_size_request(240, -1);
+    scale
+}
+
+fn attach_row(grid: &gtk::Grid, row: i32, label: &str, widget: &impl IsA<gtk::Widget>) {
+    let label = gtk::Label::new(Some(label));
+    label.set_halign(gtk::Align::Start);
+    grid.attach(&label, 0, row, 1, 1);
+    grid.attach(widget, 1, row, 1, 1);
+}
+
+// every change is applied at once like the preferences. on_changed gets the adjustments
+// and whether they are kept for the current book only.
+pub fn show_adjustments_window<F: Fn(ImageAdjustments, bool) + 'static>(
+    parent: &impl IsA<gtk::Window>,
+    adjustments: ImageAdjustments,
+    is_book: bool,
+    has_book: bool,
+    on_changed: F,
+) {
+    let grid = gtk::Grid::new();
+    grid.set_row_spacing(8);
+    grid.set_column_spacing(12);
+    grid.set_margin_top(12);
+    grid.set_margin_bottom(12);
+    grid.set_margin_start(12);
+    grid.set_margin_end(12);
+
+    let brightness_scale = create_scale(
+        image_filter::MIN_BRIGHTNESS,
+        image_filter::MAX_BRIGHTNESS,
+        adjustments.brightness,
+    );
+    attach_row(&grid, 0, "Brightness", &brightness_scale);
+    let contrast_scale = create_scale(0.0, image_filter::MAX_CONTRAST, adjustments.contrast);
+    attach_row(&grid, 1, "Contrast", &contrast_scale);
+    let gamma_scale = create_scale(
+        image_filter::MIN_GAMMA,
+        image_filter::MAX_GAMMA,
+        adjustments.gamma,
+    );
+    attach_row(&grid, 2, "Gamma", &gamma_scale);
+    let saturation_scale = create_scale(0.0, image_filter::MAX_SATURATION, adjustments.saturation);
+    attach_row(&grid, 3, "Saturation (0 is grayscale)", &saturation_scale);
+    let sepia_scale = create_scale(0.0, 1.0, adjustments.sepia);
+    attach_row(&grid, 4, "Sepia", &sepia_scale);
+
+    let invert_switch = gtk::Switch::new();
+    invert_switch.set_halign(gtk::Align::Start);
+    invert_switch.set_active(adjustments.is_inverted);
+    attach_row(&grid, 5, "Invert (night mode)", &invert_switch);
+
+    let book_switch = gtk::Switch::new();
+    book_switch.set_halign(gtk::Align::Start);
+    book_switch.set_active(is_book);
+    book_switch.set_sensitive(has_book);
+    attach_row(&grid, 6, "This book only", &book_switch);
+
+    // the widgets are held weakly, so the window is freed when it is closed.
+    let notify: Rc<dyn Fn()> = Rc::new(glib::clone!(
+        #[weak]
+        brightness_scale,
+        #[weak]
+        contrast_scale,
+        #[weak]
+        gamma_scale,
+        #[weak]
+        saturation_scale,
+        #[weak]
+        sepia_scale,
+        #[weak]
+        invert_switch,
+        #[weak]
+        book_switch,
+        move || {
+            let adjustments = ImageAdjustments {
+                brightness: brightness_scale.value(),
+                contrast: contrast_scale.value(),
+                gamma: gamma_scale.value(),
+                saturation: saturation_scale.value(),
+                sepia: sepia_scale.value(),
+                is_inverted: invert_switch.is_active(),
+            };
+            on_changed(adjustments, book_switch.is_active());
+        }
+    ));
+
+    for scale in [
+        &brightness_scale,
+        &contrast_scale,
+        &gamma_scale,
+        &saturation_scale,
+        &sepia_scale,
+    ] {
+        scale.connect_value_changed(glib::clone!(
+            #[strong]
+            notify,
+            move |_scale: &gtk::Scale| notify()
+        ));
+    }
+    for switch in [&invert_switch, &book_switch] {
+        switch.connect_active_notify(glib::clone!(
+            #[strong]
+            notify,
+            move |_switch: &gtk::Switch| notify()
+        ));
+    }
+
+    let reset_button = gtk::Button::with_label("Reset");
+    reset_button.set_halign(gtk::Align::End);
+    reset_button.connect_clicked(glib::clone!(
+        #[weak]
+        brightness_scale,
+        #[weak]
+        contrast_scale,
+        #[weak]
+        gamma_scale,
+        #[weak]
+        saturation_scale,
+        #[weak]
+        sepia_scale,
+        #[weak]
+        invert_switch,
+        move |_button: &gtk::Button| {
+            let adjustments = ImageAdjustments::default();
+            brightness_scale.set_value(adjustments.brightness);
+            contrast_scale.set_value(adjustments.contrast);
+            gamma_scale.set_value(adjustments.gamma);
+            saturation_scale.set_value(adjustments.saturation);
+            sepia_scale.set_value(adjustments.sepia);
+            invert_switch.set_active(adjustments.is_inverted);
+        }
+    ));
+
+    let v_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    v_box.append(&grid);
+    v_box.append(&reset_button);
+    reset_button.set_margin_end(12);
+    reset_button.set_margin_bottom(12);
+
+    let window = gtk::Window::builder()
+        .title("Image Adjustments")
+        .transient_for(parent)
+        .destroy_with_parent(true)
+        .resizable(false)
+        .child(&v_box)
+        .build();
+    window.present();
+}
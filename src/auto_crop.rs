@@ -0,0 +1,450 @@
+use std::collections::BTreeMap;
+
+// no side is cut by more than this, so a mostly blank page keeps its content.
+pub const MAX_CROP_RATIO: f64 = 0.45;
+pub const DEFAULT_TOLERANCE: u8 = 24;
+// pixels of a line which may differ from the border, such as dust on a scan.
+const NOISE_RATIO: f64 = 0.005;
+
+// margins to cut off as ratios of the page size, so one box fits pages of any size.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct CropBox {
+    pub left: f64,
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+}
+
+// pixels of an image as gdk-pixbuf keeps them, rows may be padded.
+pub struct PixelData<'a> {
+    pub pixels: &'a [u8],
+    pub width: usize,
+    pub height: usize,
+    pub rowstride: usize,
+    pub n_channels: usize,
+}
+
+impl CropBox {
+    pub fn is_empty(self) -> bool {
+        self.clamped() == CropBox::default()
+    }
+
+    pub fn clamped(self) -> Self {
+        let clamp = |v: f64| {
+            if v.is_finite() {
+                v.clamp(0.0, MAX_CROP_RATIO)
+            } else {
+                0.0
+            }
+        };
+        CropBox {
+            left: clamp(self.left),
+            top: clamp(self.top),
+            right: clamp(self.right),
+            bottom: clamp(self.bottom),
+        }
+    }
+
+    // the smaller margin of each side, so the pages of a spread keep the same height.
+    pub fn union(self, other: CropBox) -> Self {
+        CropBox {
+            left: self.left.min(other.left),
+            top: self.top.min(other.top),
+            right: self.right.min(other.right),
+            bottom: self.bottom.min(other.bottom),
+        }
+    }
+
+    // x, y, width and height of the part which is kept, it is never smaller than a pixel.
+    pub fn to_rect(self, width: i32, height: i32) -> (i32, i32, i32, i32) {
+        let crop_box = self.clamped();
+        let left = (width as f64 * crop_box.left).floor() as i32;
+        let top = (height as f64 * crop_box.top).floor() as i32;
+        let right = (width as f64 * crop_box.right).floor() as i32;
+        let bottom = (height as f64 * crop_box.bottom).floor() as i32;
+        (
+            left,
+            top,
+            (width - left - right).max(1),
+            (height - top - bottom).max(1),
+        )
+    }
+
+    // saved as "left;top;right;bottom".
+    pub fn to_text(self) -> String {
+        format!("{};{};{};{}", self.left, self.top, self.right, self.bottom)
+    }
+
+    pub fn from_text(text: &str) -> Option<Self> {
+        let mut values = text.split(';').map(|v| v.trim().parse::<f64>().ok());
+        let crop_box = CropBox {
+            left: values.next()??,
+            top: values.next()??,
+            right: values.next()??,
+            bottom: values.next()??,
+        };
+        if values.next().is_some() {
+            return None;
+        }
+        Some(crop_box.clamped())
+    }
+}
+
+// crop boxes of single pages are saved as "page_index:left;top;right;bottom" separated by commas.
+pub fn encode_crop_boxes(crop_boxes: &BTreeMap<usize, CropBox>) -> String {
+    crop_boxes
+        .iter()
+        .map(|(page_index, v)| format!("{}:{}", page_index, v.to_text()))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+pub fn decode_crop_boxes(text: &str) -> BTreeMap<usize, CropBox> {
+    text.split(',')
+        .filter_map(|v| {
+            let (page_index, crop_box) = v.split_once(':')?;
+            let page_index = page_index.trim().parse::<usize>().ok()?;
+            Some((page_index, CropBox::from_text(crop_box)?))
+        })
+        .collect()
+}
+
+impl PixelData<'_> {
+    fn is_valid(&self) -> bool {
+        self.width > 0
+            && self.height > 0
+            && self.n_channels > 0
+            && self.rowstride >= self.width * self.n_channels
+            && self.pixels.len()
+                >= self.rowstride * (self.height - 1) + self.width * self.n_channels
+    }
+
+    // alpha is not compared, a transparent border is found by its colour.
+    fn pixel(&self, x: usize, y: usize) -> &[u8] {
+        let offset = y * self.rowstride + x * self.n_channels;
+        &self.pixels[offset..offset + self.n_channels.min(3)]
+    }
+
+    fn is_uniform_line(
+        &self,
+        points: impl Iterator<Item = (usize, usize)>,
+        background: &[u8],
+        tolerance: u8,
+        max_noise: usize,
+    ) -> bool {
+        let mut noise = 0;
+        for (x, y) in points {
+            let is_near = self
+                .pixel(x, y)
+                .iter()
+                .zip(background)
+                .all(|(a, b)| a.abs_diff(*b) <= tolerance);
+            if !is_near {
+                noise += 1;
+                if noise > max_noise {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+// lines from each edge are cut while they are the colour of the corner within the tolerance,
+// a white or black border is found the same way. a blank page is not cropped.
+pub fn detect_crop_box(pixel_data: &PixelData, tolerance: u8) -> CropBox {
+    if !pixel_data.is_valid() {
+        return CropBox::default();
+    }
+
+    let width = pixel_data.width;
+    let height = pixel_data.height;
+    let max_noise_for_row = (width as f64 * NOISE_RATIO) as usize;
+    let max_noise_for_column = (height as f64 * NOISE_RATIO) as usize;
+
+    let background = pixel_data.pixel(0, 0);
+    let top = (0..height)
+        .take_while(|y| {
+            pixel_data.is_uniform_line(
+                (0..width).map(|x| (x, *y)),
+                background,
+                tolerance,
+                max_noise_for_row,
+            )
+        })
+        .count();
+    if top == height {
+        return CropBox::default();
+    }
+
+    let background = pixel_data.pixel(width - 1, height - 1);
+    let bottom = (0..height - top)
+        .take_while(|v| {
+            pixel_data.is_uniform_line(
+                (0..width).map(|x| (x, height - 1 - v)),
+                background,
+                tolerance,
+                max_noise_for_row,
+            )
+        })
+        .count();
+
+    let rows = top..height - bottom;
+    let background = pixel_data.pixel(0, height - 1);
+    let left = (0..width)
+        .take_while(|x| {
+            pixel_data.is_uniform_line(
+                rows.clone().map(|y| (*x, y)),
+                background,
+                tolerance,
+                max_noise_for_column,
+            )
+        })
+        .count();
+
+    let background = pixel_data.pixel(width - 1, 0);
+    let right = (0..width - left)
+        .take_while(|v| {
+            pixel_data.is_uniform_line(
+                rows.clone().map(|y| (width - 1 - v, y)),
+                background,
+                tolerance,
+                max_noise_for_column,
+            )
+        })
+        .count();
+
+    CropBox {
+        left: left as f64 / width as f64,
+        top: top as f64 / height as f64,
+        right: right as f64 / width as f64,
+        bottom: bottom as f64 / height as f64,
+    }
+    .clamped()
+}
+
+// the mean colour of the outermost pixels, a background of this colour continues the paper.
+pub fn get_border_color(pixel_data: &PixelData) -> Option<[u8; 3]> {
+    if !pixel_data.is_valid() || pixel_data.n_channels < 3 {
+        return None;
+    }
+
+    let width = pixel_data.width;
+    let height = pixel_data.height;
+    let rows = (0..width).flat_map(|x| [(x, 0), (x, height - 1)]);
+    let columns = (1..height.saturating_sub(1)).flat_map(|y| [(0, y), (width - 1, y)]);
+    let mut sums = [0u64; 3];
+    let mut count = 0u64;
+    for (x, y) in rows.chain(columns) {
+        for (sum, value) in sums.iter_mut().zip(pixel_data.pixel(x, y)) {
+            *sum += u64::from(*value);
+        }
+        count += 1;
+    }
+    Some(sums.map(|v| (v / count) as u8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: [u8; 3] = [255, 255, 255];
+    const BLACK: [u8; 3] = [0, 0, 0];
+
+    // an rgb image of the background colour with a filled rectangle.
+    fn make_pixels(
+        width: usize,
+        height: usize,
+        background: [u8; 3],
+        content: (usize, usize, usize, usize),
+    ) -> Vec<u8> {
+        let (x, y, content_width, content_height) = content;
+        let mut pixels = Vec::with_capacity(width * height * 3);
+        for row in 0..height {
+            for column in 0..width {
+                let is_content = (x..x + content_width).contains(&column)
+                    && (y..y + content_height).contains(&row);
+                if is_content {
+                    pixels.extend_from_slice(&[120, 60, 30]);
+                } else {
+                    pixels.extend_from_slice(&background);
+                }
+            }
+        }
+        pixels
+    }
+
+    fn detect(pixels: &[u8], width: usize, height: usize, tolerance: u8) -> CropBox {
+        let pixel_data = PixelData {
+            pixels,
+            width,
+            height,
+            rowstride: width * 3,
+            n_channels: 3,
+        };
+        detect_crop_box(&pixel_data, tolerance)
+    }
+
+    #[test]
+    fn detects_white_margins() {
+        let pixels = make_pixels(100, 200, WHITE, (10, 20, 70, 150));
+        let crop_box = detect(&pixels, 100, 200, 0);
+        assert_eq!(
+            crop_box,
+            CropBox {
+                left: 0.1,
+                top: 0.1,
+                right: 0.2,
+                bottom: 0.15,
+            }
+        );
+        assert_eq!(crop_box.to_rect(100, 200), (10, 20, 70, 150));
+    }
+
+    #[test]
+    fn detects_black_border() {
+        let pixels = make_pixels(50, 50, BLACK, (5, 5, 40, 40));
+        assert_eq!(detect(&pixels, 50, 50, 0).to_rect(50, 50), (5, 5, 40, 40));
+    }
+
+    #[test]
+    fn tolerance_absorbs_uneven_paper() {
+        let mut pixels = make_pixels(40, 40, WHITE, (10, 10, 20, 20));
+        // a slightly grey scan line in the top margin.
+        for x in 0..40 {
+            pixels[(2 * 40 + x) * 3..(2 * 40 + x) * 3 + 3].copy_from_slice(&[240, 240, 235]);
+        }
+        assert_eq!(detect(&pixels, 40, 40, 0).top, 2.0 / 40.0);
+        assert_eq!(detect(&pixels, 40, 40, 24).top, 0.25);
+    }
+
+    #[test]
+    fn blank_page_is_not_cropped() {
+        let pixels = make_pixels(30, 30, WHITE, (0, 0, 0, 0));
+        assert!(detect(&pixels, 30, 30, 24).is_empty());
+        assert!(detect(&[], 0, 0, 24).is_empty());
+    }
+
+    #[test]
+    fn crop_is_limited_on_each_side() {
+        let pixels = make_pixels(100, 100, WHITE, (95, 0, 5, 100));
+        assert_eq!(detect(&pixels, 100, 100, 0).left, MAX_CROP_RATIO);
+    }
+
+    #[test]
+    fn padded_rows_are_skipped() {
+        let width = 20;
+        let rowstride = width * 4 + 8;
+        let mut pixels = vec![0; rowstride * 20];
+        for y in 0..20 {
+            for x in 0..width {
+                let value = if (5..15).contains(&x) && (4..16).contains(&y) {
+                    0
+                } else {
+                    255
+                };
+                pixels[y * rowstride + x * 4..y * rowstride + x * 4 + 4]
+                    .copy_from_slice(&[value, value, value, 255]);
+            }
+        }
+        let pixel_data = PixelData {
+            pixels: &pixels,
+            width,
+            height: 20,
+            rowstride,
+            n_channels: 4,
+        };
+        assert_eq!(
+            detect_crop_box(&pixel_data, 0).to_rect(20, 20),
+            (5, 4, 10, 12)
+        );
+    }
+
+    #[test]
+    fn border_color_is_the_mean_of_the_edges() {
+        let pixels = make_pixels(20, 10, [200, 100, 50], (1, 1, 18, 8));
+        let pixel_data = PixelData {
+            pixels: &pixels,
+            width: 20,
+            height: 10,
+            rowstride: 60,
+            n_channels: 3,
+        };
+        assert_eq!(get_border_color(&pixel_data), Some([200, 100, 50]));
+
+        // a black left edge takes its share of the 56 edge pixels.
+        let mut pixels = make_pixels(20, 10, WHITE, (1, 1, 18, 8));
+        for y in 0..10 {
+            pixels[y * 60..y * 60 + 3].copy_from_slice(&BLACK);
+        }
+        let pixel_data = PixelData {
+            pixels: &pixels,
+            width: 20,
+            height: 10,
+            rowstride: 60,
+            n_channels: 3,
+        };
+        let value = (255 * 46 / 56) as u8;
+        assert_eq!(get_border_color(&pixel_data), Some([value; 3]));
+
+        let pixel = [10, 20, 30, 255];
+        let pixel_data = PixelData {
+            pixels: &pixel,
+            width: 1,
+            height: 1,
+            rowstride: 4,
+            n_channels: 4,
+        };
+        assert_eq!(get_border_color(&pixel_data), Some([10, 20, 30]));
+    }
+
+    #[test]
+    fn spread_keeps_the_smaller_margins() {
+        let left_page = CropBox {
+            left: 0.1,
+            top: 0.05,
+            right: 0.0,
+            bottom: 0.2,
+        };
+        let right_page = CropBox {
+            left: 0.0,
+            top: 0.1,
+            right: 0.15,
+            bottom: 0.1,
+        };
+        assert_eq!(
+            left_page.union(right_page),
+            CropBox {
+                left: 0.0,
+                top: 0.05,
+                right: 0.0,
+                bottom: 0.1,
+            }
+        );
+    }
+
+    #[test]
+    fn crop_boxes_round_trip() {
+        let mut crop_boxes = BTreeMap::new();
+        crop_boxes.insert(
+            3,
+            CropBox {
+                left: 0.1,
+                top: 0.0,
+                right: 0.25,
+                bottom: 0.05,
+            },
+        );
+        crop_boxes.insert(0, CropBox::default());
+
+        let text = encode_crop_boxes(&crop_boxes);
+        assert_eq!(text, "0:0;0;0;0,3:0.1;0;0.25;0.05");
+        assert_eq!(decode_crop_boxes(&text), crop_boxes);
+        assert!(decode_crop_boxes("").is_empty());
+        assert!(decode_crop_boxes("1:0;0;0,x:0;0;0;0").is_empty());
+        assert_eq!(
+            CropBox::from_text("0.9;0;0;0").unwrap().left,
+            MAX_CROP_RATIO
+        );
+    }
+}
@@ -78,10 +78,42 @@ impl DbManager {
 
             self.conn.execute("PRAGMA user_version = 2", ()).unwrap();
         }
+
+        if user_version < 3 {
+            if !self.has_open_file_history_column("page_transform") {
+                self.conn.execute_batch("alter table open_file_history add column page_transform integer not null default 0;").unwrap();
+            }
+
+            if !self.has_open_file_history_column("page_transforms") {
+                self.conn.execute_batch("alter table open_file_history add column page_transforms text not null default '';").unwrap();
+            }
+
+            self.conn.execute("PRAGMA user_version = 3", ()).unwrap();
+        }
+
+        if user_version < 4 {
+            if !self.has_open_file_history_column("crop_box") {
+                self.conn.execute_batch("alter table open_file_history add column crop_box text not null default '';").unwrap();
+            }
+
+            if !self.has_open_file_history_column("crop_boxes") {
+                self.conn.execute_batch("alter table open_file_history add column crop_boxes text not null default '';").unwrap();
+            }
+
+            self.conn.execute("PRAGMA user_version = 4", ()).unwrap();
+        }
+
+        if user_version < 5 {
+            if !self.has_open_file_history_column("adjustments") {
+                self.conn.execute_batch("alter table open_file_history add column adjustments text not null default '';").unwrap();
+            }
+
+            self.conn.execute("PRAGMA user_version = 5", ()).unwrap();
+        }
     }
 
     pub fn init(&self) {
-        self.conn.execute("create table if not exists open_file_history (id integer primary key autoincrement, location_path text not null unique, unixtime integer not null, last_show_page_index integer not null, page_direction integer not null default 0)",
+        self.conn.execute("create table if not exists open_file_history (id integer primary key autoincrement, location_path text not null unique, unixtime integer not null, last_show_page_index integer not null, page_direction integer not null default 0, page_transform integer not null default 0, page_transforms text not null default '', crop_box text not null default '', crop_boxes text not null default '', adjustments text not null default '')",
                           ()).unwrap();
         self.migrate();
     }
@@ -123,6 +155,71 @@ impl DbManager {
             .unwrap();
     }
 
+    // the transform of the whole book and the encoded transforms of single pages.
+    pub fn update_page_transforms(
+        &self,
+        file_path: &str,
+        page_transform: i64,
+        page_transforms: &str,
+    ) {
+        self.conn
+            .execute(
+                "update open_file_history set page_transform = ?1, page_transforms = ?2 where location_path = ?3",
+                params![page_transform, page_transforms, file_path.to_owned()],
+            )
+            .unwrap();
+    }
+
+    pub fn get_page_transforms(&self, file_path: &str) -> Option<(i64, String)> {
+        self.conn
+            .query_row(
+                "select page_transform, page_transforms from open_file_history where location_path = ?1",
+                [file_path],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .ok()
+    }
+
+    // the crop box of the whole book and the encoded crop boxes of single pages.
+    pub fn update_crop_boxes(&self, file_path: &str, crop_box: &str, crop_boxes: &str) {
+        self.conn
+            .execute(
+                "update open_file_history set crop_box = ?1, crop_boxes = ?2 where location_path = ?3",
+                params![crop_box, crop_boxes, file_path.to_owned()],
+            )
+            .unwrap();
+    }
+
+    pub fn get_crop_boxes(&self, file_path: &str) -> Option<(String, String)> {
+        self.conn
+            .query_row(
+                "select crop_box, crop_boxes from open_file_history where location_path = ?1",
+                [file_path],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .ok()
+    }
+
+    // empty when the book follows the adjustments of the settings.
+    pub fn update_adjustments(&self, file_path: &str, adjustments: &str) {
+        self.conn
+            .execute(
+                "update open_file_history set adjustments = ?1 where location_path = ?2",
+                params![adjustments, file_path.to_owned()],
+            )
+            .unwrap();
+    }
+
+    pub fn get_adjustments(&self, file_path: &str) -> Option<String> {
+        self.conn
+            .query_row(
+                "select adjustments from open_file_history where location_path = ?1",
+                [file_path],
+                |row| row.get(0),
+            )
+            .ok()
+    }
+
     pub fn is_exists_file_path(&self, file_path: &str) -> bool {
         let mut stmt = self
             .conn
@@ -190,12 +287,12 @@ impl DbManager {
         None
     }
 
-    pub fn get_history(&self) -> Vec<FileHistory> {
+    pub fn get_history(&self, limit: usize) -> Vec<FileHistory> {
         let mut file_history_list: Vec<FileHistory> = vec![];
 
-        let mut stmt = self.conn.prepare("select id, location_path, unixtime, last_show_page_index, page_direction from open_file_history order by unixtime desc limit 10").unwrap();
+        let mut stmt = self.conn.prepare("select id, location_path, unixtime, last_show_page_index, page_direction from open_file_history order by unixtime desc limit ?1").unwrap();
         let stmt_iter = stmt
-            .query_map([], |row| {
+            .query_map(params![limit as i64], |row| {
                 let unixtime: i64 = row.get(2).unwrap();
                 let page_direction_tmp: i64 = row.get(4).unwrap();
                 let page_direction = PageDirection::try_from(page_direction_tmp).unwrap();
@@ -232,34 +329,147 @@ mod tests {
             .to_string()
     }
 
+    // the columns of the history table with the schema version which added them,
+    // and the value of the book stored before the migration.
+    const HISTORY_COLUMNS: [(i64, &str, &str); 8] = [
+        (1, "unixtime integer not null", "10"),
+        (1, "page_direction integer not null default 0", "1"),
+        (2, "last_show_page_index integer not null", "3"),
+        (3, "page_transform integer not null default 0", "1"),
+        (3, "page_transforms text not null default ''", "''"),
+        (4, "crop_box text not null default ''", "''"),
+        (4, "crop_boxes text not null default ''", "''"),
+        (5, "adjustments text not null default ''", "''"),
+    ];
+
+    // creates a history db at the schema version with /tmp/sample.cbz in it.
+    fn create_db_at_version(test_name: &str, user_version: i64) -> String {
+        let db_path = make_test_db_path(test_name);
+        // the path column was renamed at version 2.
+        let path_column = if user_version < 2 {
+            "path"
+        } else {
+            "location_path"
+        };
+        let columns: Vec<(&str, &str)> = HISTORY_COLUMNS
+            .iter()
+            .filter(|(version, _, _)| *version <= user_version)
+            .map(|(_, definition, value)| (*definition, *value))
+            .collect();
+
+        let definitions: Vec<&str> = columns.iter().map(|(v, _)| *v).collect();
+        let names: Vec<&str> = columns
+            .iter()
+            .map(|(v, _)| v.split(' ').next().unwrap())
+            .collect();
+        let values: Vec<&str> = columns.iter().map(|(_, v)| *v).collect();
+
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(&format!(
+            "create table open_file_history (
+                id integer primary key autoincrement,
+                {path_column} text not null unique,
+                {}
+            );
+            insert into open_file_history ({path_column}, {})
+            values ('/tmp/sample.cbz', {});
+            PRAGMA user_version = {user_version};",
+            definitions.join(",\n"),
+            names.join(", "),
+            values.join(", "),
+        ))
+        .unwrap();
+
+        db_path
+    }
+
     #[test]
     fn init_migrates_old_history_schema() {
-        let db_path = make_test_db_path("old_history_schema");
-
-        {
-            let conn = Connection::open(&db_path).unwrap();
-            conn.execute_batch(
-                "create table open_file_history (
-                    id integer primary key autoincrement,
-                    path text not null unique,
-                    unixtime integer not null,
-                    page_direction integer not null default 0
-                );
-                insert into open_file_history (path, unixtime, page_direction)
-                values ('/tmp/sample.cbz', 10, 0);
-                PRAGMA user_version = 1;",
-            )
-            .unwrap();
-        }
+        let db_path = create_db_at_version("old_history_schema", 1);
 
         let db = DbManager::new(&db_path);
         db.init();
 
         assert!(db.has_open_file_history_column("location_path"));
         assert!(db.has_open_file_history_column("last_show_page_index"));
-        assert_eq!(db.get_history()[0].location_path, "/tmp/sample.cbz");
+        assert_eq!(db.get_history(10)[0].location_path, "/tmp/sample.cbz");
         assert_eq!(db.get_last_page_index("/tmp/sample.cbz"), Some(0));
 
         std::fs::remove_file(db_path).unwrap();
     }
+
+    #[test]
+    fn init_migrates_to_page_transforms() {
+        let db_path = create_db_at_version("page_transforms", 2);
+
+        let db = DbManager::new(&db_path);
+        db.init();
+
+        assert!(db.has_open_file_history_column("page_transform"));
+        assert!(db.has_open_file_history_column("page_transforms"));
+        assert_eq!(
+            db.get_page_transforms("/tmp/sample.cbz"),
+            Some((0, String::new()))
+        );
+
+        db.update_page_transforms("/tmp/sample.cbz", 5, "0:1,12:4");
+        assert_eq!(
+            db.get_page_transforms("/tmp/sample.cbz"),
+            Some((5, "0:1,12:4".to_string()))
+        );
+        assert_eq!(db.get_page_transforms("/tmp/missing.cbz"), None);
+        assert_eq!(db.get_last_page_index("/tmp/sample.cbz"), Some(3));
+
+        std::fs::remove_file(db_path).unwrap();
+    }
+
+    #[test]
+    fn init_migrates_to_crop_boxes() {
+        let db_path = create_db_at_version("crop_boxes", 3);
+
+        let db = DbManager::new(&db_path);
+        db.init();
+
+        assert!(db.has_open_file_history_column("crop_box"));
+        assert!(db.has_open_file_history_column("crop_boxes"));
+        assert_eq!(
+            db.get_crop_boxes("/tmp/sample.cbz"),
+            Some((String::new(), String::new()))
+        );
+
+        db.update_crop_boxes("/tmp/sample.cbz", "0.1;0;0.1;0", "2:0;0.05;0;0.05");
+        assert_eq!(
+            db.get_crop_boxes("/tmp/sample.cbz"),
+            Some(("0.1;0;0.1;0".to_string(), "2:0;0.05;0;0.05".to_string()))
+        );
+        assert_eq!(
+            db.get_page_transforms("/tmp/sample.cbz"),
+            Some((1, String::new()))
+        );
+
+        std::fs::remove_file(db_path).unwrap();
+    }
+
+    #[test]
+    fn init_adds_adjustments_to_new_and_migrated_tables() {
+        let db_path = create_db_at_version("adjustments", 4);
+
+        let db = DbManager::new(&db_path);
+        db.init();
+
+        assert_eq!(db.get_adjustments("/tmp/sample.cbz"), Some(String::new()));
+        db.update_adjustments("/tmp/sample.cbz", "0.1;1;1;0;0;1");
+        assert_eq!(
+            db.get_adjustments("/tmp/sample.cbz"),
+            Some("0.1;1;1;0;0;1".to_string())
+        );
+        std::fs::remove_file(&db_path).unwrap();
+
+        let db = DbManager::new(&db_path);
+        db.init();
+        db.add_history("/tmp/new.cbz".to_string(), 20, PageDirection::RightToLeft);
+        assert_eq!(db.get_adjustments("/tmp/new.cbz"), Some(String::new()));
+
+        std::fs::remove_file(db_path).unwrap();
+    }
 }
@@ -1,14 +1,38 @@
 use std::cell::RefCell;
 use std::fs::File;
 use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, mpsc};
 
 use gtk::{gio, glib};
 use gtk4 as gtk;
 
-use gtk::prelude::{FileExt, PixbufLoaderExt};
+use gtk::prelude::{FileExt, ObjectExt, PixbufLoaderExt};
 
+use crate::auto_crop::{self, CropBox};
+use crate::image_filter::{self, ImageAdjustments};
+use crate::image_scaler::{self, PixelBuffer};
+use crate::page_transform::PageTransform;
+use crate::types::{FitMode, Interpolation};
 use crate::{pdf_loader::PdfPixmap, utils};
 
+// threads shared by all pages for scaling them.
+const SCALE_WORKER_COUNT: usize = 2;
+// the page is sampled at this width for its border colour and the blurred background.
+const BACKGROUND_SAMPLE_WIDTH: i32 = 64;
+const BLURRED_BACKGROUND_WIDTH: i32 = 8;
+
+// a page which cannot be decoded is replaced by a page of this size telling why.
+const PLACEHOLDER_WIDTH: i32 = 800;
+const PLACEHOLDER_HEIGHT: i32 = 1130;
+const PLACEHOLDER_MARGIN: f64 = 60.0;
+const PLACEHOLDER_FONT_SIZE: f64 = 28.0;
+
+// size, interpolation and sharpening of a scaled page.
+type ScaledKey = (i32, i32, Interpolation, f64);
+// the scaled page something was made from, it is not kept alive for that.
+type ScaledSource = glib::WeakRef<gtk::gdk_pixbuf::Pixbuf>;
+
 enum PictureDirectionType {
     Vertical,
     Horizontal,
@@ -19,7 +43,50 @@ enum PictureDirectionType {
 pub struct ImageContainer {
     modified_pixbuf_data: RefCell<Option<gtk::gdk_pixbuf::Pixbuf>>,
     orig_pixbuf_data: RefCell<Option<gtk::gdk_pixbuf::Pixbuf>>,
-    // file_name: RefCell<Option<String>>,
+    // size and interpolation of modified_pixbuf_data, scaling again with them is skipped.
+    scaled_key: RefCell<Option<ScaledKey>>,
+    // scaling running in a thread, modified_pixbuf_data is a quick preview until it is done.
+    scale_job: RefCell<Option<ScaleJob>>,
+    // entry name in an archive or file name in a directory.
+    file_name: RefCell<Option<String>>,
+    // rotation and flip of the page, applied before it is scaled.
+    transform: RefCell<PageTransform>,
+    // rotation rendered into orig_pixbuf_data, only pdf pages are rendered rotated.
+    native_rotation: RefCell<Option<i32>>,
+    // orig_pixbuf_data with the transform, made again when the transform changes.
+    transformed_pixbuf_data: RefCell<Option<gtk::gdk_pixbuf::Pixbuf>>,
+    // margins cut off the transformed page before it is scaled.
+    crop_box: RefCell<CropBox>,
+    // margins found in the transformed page with the tolerance they were found with.
+    detected_crop_box: RefCell<Option<(u8, CropBox)>>,
+    // colour adjustments applied to the scaled page, orig_pixbuf_data is left as it is.
+    adjustments: RefCell<ImageAdjustments>,
+    // made from modified_pixbuf_data for the background, they are made again once it changes.
+    border_color: RefCell<Option<(ScaledSource, Option<[u8; 3]>)>>,
+    blurred_background: RefCell<Option<(ScaledSource, Option<gtk::gdk_pixbuf::Pixbuf>)>>,
+}
+
+#[derive(Clone)]
+struct ScaleJob {
+    key: ScaledKey,
+    receiver: Arc<Mutex<mpsc::Receiver<Option<PixelBuffer>>>>,
+    is_cancelled: Arc<AtomicBool>,
+}
+
+// everything a thread needs to scale a page, the pixels are shared and not copied.
+struct ScaleRequest {
+    bytes: glib::Bytes,
+    width: i32,
+    height: i32,
+    rowstride: i32,
+    n_channels: i32,
+    has_alpha: bool,
+    crop_rect: (i32, i32, i32, i32),
+    result_width: i32,
+    result_height: i32,
+    interpolation: Interpolation,
+    sharpen_amount: f64,
+    adjustments: ImageAdjustments,
 }
 
 unsafe impl Send for ImageContainer {}
@@ -33,15 +100,56 @@ pub struct AspectRatioCollection {
 
 #[allow(dead_code)]
 pub trait ImageContainerEx {
-    fn set_pixbuf_from_file(&self, file: &gio::File);
-    fn set_pixbuf_from_bytes(&self, bytes: &[u8]);
+    fn set_pixbuf_from_file(&self, file: &gio::File) -> Result<(), glib::Error>;
+    fn set_pixbuf_from_bytes(&self, bytes: &[u8]) -> Result<(), glib::Error>;
+    fn set_placeholder_pixbuf(&self, message: &str);
     fn set_pixbuf_from_pdf_pixmap(&self, pdf_pixmap: &PdfPixmap);
+    fn set_rendered_pdf_pixmap(&self, pdf_pixmap: &PdfPixmap);
     fn get_modified_pixbuf_data(&self) -> Option<gtk::gdk_pixbuf::Pixbuf>;
+    fn get_orig_pixbuf_data(&self) -> Option<gtk::gdk_pixbuf::Pixbuf>;
+    fn get_export_pixbuf_data(&self) -> Option<gtk::gdk_pixbuf::Pixbuf>;
     fn get_modified_width(&self) -> Option<i32>;
     fn get_modified_height(&self) -> Option<i32>;
     fn get_orig_width(&self) -> Option<i32>;
     fn get_orig_height(&self) -> Option<i32>;
-    fn scale(&self, target_width: i32, target_height: i32, is_dual_mode: bool);
+    fn set_file_name(&self, file_name: &str);
+    fn get_file_name(&self) -> Option<String>;
+    fn set_transform(&self, transform: PageTransform);
+    fn get_transform(&self) -> PageTransform;
+    fn get_pending_native_rotation(&self) -> Option<i32>;
+    fn set_crop_box(&self, crop_box: CropBox);
+    fn get_crop_box(&self) -> CropBox;
+    fn detect_crop_box(&self, tolerance: u8) -> Option<CropBox>;
+    fn set_adjustments(&self, adjustments: ImageAdjustments);
+    fn take_scaled_pixbuf_data(&self) -> bool;
+    fn is_scaling(&self) -> bool;
+    fn get_border_color(&self) -> Option<[u8; 3]>;
+    fn get_blurred_background(&self) -> Option<gtk::gdk_pixbuf::Pixbuf>;
+    fn get_orig_region(
+        &self,
+        x_ratio: f64,
+        y_ratio: f64,
+        width: i32,
+        height: i32,
+    ) -> Option<(gtk::gdk_pixbuf::Pixbuf, f64, f64)>;
+    fn scale(
+        &self,
+        target_width: i32,
+        target_height: i32,
+        is_dual_mode: bool,
+        fit_mode: FitMode,
+        interpolation: Interpolation,
+        sharpen_amount: f64,
+    );
+    fn prefetch(
+        &self,
+        target_width: i32,
+        target_height: i32,
+        is_dual_mode: bool,
+        fit_mode: FitMode,
+        interpolation: Interpolation,
+        sharpen_amount: f64,
+    );
 }
 
 impl ImageContainerEx for ImageContainer {
@@ -53,10 +161,18 @@ impl ImageContainerEx for ImageContainer {
         Some(v)
     }
 
-    fn set_pixbuf_from_file(&self, file: &gio::File) {
-        let Some(pixbuf_data) = create_pixbuf_from_file(file) else {
-            return;
-        };
+    fn get_orig_pixbuf_data(&self) -> Option<gtk::gdk_pixbuf::Pixbuf> {
+        self.orig_pixbuf_data.borrow().clone()
+    }
+
+    // a file which cannot be read fails like data which cannot be decoded.
+    fn set_pixbuf_from_file(&self, file: &gio::File) -> Result<(), glib::Error> {
+        let (bytes, _etag) = file.load_bytes(gio::Cancellable::NONE)?;
+        self.set_pixbuf_from_bytes(&bytes)
+    }
+
+    fn set_pixbuf_from_bytes(&self, bytes: &[u8]) -> Result<(), glib::Error> {
+        let pixbuf_data = create_pixbuf_from_bytes(bytes)?;
 
         let _ = self
             .modified_pixbuf_data
@@ -65,10 +181,16 @@ impl ImageContainerEx for ImageContainer {
         let _ = self
             .orig_pixbuf_data
             .replace_with(|_| Some(pixbuf_data.clone()));
+        let _ = self.transformed_pixbuf_data.replace(None);
+        let _ = self.detected_crop_box.replace(None);
+        self.reset_scaled_key();
+        Ok(())
     }
 
-    fn set_pixbuf_from_bytes(&self, bytes: &[u8]) {
-        let Some(pixbuf_data) = create_pixbuf_from_bytes(bytes) else {
+    // the file name has to be set first, it is shown with the message.
+    fn set_placeholder_pixbuf(&self, message: &str) {
+        let file_name = self.get_file_name().unwrap_or_default();
+        let Some(pixbuf_data) = create_placeholder_pixbuf(&file_name, message) else {
             return;
         };
 
@@ -79,6 +201,9 @@ impl ImageContainerEx for ImageContainer {
         let _ = self
             .orig_pixbuf_data
             .replace_with(|_| Some(pixbuf_data.clone()));
+        let _ = self.transformed_pixbuf_data.replace(None);
+        let _ = self.detected_crop_box.replace(None);
+        self.reset_scaled_key();
     }
 
     fn set_pixbuf_from_pdf_pixmap(&self, pdf_pixmap: &PdfPixmap) {
@@ -90,6 +215,7 @@ impl ImageContainerEx for ImageContainer {
             return;
         } // Gdk Pixbuf not support Grayscale. mupdf pixmap n, 3 is rgb, 4 is rgba.
         let stride = pdf_pixmap.pixmap.stride() as i32;
+        let _ = self.native_rotation.replace(Some(pdf_pixmap.rotation));
         let is_alpha = n == 4; // mupdf pixmap n is only have alpha channel when 4.
         let bytes = glib::Bytes::from(samples);
         let pixbuf_data = gtk::gdk_pixbuf::Pixbuf::from_bytes(
@@ -109,6 +235,191 @@ impl ImageContainerEx for ImageContainer {
         let _ = self
             .orig_pixbuf_data
             .replace_with(|_| Some(pixbuf_data.clone()));
+        let _ = self.transformed_pixbuf_data.replace(None);
+        let _ = self.detected_crop_box.replace(None);
+        self.reset_scaled_key();
+    }
+
+    fn set_file_name(&self, file_name: &str) {
+        let _ = self.file_name.replace(Some(file_name.to_string()));
+    }
+
+    fn get_file_name(&self) -> Option<String> {
+        self.file_name.borrow().clone()
+    }
+
+    fn set_transform(&self, transform: PageTransform) {
+        if *self.transform.borrow() == transform {
+            return;
+        }
+
+        let _ = self.transform.replace(transform);
+        let _ = self.transformed_pixbuf_data.replace(None);
+        let _ = self.detected_crop_box.replace(None);
+        self.reset_scaled_key();
+    }
+
+    fn get_transform(&self) -> PageTransform {
+        *self.transform.borrow()
+    }
+
+    // a pdf page whose rotation differs from the rendered one has to be rendered again.
+    fn get_pending_native_rotation(&self) -> Option<i32> {
+        let native_rotation = (*self.native_rotation.borrow())?;
+        let rotation = self.transform.borrow().rotation;
+        (native_rotation != rotation).then_some(rotation)
+    }
+
+    fn set_crop_box(&self, crop_box: CropBox) {
+        let crop_box = crop_box.clamped();
+        if *self.crop_box.borrow() == crop_box {
+            return;
+        }
+
+        let _ = self.crop_box.replace(crop_box);
+        self.reset_scaled_key();
+    }
+
+    fn get_crop_box(&self) -> CropBox {
+        *self.crop_box.borrow()
+    }
+
+    // found once for each transform and tolerance, scans the whole page.
+    fn detect_crop_box(&self, tolerance: u8) -> Option<CropBox> {
+        if let Some((detected_tolerance, crop_box)) = *self.detected_crop_box.borrow()
+            && detected_tolerance == tolerance
+        {
+            return Some(crop_box);
+        }
+
+        let pixbuf_data = self.get_transformed_pixbuf_data()?;
+        let bytes = pixbuf_data.read_pixel_bytes();
+        let pixel_data = auto_crop::PixelData {
+            pixels: &bytes,
+            width: pixbuf_data.width() as usize,
+            height: pixbuf_data.height() as usize,
+            rowstride: pixbuf_data.rowstride() as usize,
+            n_channels: pixbuf_data.n_channels() as usize,
+        };
+        let crop_box = auto_crop::detect_crop_box(&pixel_data, tolerance);
+        let _ = self.detected_crop_box.replace(Some((tolerance, crop_box)));
+        Some(crop_box)
+    }
+
+    fn set_adjustments(&self, adjustments: ImageAdjustments) {
+        if *self.adjustments.borrow() == adjustments {
+            return;
+        }
+
+        let _ = self.adjustments.replace(adjustments);
+        self.reset_scaled_key();
+    }
+
+    // the result of a finished thread is shown, true when there is a new one.
+    fn take_scaled_pixbuf_data(&self) -> bool {
+        let Some(scale_job) = self.scale_job.borrow().clone() else {
+            return false;
+        };
+
+        let result = scale_job.receiver.lock().unwrap().try_recv();
+        match result {
+            Err(mpsc::TryRecvError::Empty) => false,
+            Ok(Some(scaled)) => {
+                let _ = self.scale_job.replace(None);
+                let _ = self
+                    .modified_pixbuf_data
+                    .replace(Some(create_pixbuf_from_pixel_buffer(scaled)));
+                let _ = self.scaled_key.replace(Some(scale_job.key));
+                true
+            }
+            _ => {
+                let _ = self.scale_job.replace(None);
+                false
+            }
+        }
+    }
+
+    fn is_scaling(&self) -> bool {
+        self.scale_job.borrow().is_some()
+    }
+
+    fn get_border_color(&self) -> Option<[u8; 3]> {
+        let pixbuf_data = self.get_modified_pixbuf_data()?;
+        if let Some((source, border_color)) = self.border_color.borrow().as_ref()
+            && source.upgrade().is_some_and(|v| v == pixbuf_data)
+        {
+            return *border_color;
+        }
+
+        let border_color = create_background_sample(&pixbuf_data).and_then(|sample| {
+            let bytes = sample.read_pixel_bytes();
+            auto_crop::get_border_color(&auto_crop::PixelData {
+                pixels: &bytes,
+                width: sample.width() as usize,
+                height: sample.height() as usize,
+                rowstride: sample.rowstride() as usize,
+                n_channels: sample.n_channels() as usize,
+            })
+        });
+        let _ = self
+            .border_color
+            .replace(Some((pixbuf_data.downgrade(), border_color)));
+        border_color
+    }
+
+    // a few pixels of the page, stretched over the view they are smoothed into a blur.
+    fn get_blurred_background(&self) -> Option<gtk::gdk_pixbuf::Pixbuf> {
+        let pixbuf_data = self.get_modified_pixbuf_data()?;
+        if let Some((source, blurred)) = self.blurred_background.borrow().as_ref()
+            && source.upgrade().is_some_and(|v| v == pixbuf_data)
+        {
+            return blurred.clone();
+        }
+
+        let blurred = create_background_sample(&pixbuf_data).and_then(|v| {
+            let height = (v.height() * BLURRED_BACKGROUND_WIDTH / v.width()).max(1);
+            v.scale_simple(
+                BLURRED_BACKGROUND_WIDTH.min(v.width()),
+                height,
+                gtk::gdk_pixbuf::InterpType::Bilinear,
+            )
+        });
+        let _ = self
+            .blurred_background
+            .replace(Some((pixbuf_data.downgrade(), blurred.clone())));
+        blurred
+    }
+
+    // a part of the original around a point of the shown page, rotated, cropped and adjusted
+    // like the page. the point is given as ratios of the page and also returned in the part.
+    fn get_orig_region(
+        &self,
+        x_ratio: f64,
+        y_ratio: f64,
+        width: i32,
+        height: i32,
+    ) -> Option<(gtk::gdk_pixbuf::Pixbuf, f64, f64)> {
+        let transformed_pixbuf_data = self.get_transformed_pixbuf_data()?;
+        let pixbuf_data = crop_pixbuf(&transformed_pixbuf_data, *self.crop_box.borrow());
+        let orig_width = pixbuf_data.width();
+        let orig_height = pixbuf_data.height();
+        let width = width.clamp(1, orig_width);
+        let height = height.clamp(1, orig_height);
+
+        let center_x = x_ratio * f64::from(orig_width);
+        let center_y = y_ratio * f64::from(orig_height);
+        let x = ((center_x - f64::from(width) / 2.0) as i32).clamp(0, orig_width - width);
+        let y = ((center_y - f64::from(height) / 2.0) as i32).clamp(0, orig_height - height);
+        let region = pixbuf_data.new_subpixbuf(x, y, width, height).copy()?;
+        let region = adjust_pixbuf(&region, *self.adjustments.borrow());
+        Some((region, center_x - f64::from(x), center_y - f64::from(y)))
+    }
+
+    // the original rotated, cropped and adjusted like the shown page, but not scaled.
+    fn get_export_pixbuf_data(&self) -> Option<gtk::gdk_pixbuf::Pixbuf> {
+        let transformed_pixbuf_data = self.get_transformed_pixbuf_data()?;
+        let pixbuf_data = crop_pixbuf(&transformed_pixbuf_data, *self.crop_box.borrow());
+        Some(adjust_pixbuf(&pixbuf_data, *self.adjustments.borrow()))
     }
 
     fn get_modified_width(&self) -> Option<i32> {
@@ -129,14 +440,84 @@ impl ImageContainerEx for ImageContainer {
         utils::get_value_with_option_from_ref_cell_option(&self.orig_pixbuf_data, |x| x.height())
     }
 
-    fn scale(&self, target_width: i32, target_height: i32, is_dual_mode: bool) {
-        if target_width < 1 || target_height < 1 {
-            return;
+    fn scale(
+        &self,
+        target_width: i32,
+        target_height: i32,
+        is_dual_mode: bool,
+        fit_mode: FitMode,
+        interpolation: Interpolation,
+        sharpen_amount: f64,
+    ) {
+        self.take_scaled_pixbuf_data();
+        if let Some(scaled_key) = self.get_scaled_key(
+            target_width,
+            target_height,
+            is_dual_mode,
+            fit_mode,
+            interpolation,
+            sharpen_amount,
+        ) {
+            self.scale_to_key(scaled_key);
         }
+    }
 
-        let Some(pixbuf_data) = self.orig_pixbuf_data.borrow().clone() else {
+    // the page is only queued, it is taken like a shown page once it is scaled.
+    fn prefetch(
+        &self,
+        target_width: i32,
+        target_height: i32,
+        is_dual_mode: bool,
+        fit_mode: FitMode,
+        interpolation: Interpolation,
+        sharpen_amount: f64,
+    ) {
+        self.take_scaled_pixbuf_data();
+        let Some(scaled_key) = self.get_scaled_key(
+            target_width,
+            target_height,
+            is_dual_mode,
+            fit_mode,
+            interpolation,
+            sharpen_amount,
+        ) else {
             return;
         };
+        if *self.scaled_key.borrow() == Some(scaled_key) || self.is_scaling_to_key(scaled_key) {
+            return;
+        }
+        self.start_scale_job(scaled_key);
+    }
+
+    // a pdf page rendered again keeps its size, so it is scaled like it was before.
+    fn set_rendered_pdf_pixmap(&self, pdf_pixmap: &PdfPixmap) {
+        let scaled_key =
+            (*self.scaled_key.borrow()).or_else(|| self.scale_job.borrow().as_ref().map(|v| v.key));
+        self.set_pixbuf_from_pdf_pixmap(pdf_pixmap);
+        if let Some(scaled_key) = scaled_key {
+            self.scale_to_key(scaled_key);
+        }
+    }
+}
+
+impl ImageContainer {
+    // the size the page is scaled to by the fit mode, with how it is scaled.
+    fn get_scaled_key(
+        &self,
+        target_width: i32,
+        target_height: i32,
+        is_dual_mode: bool,
+        fit_mode: FitMode,
+        interpolation: Interpolation,
+        sharpen_amount: f64,
+    ) -> Option<ScaledKey> {
+        if target_width < 1 || target_height < 1 {
+            return None;
+        }
+
+        // the transform is applied first, so a quarter turn swaps the aspect ratio.
+        let transformed_pixbuf_data = self.get_transformed_pixbuf_data()?;
+        let pixbuf_data = crop_pixbuf(&transformed_pixbuf_data, *self.crop_box.borrow());
 
         let width = pixbuf_data.width() as f64;
         let height = pixbuf_data.height() as f64;
@@ -153,18 +534,33 @@ impl ImageContainerEx for ImageContainer {
         let tmp_target_height = target_height as f64;
 
         let aspect_ratio = calc_aspect_raito(width, height);
-        let (result_width, result_height) = if is_dual_mode {
-            let result_height = (tmp_target_width / aspect_ratio.for_width).ceil() as i32;
-            if result_height > target_height {
-                (
-                    (tmp_target_height / aspect_ratio.for_height).ceil() as i32,
-                    target_height,
-                )
-            } else {
-                (target_width, result_height)
+        let (result_width, result_height) = match fit_mode {
+            FitMode::Page => {
+                let (result_width, result_height) =
+                    calc_fit_size(width, height, tmp_target_width, tmp_target_height);
+                (result_width.ceil() as i32, result_height.ceil() as i32)
             }
-        } else {
-            match picture_direction {
+            FitMode::Width => (
+                target_width,
+                (tmp_target_width / aspect_ratio.for_width).ceil() as i32,
+            ),
+            FitMode::Height => (
+                (tmp_target_height / aspect_ratio.for_height).ceil() as i32,
+                target_height,
+            ),
+            FitMode::Original => (pixbuf_data.width(), pixbuf_data.height()),
+            FitMode::Auto if is_dual_mode => {
+                let result_height = (tmp_target_width / aspect_ratio.for_width).ceil() as i32;
+                if result_height > target_height {
+                    (
+                        (tmp_target_height / aspect_ratio.for_height).ceil() as i32,
+                        target_height,
+                    )
+                } else {
+                    (target_width, result_height)
+                }
+            }
+            FitMode::Auto => match picture_direction {
                 PictureDirectionType::Vertical => (
                     (tmp_target_height / aspect_ratio.for_height).ceil() as i32,
                     target_height,
@@ -177,19 +573,316 @@ impl ImageContainerEx for ImageContainer {
                     (tmp_target_height / aspect_ratio.for_height).ceil() as i32,
                     target_height,
                 ),
-            }
+            },
         };
+        if result_width < 1 || result_height < 1 {
+            return None;
+        }
+
+        Some((result_width, result_height, interpolation, sharpen_amount))
+    }
 
-        let Some(scaled) = pixbuf_data.scale_simple(
+    fn is_scaling_to_key(&self, scaled_key: ScaledKey) -> bool {
+        self.scale_job
+            .borrow()
+            .as_ref()
+            .is_some_and(|v| v.key == scaled_key)
+    }
+
+    fn scale_to_key(&self, scaled_key: ScaledKey) {
+        if *self.scaled_key.borrow() == Some(scaled_key) {
+            return;
+        }
+        let is_queued = self.is_scaling_to_key(scaled_key);
+        if !is_queued {
+            self.start_scale_job(scaled_key);
+        }
+
+        // a page queued by prefetching has no preview of its size yet.
+        let (result_width, result_height, _, _) = scaled_key;
+        if is_queued
+            && self.get_modified_width() == Some(result_width)
+            && self.get_modified_height() == Some(result_height)
+        {
+            return;
+        }
+
+        // bilinear is cheap enough for the main thread and already has the final size.
+        let Some(transformed_pixbuf_data) = self.get_transformed_pixbuf_data() else {
+            return;
+        };
+        let pixbuf_data = crop_pixbuf(&transformed_pixbuf_data, *self.crop_box.borrow());
+        if let Some(preview) = pixbuf_data.scale_simple(
             result_width,
             result_height,
             gtk::gdk_pixbuf::InterpType::Bilinear,
-        ) else {
+        ) {
+            let _ = self
+                .modified_pixbuf_data
+                .replace(Some(adjust_pixbuf(&preview, *self.adjustments.borrow())));
+        }
+    }
+
+    // anything which changes the look of the page makes the scaled page and a running job stale.
+    fn reset_scaled_key(&self) {
+        let _ = self.scaled_key.replace(None);
+        self.cancel_scale_job();
+    }
+
+    fn cancel_scale_job(&self) {
+        if let Some(scale_job) = self.scale_job.replace(None) {
+            scale_job.is_cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn start_scale_job(&self, key: ScaledKey) {
+        self.cancel_scale_job();
+        let Some(transformed_pixbuf_data) = self.get_transformed_pixbuf_data() else {
             return;
         };
+        let (result_width, result_height, interpolation, sharpen_amount) = key;
+        let request = ScaleRequest {
+            bytes: transformed_pixbuf_data.read_pixel_bytes(),
+            width: transformed_pixbuf_data.width(),
+            height: transformed_pixbuf_data.height(),
+            rowstride: transformed_pixbuf_data.rowstride(),
+            n_channels: transformed_pixbuf_data.n_channels(),
+            has_alpha: transformed_pixbuf_data.has_alpha(),
+            crop_rect: self.crop_box.borrow().to_rect(
+                transformed_pixbuf_data.width(),
+                transformed_pixbuf_data.height(),
+            ),
+            result_width,
+            result_height,
+            interpolation,
+            sharpen_amount,
+            adjustments: *self.adjustments.borrow(),
+        };
+
+        let (tx, rx) = mpsc::channel::<Option<PixelBuffer>>();
+        let is_cancelled = Arc::new(AtomicBool::new(false));
+        let _ = get_scale_worker_sender().send(QueuedScaleJob {
+            request,
+            sender: tx,
+            is_cancelled: Arc::clone(&is_cancelled),
+        });
+
+        let _ = self.scale_job.replace(Some(ScaleJob {
+            key,
+            receiver: Arc::new(Mutex::new(rx)),
+            is_cancelled,
+        }));
+    }
+
+    fn get_transformed_pixbuf_data(&self) -> Option<gtk::gdk_pixbuf::Pixbuf> {
+        if let Some(v) = self.transformed_pixbuf_data.borrow().clone() {
+            return Some(v);
+        }
+
+        let pixbuf_data = self.orig_pixbuf_data.borrow().clone()?;
+        // the rotation already rendered into the original is taken back.
+        let native_rotation = self.native_rotation.borrow().unwrap_or(0);
+        let transform =
+            PageTransform::from_rotation(-native_rotation).then(*self.transform.borrow());
+        let transformed = apply_page_transform(&pixbuf_data, transform)?;
+        // pixels kept in bytes are shared with scaling threads without copying them again.
+        let transformed = gtk::gdk_pixbuf::Pixbuf::from_bytes(
+            &transformed.read_pixel_bytes(),
+            transformed.colorspace(),
+            transformed.has_alpha(),
+            transformed.bits_per_sample(),
+            transformed.width(),
+            transformed.height(),
+            transformed.rowstride(),
+        );
         let _ = self
-            .modified_pixbuf_data
-            .replace_with(|_| Some(scaled.clone()));
+            .transformed_pixbuf_data
+            .replace(Some(transformed.clone()));
+        Some(transformed)
+    }
+}
+
+struct QueuedScaleJob {
+    request: ScaleRequest,
+    sender: mpsc::Sender<Option<PixelBuffer>>,
+    is_cancelled: Arc<AtomicBool>,
+}
+
+// jobs wait for one of a few workers, those cancelled meanwhile by a newer size are dropped.
+fn get_scale_worker_sender() -> &'static mpsc::Sender<QueuedScaleJob> {
+    static SENDER: OnceLock<mpsc::Sender<QueuedScaleJob>> = OnceLock::new();
+    SENDER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel::<QueuedScaleJob>();
+        let rx = Arc::new(Mutex::new(rx));
+        for _ in 0..SCALE_WORKER_COUNT {
+            let rx = Arc::clone(&rx);
+            let _ = std::thread::spawn(move || {
+                loop {
+                    let received = rx.lock().unwrap().recv();
+                    let Ok(job) = received else {
+                        break;
+                    };
+                    if job.is_cancelled.load(Ordering::Relaxed) {
+                        continue;
+                    }
+                    let _ = job
+                        .sender
+                        .send(scale_pixels(&job.request, &job.is_cancelled));
+                }
+            });
+        }
+        tx
+    })
+}
+
+// runs in a scaling thread, so it only touches pixbufs it makes itself.
+fn scale_pixels(request: &ScaleRequest, is_cancelled: &AtomicBool) -> Option<PixelBuffer> {
+    let (x, y, crop_width, crop_height) = request.crop_rect;
+    let n_channels = request.n_channels as usize;
+    let mut scaled = match request.interpolation.as_interp_type() {
+        Some(interp_type) => {
+            let pixbuf_data = gtk::gdk_pixbuf::Pixbuf::from_bytes(
+                &request.bytes,
+                gtk::gdk_pixbuf::Colorspace::Rgb,
+                request.has_alpha,
+                8,
+                request.width,
+                request.height,
+                request.rowstride,
+            );
+            let scaled = pixbuf_data
+                .new_subpixbuf(x, y, crop_width, crop_height)
+                .scale_simple(request.result_width, request.result_height, interp_type)?;
+            PixelBuffer {
+                pixels: scaled.read_pixel_bytes().to_vec(),
+                width: scaled.width() as usize,
+                height: scaled.height() as usize,
+                rowstride: scaled.rowstride() as usize,
+                n_channels: scaled.n_channels() as usize,
+            }
+        }
+        None => {
+            let rowstride = request.rowstride as usize;
+            let offset = y as usize * rowstride + x as usize * n_channels;
+            let length = (crop_height as usize - 1) * rowstride + crop_width as usize * n_channels;
+            let src = PixelBuffer {
+                pixels: request.bytes.get(offset..offset + length)?.to_vec(),
+                width: crop_width as usize,
+                height: crop_height as usize,
+                rowstride,
+                n_channels,
+            };
+            image_scaler::scale_lanczos3(
+                &src,
+                request.result_width as usize,
+                request.result_height as usize,
+                is_cancelled,
+            )?
+        }
+    };
+    if is_cancelled.load(Ordering::Relaxed) {
+        return None;
+    }
+    if scaled.pixels.is_empty() {
+        return None;
+    }
+
+    // sharpening brings back the edges lost by shrinking, enlarged pages are left soft.
+    if request.result_width < crop_width || request.result_height < crop_height {
+        image_scaler::unsharp_mask(&mut scaled, request.sharpen_amount);
+    }
+    image_filter::apply_adjustments(
+        &mut scaled.pixels,
+        scaled.width,
+        scaled.height,
+        scaled.rowstride,
+        scaled.n_channels,
+        request.adjustments,
+    );
+    Some(scaled)
+}
+
+// nearest only reads the pixels it keeps, so even a large page is sampled quickly.
+fn create_background_sample(pixbuf: &gtk::gdk_pixbuf::Pixbuf) -> Option<gtk::gdk_pixbuf::Pixbuf> {
+    let width = BACKGROUND_SAMPLE_WIDTH.min(pixbuf.width());
+    let height = (i64::from(pixbuf.height()) * i64::from(width) / i64::from(pixbuf.width())).max(1);
+    pixbuf.scale_simple(
+        width,
+        i32::try_from(height).ok()?,
+        gtk::gdk_pixbuf::InterpType::Nearest,
+    )
+}
+
+fn create_pixbuf_from_pixel_buffer(buffer: PixelBuffer) -> gtk::gdk_pixbuf::Pixbuf {
+    gtk::gdk_pixbuf::Pixbuf::from_bytes(
+        &glib::Bytes::from_owned(buffer.pixels),
+        gtk::gdk_pixbuf::Colorspace::Rgb,
+        buffer.n_channels == 4,
+        8,
+        buffer.width as i32,
+        buffer.height as i32,
+        buffer.rowstride as i32,
+    )
+}
+
+// the preview is small, so adjusting it is cheaper than adjusting the original.
+fn adjust_pixbuf(
+    pixbuf_data: &gtk::gdk_pixbuf::Pixbuf,
+    adjustments: ImageAdjustments,
+) -> gtk::gdk_pixbuf::Pixbuf {
+    if adjustments.is_identity() {
+        return pixbuf_data.clone();
+    }
+
+    let mut pixels = pixbuf_data.read_pixel_bytes().to_vec();
+    let rowstride = pixbuf_data.rowstride();
+    image_filter::apply_adjustments(
+        &mut pixels,
+        pixbuf_data.width() as usize,
+        pixbuf_data.height() as usize,
+        rowstride as usize,
+        pixbuf_data.n_channels() as usize,
+        adjustments,
+    );
+    gtk::gdk_pixbuf::Pixbuf::from_bytes(
+        &glib::Bytes::from_owned(pixels),
+        pixbuf_data.colorspace(),
+        pixbuf_data.has_alpha(),
+        pixbuf_data.bits_per_sample(),
+        pixbuf_data.width(),
+        pixbuf_data.height(),
+        rowstride,
+    )
+}
+
+// the sub pixbuf shares the pixels, so cropping copies nothing.
+pub fn crop_pixbuf(
+    pixbuf_data: &gtk::gdk_pixbuf::Pixbuf,
+    crop_box: CropBox,
+) -> gtk::gdk_pixbuf::Pixbuf {
+    if crop_box.is_empty() {
+        return pixbuf_data.clone();
+    }
+
+    let (x, y, width, height) = crop_box.to_rect(pixbuf_data.width(), pixbuf_data.height());
+    pixbuf_data.new_subpixbuf(x, y, width, height)
+}
+
+pub fn apply_page_transform(
+    pixbuf_data: &gtk::gdk_pixbuf::Pixbuf,
+    transform: PageTransform,
+) -> Option<gtk::gdk_pixbuf::Pixbuf> {
+    let rotated = match transform.rotation {
+        90 => pixbuf_data.rotate_simple(gtk::gdk_pixbuf::PixbufRotation::Clockwise)?,
+        180 => pixbuf_data.rotate_simple(gtk::gdk_pixbuf::PixbufRotation::Upsidedown)?,
+        270 => pixbuf_data.rotate_simple(gtk::gdk_pixbuf::PixbufRotation::Counterclockwise)?,
+        _ => pixbuf_data.clone(),
+    };
+
+    if transform.is_flipped {
+        rotated.flip(true)
+    } else {
+        Some(rotated)
     }
 }
 
@@ -204,73 +897,122 @@ pub fn read_bytes_from_file_path(path_str: &str) -> Option<Vec<u8>> {
     }
 }
 
-pub fn create_pixbuf_from_bytes(bytes: &[u8]) -> Option<gtk::gdk_pixbuf::Pixbuf> {
+// data the loader cannot read is an error of this page only, the caller decides what to show.
+pub fn create_pixbuf_from_bytes(bytes: &[u8]) -> Result<gtk::gdk_pixbuf::Pixbuf, glib::Error> {
     let pixbuf_loader = gtk::gdk_pixbuf::PixbufLoader::new();
-    pixbuf_loader.write(bytes).unwrap();
-    // let result_of_pixbuf_loader_write = pixbuf_loader.write(bytes);
-    // if result_of_pixbuf_loader_write.is_err() {
-    //     println!("{}", result_of_pixbuf_loader_write.err().unwrap());
-    //     return None;
-    // }
-
-    let Some(pixbuf_data) = pixbuf_loader.pixbuf() else {
-        return None;
-    };
-
-    let result_of_loder_close = pixbuf_loader.close();
-    if result_of_loder_close.is_err() {
-        return None;
-    }
+    let result_of_pixbuf_loader_write = pixbuf_loader.write(bytes);
+    // the loader is closed after a failed write too, so it is not left open when dropped.
+    let result_of_loader_close = pixbuf_loader.close();
+    result_of_pixbuf_loader_write?;
+    result_of_loader_close?;
 
-    Some(pixbuf_data)
+    pixbuf_loader.pixbuf().ok_or_else(|| {
+        glib::Error::new(
+            gtk::gdk_pixbuf::PixbufError::CorruptImage,
+            "no image in the data",
+        )
+    })
 }
 
-#[allow(dead_code)]
-pub fn create_pixbuf_from_file_path(path_str: String) -> Option<gtk::gdk_pixbuf::Pixbuf> {
-    let Some(buf) = read_bytes_from_file_path(&path_str) else {
-        return None;
-    };
-
-    let pixbuf_loader = gtk::gdk_pixbuf::PixbufLoader::new();
-    let result_of_pixbuf_loader_write = pixbuf_loader.write(&buf);
-    if result_of_pixbuf_loader_write.is_err() {
-        return None;
-    };
-
-    let Some(pixbuf_data) = pixbuf_loader.pixbuf() else {
-        return None;
-    };
+// a grey page with the name of the entry and the error, drawn with cairo so it works in the
+// loader thread.
+pub fn create_placeholder_pixbuf(
+    file_name: &str,
+    message: &str,
+) -> Option<gtk::gdk_pixbuf::Pixbuf> {
+    let mut surface = gtk::cairo::ImageSurface::create(
+        gtk::cairo::Format::Rgb24,
+        PLACEHOLDER_WIDTH,
+        PLACEHOLDER_HEIGHT,
+    )
+    .ok()?;
+    {
+        let ctx = gtk::cairo::Context::new(&surface).ok()?;
+        ctx.set_source_rgb(0.25, 0.25, 0.25);
+        ctx.paint().ok()?;
 
-    let result_of_loader_close = pixbuf_loader.close();
-    if result_of_loader_close.is_err() {
-        return None;
+        ctx.select_font_face(
+            "Sans",
+            gtk::cairo::FontSlant::Normal,
+            gtk::cairo::FontWeight::Normal,
+        );
+        ctx.set_font_size(PLACEHOLDER_FONT_SIZE);
+        ctx.set_source_rgb(0.9, 0.9, 0.9);
+        let max_width = f64::from(PLACEHOLDER_WIDTH) - PLACEHOLDER_MARGIN * 2.0;
+        let lines = ["This page cannot be shown.", "", file_name, "", message]
+            .iter()
+            .flat_map(|v| wrap_text(&ctx, v, max_width))
+            .collect::<Vec<String>>();
+        let line_height = PLACEHOLDER_FONT_SIZE * 1.5;
+        let mut y = (f64::from(PLACEHOLDER_HEIGHT) - line_height * lines.len() as f64) / 2.0;
+        for line in lines {
+            y += line_height;
+            ctx.move_to(PLACEHOLDER_MARGIN, y);
+            let _ = ctx.show_text(&line);
+        }
     }
+    surface.flush();
 
-    Some(pixbuf_data)
+    // cairo keeps rgb24 as native endian 32 bit pixels, gdk-pixbuf wants rgb bytes.
+    let stride = surface.stride() as usize;
+    let data = surface.data().ok()?;
+    let mut pixels = Vec::with_capacity((PLACEHOLDER_WIDTH * PLACEHOLDER_HEIGHT * 3) as usize);
+    for row in data.chunks_exact(stride) {
+        for pixel in row[..PLACEHOLDER_WIDTH as usize * 4].chunks_exact(4) {
+            let value = u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+            pixels.extend_from_slice(&[(value >> 16) as u8, (value >> 8) as u8, value as u8]);
+        }
+    }
+    Some(gtk::gdk_pixbuf::Pixbuf::from_bytes(
+        &glib::Bytes::from_owned(pixels),
+        gtk::gdk_pixbuf::Colorspace::Rgb,
+        false,
+        8,
+        PLACEHOLDER_WIDTH,
+        PLACEHOLDER_HEIGHT,
+        PLACEHOLDER_WIDTH * 3,
+    ))
 }
 
-pub fn create_pixbuf_from_file(file: &gio::File) -> Option<gtk::gdk_pixbuf::Pixbuf> {
-    let Ok((bytes, _s)) = file.load_bytes(gio::Cancellable::NONE) else {
-        return None;
+// words go to the next line when they do not fit, a long word is broken by characters.
+fn wrap_text(ctx: &gtk::cairo::Context, text: &str, max_width: f64) -> Vec<String> {
+    let fits = |v: &str| {
+        ctx.text_extents(v)
+            .is_ok_and(|v| v.x_advance() <= max_width)
     };
-
-    let buf: Vec<u8> = bytes.to_vec();
-    let pixbuf_loader = gtk::gdk_pixbuf::PixbufLoader::new();
-    let result_of_pixbuf_loader_write = pixbuf_loader.write(&buf);
-    if result_of_pixbuf_loader_write.is_err() {
-        return None;
-    };
-
-    let Some(pixbuf_data) = pixbuf_loader.pixbuf() else {
-        return None;
-    };
-
-    let result_of_loader_close = pixbuf_loader.close();
-    if result_of_loader_close.is_err() {
-        return None;
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split(' ') {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", line, word)
+        };
+        if fits(&candidate) {
+            line = candidate;
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        for c in word.chars() {
+            line.push(c);
+            if !fits(&line) && line.chars().count() > 1 {
+                line.pop();
+                lines.push(std::mem::replace(&mut line, c.to_string()));
+            }
+        }
     }
+    lines.push(line);
+    lines
+}
 
-    Some(pixbuf_data)
+#[allow(dead_code)]
+pub fn create_pixbuf_from_file_path(path_str: String) -> Option<gtk::gdk_pixbuf::Pixbuf> {
+    let buf = read_bytes_from_file_path(&path_str)?;
+    create_pixbuf_from_bytes(&buf)
+        .inspect_err(|e| eprintln!("{}: {}", path_str, e))
+        .ok()
 }
 
 fn calc_aspect_raito(width: f64, height: f64) -> AspectRatioCollection {
@@ -282,3 +1024,87 @@ fn calc_aspect_raito(width: f64, height: f64) -> AspectRatioCollection {
         for_height,
     }
 }
+
+// returns size which fits into target size with keeping aspect ratio.
+pub fn calc_fit_size(width: f64, height: f64, target_width: f64, target_height: f64) -> (f64, f64) {
+    let aspect_ratio = calc_aspect_raito(width, height);
+    let result_height = target_width / aspect_ratio.for_width;
+    if result_height > target_height {
+        (target_height / aspect_ratio.for_height, target_height)
+    } else {
+        (target_width, result_height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image_loader;
+
+    // the second page is a jpeg cut off inside its header, in a book and in a directory.
+    const TRUNCATED_PAGE_FIXTURE: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/truncated_page.cbz"
+    );
+    const TRUNCATED_PAGE_DIR_FIXTURE: &str =
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/truncated_page");
+
+    fn assert_placeholder_is_shown(image_container: &ImageContainer, e: &glib::Error) {
+        assert!(image_container.get_orig_pixbuf_data().is_none());
+        image_container.set_placeholder_pixbuf(&e.to_string());
+        assert!(image_container.get_orig_width().is_some_and(|v| v > 0));
+        assert!(image_container.get_modified_pixbuf_data().is_some());
+    }
+
+    #[test]
+    fn truncated_page_in_book_does_not_stop_the_book() {
+        let extracted =
+            image_loader::load_from_compressed_file_to_memory(TRUNCATED_PAGE_FIXTURE).unwrap();
+        let file_names: Vec<&str> = extracted
+            .iter()
+            .map(|v| v.file_info.file_name.as_str())
+            .collect();
+        assert_eq!(file_names, ["page01.png", "page02.jpg", "page03.png"]);
+
+        let is_decoded: Vec<bool> = extracted
+            .iter()
+            .map(|v| create_pixbuf_from_bytes(&v.value).is_ok())
+            .collect();
+        assert_eq!(is_decoded, [true, false, true]);
+
+        let image_container = ImageContainer::default();
+        image_container.set_file_name(&extracted[1].file_info.file_name);
+        let e = image_container
+            .set_pixbuf_from_bytes(&extracted[1].value)
+            .unwrap_err();
+        assert_placeholder_is_shown(&image_container, &e);
+    }
+
+    #[test]
+    fn truncated_page_in_directory_does_not_stop_the_book() {
+        let is_decoded: Vec<bool> = ["page01.png", "page02.jpg", "page03.png"]
+            .iter()
+            .map(|v| {
+                let file = gio::File::for_path(format!("{}/{}", TRUNCATED_PAGE_DIR_FIXTURE, v));
+                ImageContainer::default()
+                    .set_pixbuf_from_file(&file)
+                    .is_ok()
+            })
+            .collect();
+        assert_eq!(is_decoded, [true, false, true]);
+
+        let image_container = ImageContainer::default();
+        image_container.set_file_name("page02.jpg");
+        let file = gio::File::for_path(format!("{}/page02.jpg", TRUNCATED_PAGE_DIR_FIXTURE));
+        let e = image_container.set_pixbuf_from_file(&file).unwrap_err();
+        assert_placeholder_is_shown(&image_container, &e);
+
+        let missing_file =
+            gio::File::for_path(format!("{}/missing.png", TRUNCATED_PAGE_DIR_FIXTURE));
+        assert!(
+            ImageContainer::default()
+                .set_pixbuf_from_file(&missing_file)
+                .is_err()
+        );
+    }
+}
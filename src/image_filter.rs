@@ -0,0 +1,297 @@
+pub const MIN_BRIGHTNESS: f64 = -1.0;
+pub const MAX_BRIGHTNESS: f64 = 1.0;
+pub const MAX_CONTRAST: f64 = 3.0;
+pub const MIN_GAMMA: f64 = 0.2;
+pub const MAX_GAMMA: f64 = 5.0;
+pub const MAX_SATURATION: f64 = 2.0;
+
+// colour adjustments of the shown pages, the default changes nothing.
+// saturation 0 is grayscale, and invert with a little sepia is a night mode.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImageAdjustments {
+    // added to every channel, -1 is black and 1 is white.
+    pub brightness: f64,
+    // distance from middle grey is multiplied by this.
+    pub contrast: f64,
+    pub gamma: f64,
+    pub saturation: f64,
+    // mix of the sepia tone, from 0 to 1.
+    pub sepia: f64,
+    pub is_inverted: bool,
+}
+
+impl Default for ImageAdjustments {
+    fn default() -> Self {
+        ImageAdjustments {
+            brightness: 0.0,
+            contrast: 1.0,
+            gamma: 1.0,
+            saturation: 1.0,
+            sepia: 0.0,
+            is_inverted: false,
+        }
+    }
+}
+
+fn clamp_or(value: f64, min: f64, max: f64, default: f64) -> f64 {
+    if value.is_finite() {
+        value.clamp(min, max)
+    } else {
+        default
+    }
+}
+
+impl ImageAdjustments {
+    pub fn is_identity(self) -> bool {
+        self.clamped() == ImageAdjustments::default()
+    }
+
+    pub fn clamped(self) -> Self {
+        ImageAdjustments {
+            brightness: clamp_or(self.brightness, MIN_BRIGHTNESS, MAX_BRIGHTNESS, 0.0),
+            contrast: clamp_or(self.contrast, 0.0, MAX_CONTRAST, 1.0),
+            gamma: clamp_or(self.gamma, MIN_GAMMA, MAX_GAMMA, 1.0),
+            saturation: clamp_or(self.saturation, 0.0, MAX_SATURATION, 1.0),
+            sepia: clamp_or(self.sepia, 0.0, 1.0, 0.0),
+            is_inverted: self.is_inverted,
+        }
+    }
+
+    // saved as "brightness;contrast;gamma;saturation;sepia;invert".
+    pub fn to_text(self) -> String {
+        format!(
+            "{};{};{};{};{};{}",
+            self.brightness,
+            self.contrast,
+            self.gamma,
+            self.saturation,
+            self.sepia,
+            u8::from(self.is_inverted)
+        )
+    }
+
+    pub fn from_text(text: &str) -> Option<Self> {
+        let values = text
+            .split(';')
+            .map(|v| v.trim().parse::<f64>().ok())
+            .collect::<Option<Vec<f64>>>()?;
+        let [brightness, contrast, gamma, saturation, sepia, is_inverted] = values[..] else {
+            return None;
+        };
+        Some(
+            ImageAdjustments {
+                brightness,
+                contrast,
+                gamma,
+                saturation,
+                sepia,
+                is_inverted: is_inverted != 0.0,
+            }
+            .clamped(),
+        )
+    }
+
+    // brightness, contrast and gamma change each channel alone, so they are looked up.
+    fn build_lookup_table(self) -> [u8; 256] {
+        let mut lookup_table = [0u8; 256];
+        for (i, v) in lookup_table.iter_mut().enumerate() {
+            let value = i as f64 / 255.0 + self.brightness;
+            let value = ((value - 0.5) * self.contrast + 0.5).clamp(0.0, 1.0);
+            let value = value.powf(1.0 / self.gamma);
+            *v = to_channel(value * 255.0);
+        }
+        lookup_table
+    }
+}
+
+fn to_channel(value: f64) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+// rec. 601 luma, the same weights as most grayscale conversions.
+fn luma(rgb: [f64; 3]) -> f64 {
+    0.299 * rgb[0] + 0.587 * rgb[1] + 0.114 * rgb[2]
+}
+
+fn sepia_tone(rgb: [f64; 3]) -> [f64; 3] {
+    [
+        0.393 * rgb[0] + 0.769 * rgb[1] + 0.189 * rgb[2],
+        0.349 * rgb[0] + 0.686 * rgb[1] + 0.168 * rgb[2],
+        0.272 * rgb[0] + 0.534 * rgb[1] + 0.131 * rgb[2],
+    ]
+}
+
+// pixels are changed in place, rows may be padded and alpha is kept.
+// grayscale pixels with one or two channels only get the lookup and the inversion.
+pub fn apply_adjustments(
+    pixels: &mut [u8],
+    width: usize,
+    height: usize,
+    rowstride: usize,
+    n_channels: usize,
+    adjustments: ImageAdjustments,
+) {
+    let adjustments = adjustments.clamped();
+    if adjustments.is_identity() || n_channels == 0 || rowstride < width * n_channels {
+        return;
+    }
+
+    let lookup_table = adjustments.build_lookup_table();
+    let is_colour = n_channels >= 3;
+    let is_saturation_changed = is_colour && adjustments.saturation != 1.0;
+    let is_sepia = is_colour && adjustments.sepia > 0.0;
+    let colour_channels = n_channels.min(3);
+
+    for row in pixels.chunks_mut(rowstride).take(height) {
+        for pixel in row.chunks_exact_mut(n_channels).take(width) {
+            for v in pixel.iter_mut().take(colour_channels) {
+                *v = lookup_table[*v as usize];
+            }
+
+            if is_saturation_changed || is_sepia {
+                let mut rgb = [pixel[0] as f64, pixel[1] as f64, pixel[2] as f64];
+                if is_saturation_changed {
+                    let gray = luma(rgb);
+                    rgb = rgb.map(|v| gray + (v - gray) * adjustments.saturation);
+                }
+                if is_sepia {
+                    let sepia = sepia_tone(rgb);
+                    for (v, s) in rgb.iter_mut().zip(sepia) {
+                        *v += (s - *v) * adjustments.sepia;
+                    }
+                }
+                for (v, value) in pixel.iter_mut().zip(rgb) {
+                    *v = to_channel(value);
+                }
+            }
+
+            if adjustments.is_inverted {
+                for v in pixel.iter_mut().take(colour_channels) {
+                    *v = 255 - *v;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply_rgb(pixels: &[u8], adjustments: ImageAdjustments) -> Vec<u8> {
+        let mut pixels = pixels.to_vec();
+        let width = pixels.len() / 3;
+        apply_adjustments(&mut pixels, width, 1, width * 3, 3, adjustments);
+        pixels
+    }
+
+    #[test]
+    fn default_changes_nothing() {
+        let pixels = [0, 1, 2, 127, 128, 129, 253, 254, 255];
+        assert_eq!(apply_rgb(&pixels, ImageAdjustments::default()), pixels);
+        assert!(ImageAdjustments::default().is_identity());
+    }
+
+    #[test]
+    fn brightness_shifts_and_clamps() {
+        let adjustments = ImageAdjustments {
+            brightness: 0.2,
+            ..Default::default()
+        };
+        assert_eq!(apply_rgb(&[0, 100, 250], adjustments), [51, 151, 255]);
+    }
+
+    #[test]
+    fn contrast_spreads_from_middle_grey() {
+        let adjustments = ImageAdjustments {
+            contrast: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(apply_rgb(&[0, 255, 255], adjustments), [64, 191, 191]);
+        let adjustments = ImageAdjustments {
+            contrast: 3.0,
+            ..Default::default()
+        };
+        assert_eq!(apply_rgb(&[40, 215, 255], adjustments), [0, 255, 255]);
+        let adjustments = ImageAdjustments {
+            contrast: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(apply_rgb(&[0, 128, 255], adjustments), [128, 128, 128]);
+    }
+
+    #[test]
+    fn gamma_brightens_midtones_only() {
+        let adjustments = ImageAdjustments {
+            gamma: 2.0,
+            ..Default::default()
+        };
+        // sqrt(64 / 255) * 255 is 127.75.
+        assert_eq!(apply_rgb(&[0, 64, 255], adjustments), [0, 128, 255]);
+    }
+
+    #[test]
+    fn grayscale_uses_luma() {
+        let adjustments = ImageAdjustments {
+            saturation: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(apply_rgb(&[255, 0, 0], adjustments), [76, 76, 76]);
+        assert_eq!(apply_rgb(&[0, 255, 0], adjustments), [150, 150, 150]);
+    }
+
+    #[test]
+    fn sepia_tints_white_and_keeps_black() {
+        let adjustments = ImageAdjustments {
+            sepia: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            apply_rgb(&[0, 0, 0, 100, 100, 100], adjustments),
+            [0, 0, 0, 135, 120, 94]
+        );
+    }
+
+    #[test]
+    fn invert_keeps_alpha_and_padding() {
+        let adjustments = ImageAdjustments {
+            is_inverted: true,
+            ..Default::default()
+        };
+        // two rgba pixels in each row and two bytes of padding.
+        let mut pixels = vec![
+            0, 10, 255, 200, 100, 100, 100, 0, 7, 7, //
+            255, 255, 255, 255, 1, 2, 3, 4, 7, 7,
+        ];
+        apply_adjustments(&mut pixels, 2, 2, 10, 4, adjustments);
+        assert_eq!(
+            pixels,
+            vec![
+                255, 245, 0, 200, 155, 155, 155, 0, 7, 7, //
+                0, 0, 0, 255, 254, 253, 252, 4, 7, 7,
+            ]
+        );
+    }
+
+    #[test]
+    fn text_round_trips() {
+        let adjustments = ImageAdjustments {
+            brightness: -0.25,
+            contrast: 1.5,
+            gamma: 0.8,
+            saturation: 0.0,
+            sepia: 0.3,
+            is_inverted: true,
+        };
+        assert_eq!(
+            ImageAdjustments::from_text(&adjustments.to_text()),
+            Some(adjustments)
+        );
+        assert_eq!(ImageAdjustments::from_text(""), None);
+        assert_eq!(ImageAdjustments::from_text("0;1;1;1;0"), None);
+        assert_eq!(
+            ImageAdjustments::from_text("9;1;1;1;0;0").map(|v| v.brightness),
+            Some(MAX_BRIGHTNESS)
+        );
+    }
+}
@@ -0,0 +1,353 @@
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const LANCZOS_LOBES: f64 = 3.0;
+pub const MAX_SHARPEN_AMOUNT: f64 = 2.0;
+
+// pixels of an image with padded rows, as gdk-pixbuf keeps them.
+pub struct PixelBuffer {
+    pub pixels: Vec<u8>,
+    pub width: usize,
+    pub height: usize,
+    pub rowstride: usize,
+    pub n_channels: usize,
+}
+
+impl PixelBuffer {
+    fn is_valid(&self) -> bool {
+        self.width > 0
+            && self.height > 0
+            && self.n_channels > 0
+            && self.rowstride >= self.width * self.n_channels
+            && self.pixels.len()
+                >= self.rowstride * (self.height - 1) + self.width * self.n_channels
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+fn lanczos3(x: f64) -> f64 {
+    if x.abs() < LANCZOS_LOBES {
+        sinc(x) * sinc(x / LANCZOS_LOBES)
+    } else {
+        0.0
+    }
+}
+
+// source pixels and their weights for each destination pixel along one axis.
+// the filter is stretched when shrinking, so every source pixel is taken into account,
+// and pixels beyond the border repeat the edge.
+struct Contributions {
+    indices: Vec<usize>,
+    weights: Vec<f64>,
+}
+
+fn calc_contributions(src_size: usize, dst_size: usize) -> Vec<Contributions> {
+    let scale = src_size as f64 / dst_size as f64;
+    let filter_scale = scale.max(1.0);
+    let support = LANCZOS_LOBES * filter_scale;
+
+    (0..dst_size)
+        .map(|i| {
+            let center = (i as f64 + 0.5) * scale;
+            let first = (center - support).floor() as i64;
+            let last = (center + support).ceil() as i64;
+            let (indices, mut weights): (Vec<usize>, Vec<f64>) = (first..last)
+                .map(|v| {
+                    let weight = lanczos3((v as f64 + 0.5 - center) / filter_scale);
+                    (v.clamp(0, src_size as i64 - 1) as usize, weight)
+                })
+                .filter(|(_, weight)| *weight != 0.0)
+                .unzip();
+            let sum: f64 = weights.iter().sum();
+            if sum != 0.0 {
+                weights.iter_mut().for_each(|v| *v /= sum);
+            }
+            Contributions { indices, weights }
+        })
+        .collect()
+}
+
+fn to_channel(value: f64) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+fn scale_row(src: &PixelBuffer, y: usize, horizontal: &[Contributions], dst_row: &mut [f64]) {
+    let n_channels = src.n_channels;
+    let src_row = &src.pixels[y * src.rowstride..];
+    dst_row.iter_mut().for_each(|v| *v = 0.0);
+    for (x, contributions) in horizontal.iter().enumerate() {
+        for (index, weight) in contributions.indices.iter().zip(&contributions.weights) {
+            let offset = index * n_channels;
+            for c in 0..n_channels {
+                dst_row[x * n_channels + c] += src_row[offset + c] as f64 * weight;
+            }
+        }
+    }
+}
+
+// horizontal then vertical pass, the result has no row padding.
+// channels are filtered alone, alpha included.
+// only the rows scaled horizontally which the next destination rows need are kept,
+// and none is returned once it is cancelled.
+pub fn scale_lanczos3(
+    src: &PixelBuffer,
+    dst_width: usize,
+    dst_height: usize,
+    is_cancelled: &AtomicBool,
+) -> Option<PixelBuffer> {
+    let n_channels = src.n_channels;
+    if !src.is_valid() || dst_width == 0 || dst_height == 0 {
+        return Some(PixelBuffer {
+            pixels: vec![],
+            width: 0,
+            height: 0,
+            rowstride: 0,
+            n_channels,
+        });
+    }
+
+    let horizontal = calc_contributions(src.width, dst_width);
+    let vertical = calc_contributions(src.height, dst_height);
+    let rowstride = dst_width * n_channels;
+    let mut pixels = vec![0u8; rowstride * dst_height];
+    let mut sums = vec![0.0f64; rowstride];
+    // rows from first_row_index onwards, the first needed row only moves down.
+    // dropped rows are kept as spare ones, so rows are not allocated again.
+    let mut rows: VecDeque<Vec<f64>> = VecDeque::new();
+    let mut spare_rows: Vec<Vec<f64>> = vec![];
+    let mut first_row_index = 0;
+    for (y, contributions) in vertical.iter().enumerate() {
+        if is_cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let (Some(min_index), Some(max_index)) = (
+            contributions.indices.iter().min(),
+            contributions.indices.iter().max(),
+        ) else {
+            continue;
+        };
+        while first_row_index < *min_index
+            && let Some(row) = rows.pop_front()
+        {
+            spare_rows.push(row);
+            first_row_index += 1;
+        }
+        if rows.is_empty() {
+            first_row_index = *min_index;
+        }
+        while first_row_index + rows.len() <= *max_index {
+            let mut row = spare_rows.pop().unwrap_or_else(|| vec![0.0f64; rowstride]);
+            scale_row(src, first_row_index + rows.len(), &horizontal, &mut row);
+            rows.push_back(row);
+        }
+
+        sums.iter_mut().for_each(|v| *v = 0.0);
+        for (index, weight) in contributions.indices.iter().zip(&contributions.weights) {
+            let row = &rows[index - first_row_index];
+            for (sum, value) in sums.iter_mut().zip(row) {
+                *sum += value * weight;
+            }
+        }
+        for (v, sum) in pixels[y * rowstride..][..rowstride].iter_mut().zip(&sums) {
+            *v = to_channel(*sum);
+        }
+    }
+
+    Some(PixelBuffer {
+        pixels,
+        width: dst_width,
+        height: dst_height,
+        rowstride,
+        n_channels,
+    })
+}
+
+// each colour channel is pushed away from its 3x3 gaussian blur by the amount,
+// so edges softened by downscaling look crisp again. alpha is kept.
+pub fn unsharp_mask(buffer: &mut PixelBuffer, amount: f64) {
+    if !buffer.is_valid()
+        || amount.is_nan()
+        || amount <= 0.0
+        || buffer.width < 3
+        || buffer.height < 3
+    {
+        return;
+    }
+
+    let amount = amount.min(MAX_SHARPEN_AMOUNT);
+    let width = buffer.width;
+    let height = buffer.height;
+    let rowstride = buffer.rowstride;
+    let n_channels = buffer.n_channels;
+    let colour_channels = n_channels.min(3);
+    let src = buffer.pixels.clone();
+    let get = |x: usize, y: usize, c: usize| src[y * rowstride + x * n_channels + c] as f64;
+
+    // border pixels have no full neighbourhood and are left as they are.
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            for c in 0..colour_channels {
+                let blur = (get(x - 1, y - 1, c)
+                    + 2.0 * get(x, y - 1, c)
+                    + get(x + 1, y - 1, c)
+                    + 2.0 * get(x - 1, y, c)
+                    + 4.0 * get(x, y, c)
+                    + 2.0 * get(x + 1, y, c)
+                    + get(x - 1, y + 1, c)
+                    + 2.0 * get(x, y + 1, c)
+                    + get(x + 1, y + 1, c))
+                    / 16.0;
+                let value = get(x, y, c);
+                buffer.pixels[y * rowstride + x * n_channels + c] =
+                    to_channel(value + (value - blur) * amount);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_gray(width: usize, height: usize, f: impl Fn(usize, usize) -> u8) -> PixelBuffer {
+        let mut pixels = vec![];
+        for y in 0..height {
+            for x in 0..width {
+                pixels.push(f(x, y));
+            }
+        }
+        PixelBuffer {
+            pixels,
+            width,
+            height,
+            rowstride: width,
+            n_channels: 1,
+        }
+    }
+
+    #[test]
+    fn flat_colour_stays_flat() {
+        let src = PixelBuffer {
+            pixels: [10, 200, 30, 255].repeat(12 * 9),
+            width: 12,
+            height: 9,
+            rowstride: 48,
+            n_channels: 4,
+        };
+        for (width, height) in [(5, 4), (12, 9), (30, 20)] {
+            let dst = scale_lanczos3(&src, width, height, &AtomicBool::new(false)).unwrap();
+            assert_eq!(
+                (dst.width, dst.height, dst.rowstride),
+                (width, height, width * 4)
+            );
+            assert!(dst.pixels.chunks(4).all(|v| v == [10, 200, 30, 255]));
+        }
+    }
+
+    #[test]
+    fn halving_averages_pairs() {
+        // columns of 0 and 200 become a flat 100 grey away from the borders.
+        let src = make_gray(16, 4, |x, _| if x % 2 == 0 { 0 } else { 200 });
+        let dst = scale_lanczos3(&src, 8, 2, &AtomicBool::new(false)).unwrap();
+        for row in dst.pixels.chunks(8) {
+            assert!(row[2..6].iter().all(|v| v.abs_diff(100) <= 2), "{:?}", row);
+        }
+    }
+
+    #[test]
+    fn edge_stays_sharp_when_downscaled() {
+        let src = make_gray(40, 1, |x, _| if x < 20 { 0 } else { 255 });
+        let dst = scale_lanczos3(&src, 10, 1, &AtomicBool::new(false)).unwrap();
+        // the step spans two pixels, lanczos rings a little beside it.
+        assert!(dst.pixels[..4].iter().all(|v| *v <= 8), "{:?}", dst.pixels);
+        assert!(
+            dst.pixels[6..].iter().all(|v| *v >= 247),
+            "{:?}",
+            dst.pixels
+        );
+        assert!(dst.pixels[4] < 128 && dst.pixels[5] > 128);
+    }
+
+    #[test]
+    fn padded_rows_are_skipped() {
+        let src = PixelBuffer {
+            pixels: vec![50, 50, 50, 9, 9, 50, 50, 50, 9, 9],
+            width: 3,
+            height: 2,
+            rowstride: 5,
+            n_channels: 1,
+        };
+        let is_cancelled = AtomicBool::new(false);
+        let dst = scale_lanczos3(&src, 6, 4, &is_cancelled).unwrap();
+        assert!(dst.pixels.iter().all(|v| *v == 50));
+        assert!(
+            scale_lanczos3(&src, 0, 4, &is_cancelled)
+                .unwrap()
+                .pixels
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn tall_page_matches_every_row() {
+        // rows are dropped and scaled again while the window moves down the page.
+        let src = make_gray(3, 200, |_, y| (y % 256) as u8);
+        let dst = scale_lanczos3(&src, 3, 50, &AtomicBool::new(false)).unwrap();
+        let columns: Vec<&[u8]> = dst.pixels.chunks(3).collect();
+        assert!(columns.iter().all(|v| v[0] == v[1] && v[1] == v[2]));
+        assert!(
+            columns
+                .windows(2)
+                .skip(1)
+                .take(47)
+                .all(|v| v[0][0] < v[1][0])
+        );
+    }
+
+    #[test]
+    fn cancelled_scaling_returns_nothing() {
+        let src = make_gray(8, 8, |x, y| (x * y) as u8);
+        assert!(scale_lanczos3(&src, 4, 4, &AtomicBool::new(true)).is_none());
+    }
+
+    #[test]
+    fn unsharp_mask_keeps_flat_area_and_raises_edges() {
+        let mut flat = make_gray(5, 5, |_, _| 90);
+        unsharp_mask(&mut flat, 1.0);
+        assert!(flat.pixels.iter().all(|v| *v == 90));
+
+        let mut edge = make_gray(6, 3, |x, _| if x < 3 { 100 } else { 150 });
+        unsharp_mask(&mut edge, 1.0);
+        let middle_row = &edge.pixels[6..12];
+        assert!(
+            middle_row[2] < 100 && middle_row[3] > 150,
+            "{:?}",
+            middle_row
+        );
+        assert_eq!(middle_row[0], 100);
+        assert_eq!(&edge.pixels[..6], &[100, 100, 100, 150, 150, 150]);
+    }
+
+    #[test]
+    fn unsharp_mask_keeps_alpha() {
+        let mut buffer = PixelBuffer {
+            pixels: (0..9).flat_map(|v| [v * 20, 0, 255, 77]).collect(),
+            width: 3,
+            height: 3,
+            rowstride: 12,
+            n_channels: 4,
+        };
+        unsharp_mask(&mut buffer, 2.0);
+        assert!(buffer.pixels.chunks(4).all(|v| v[3] == 77));
+        unsharp_mask(&mut buffer, 0.0);
+    }
+}
@@ -0,0 +1,486 @@
+use gtk::gdk;
+use gtk::glib;
+use gtk::prelude::GtkApplicationExt;
+use gtk4 as gtk;
+
+use crate::utils;
+
+const KEY_BINDINGS_FILE_NAME: &str = "keybindings.ini";
+const KEY_BINDINGS_GROUP: &str = "keybindings";
+
+pub struct KeyBinding {
+    pub action_name: &'static str,
+    pub group: &'static str,
+    pub title: &'static str,
+    pub accels: Vec<String>,
+}
+
+// action name, group and title for the shortcuts window, default accelerators.
+const DEFAULT_KEY_BINDINGS: &[(&str, &str, &str, &[&str])] = &[
+    (
+        "page_left",
+        "Navigation",
+        "Move to Left",
+        &["h", "Left", "<Control>b"],
+    ),
+    (
+        "page_right",
+        "Navigation",
+        "Move to Right",
+        &["l", "Right", "<Control>f"],
+    ),
+    ("next_page", "Navigation", "Next Page", &[]),
+    ("previous_page", "Navigation", "Previous Page", &[]),
+    ("first_page", "Navigation", "First Page", &["Home"]),
+    ("last_page", "Navigation", "Last Page", &["End"]),
+    ("go_to_page", "Navigation", "Go to Page", &["<Control>G"]),
+    ("page_overview", "Navigation", "Page Overview", &["Tab"]),
+    (
+        "next_book",
+        "Navigation",
+        "Next Book",
+        &["<Control>Page_Down"],
+    ),
+    (
+        "previous_book",
+        "Navigation",
+        "Previous Book",
+        &["<Control>Page_Up"],
+    ),
+    ("fullscreen", "View", "Fullscreen", &["<Alt>Return", "F11"]),
+    ("leave_fullscreen", "View", "Leave Fullscreen", &["Escape"]),
+    ("file_open", "File", "Open File", &["<Control>O"]),
+    (
+        "new_window",
+        "File",
+        "New Window",
+        &["<Control>N", "<Control>T"],
+    ),
+    ("save_page", "File", "Save Page As", &["<Control>S"]),
+    (
+        "save_spread",
+        "File",
+        "Save Spread As",
+        &["<Control><Shift>S"],
+    ),
+    ("copy_page", "File", "Copy Page", &["<Control>C"]),
+    ("copy_spread", "File", "Copy Spread", &["<Control><Shift>C"]),
+    ("print", "File", "Print", &["<Control>P"]),
+    ("preferences", "File", "Preferences", &["<Control>comma"]),
+    ("quit", "File", "Quit", &["<Control>Q", "<Alt>F4"]),
+    (
+        "compare_open",
+        "Compare",
+        "Compare With",
+        &["<Control><Shift>O"],
+    ),
+    (
+        "compare_close",
+        "Compare",
+        "Close Comparison",
+        &["<Control><Shift>W"],
+    ),
+    ("compare_difference", "Compare", "Difference Blend", &["d"]),
+    (
+        "compare_offset_forward",
+        "Compare",
+        "Shift Compared Book Forward",
+        &["bracketright"],
+    ),
+    (
+        "compare_offset_backward",
+        "Compare",
+        "Shift Compared Book Backward",
+        &["bracketleft"],
+    ),
+    (
+        "rotate_page_clockwise",
+        "Rotation",
+        "Rotate Page Clockwise",
+        &["r"],
+    ),
+    ("rotate_page_180", "Rotation", "Rotate Page 180°", &[]),
+    (
+        "rotate_page_counterclockwise",
+        "Rotation",
+        "Rotate Page Counterclockwise",
+        &["<Shift>r"],
+    ),
+    (
+        "flip_page_horizontally",
+        "Rotation",
+        "Flip Page Horizontally",
+        &["m"],
+    ),
+    (
+        "flip_page_vertically",
+        "Rotation",
+        "Flip Page Vertically",
+        &["<Shift>m"],
+    ),
+    ("reset_page_transform", "Rotation", "Reset Page", &[]),
+    (
+        "rotate_book_clockwise",
+        "Rotation",
+        "Rotate Book Clockwise",
+        &["<Control>r"],
+    ),
+    ("rotate_book_180", "Rotation", "Rotate Book 180°", &[]),
+    (
+        "rotate_book_counterclockwise",
+        "Rotation",
+        "Rotate Book Counterclockwise",
+        &["<Control><Shift>r"],
+    ),
+    (
+        "flip_book_horizontally",
+        "Rotation",
+        "Flip Book Horizontally",
+        &[],
+    ),
+    (
+        "flip_book_vertically",
+        "Rotation",
+        "Flip Book Vertically",
+        &[],
+    ),
+    ("reset_book_transform", "Rotation", "Reset Book", &[]),
+    ("auto_crop", "Crop", "Auto Crop", &["c"]),
+    ("edit_crop_box", "Crop", "Crop Margins", &["<Shift>c"]),
+    ("adjustments", "Adjustments", "Image Adjustments", &["a"]),
+    ("invert_colors", "Adjustments", "Invert Colours", &["n"]),
+    ("loupe", "View", "Loupe (hold)", &["z"]),
+    ("slideshow", "View", "Slideshow", &["F5"]),
+    (
+        "shortcuts",
+        "Help",
+        "Keyboard Shortcuts",
+        &["<Control>question"],
+    ),
+];
+
+// actions of the application, all the others belong to each window.
+const APP_ACTION_NAMES: &[&str] = &["new_window", "quit"];
+
+pub fn get_detailed_action_name(action_name: &str) -> String {
+    if APP_ACTION_NAMES.contains(&action_name) {
+        format!("app.{}", action_name)
+    } else {
+        format!("win.{}", action_name)
+    }
+}
+
+// a binding written in the file replaces all default accelerators of the action,
+// an empty value unbinds it.
+pub fn load_key_bindings() -> Vec<KeyBinding> {
+    let key_file = utils::load_config_key_file(KEY_BINDINGS_FILE_NAME);
+    create_key_bindings(key_file.as_ref(), is_valid_accel)
+}
+
+fn create_key_bindings(
+    key_file: Option<&glib::KeyFile>,
+    is_valid_accel: impl Fn(&str, &str) -> bool,
+) -> Vec<KeyBinding> {
+    DEFAULT_KEY_BINDINGS
+        .iter()
+        .map(|(action_name, group, title, default_accels)| {
+            let accels = key_file
+                .and_then(|v| v.string_list(KEY_BINDINGS_GROUP, action_name).ok())
+                .map(|v| {
+                    v.iter()
+                        .map(|accel| accel.to_string())
+                        .filter(|accel| is_valid_accel(action_name, accel))
+                        .collect()
+                })
+                .unwrap_or_else(|| default_accels.iter().map(|v| v.to_string()).collect());
+
+            KeyBinding {
+                action_name,
+                group,
+                title,
+                accels,
+            }
+        })
+        .collect()
+}
+
+fn is_valid_accel(action_name: &str, accel: &str) -> bool {
+    if gtk::accelerator_parse(accel).is_some() {
+        return true;
+    }
+
+    eprintln!("invalid key binding for {}: {}", action_name, accel);
+    false
+}
+
+fn has_modifier(accel: &str) -> bool {
+    let Some((_key, modifier)) = gtk::accelerator_parse(accel) else {
+        return false;
+    };
+
+    modifier.intersects(gdk::ModifierType::CONTROL_MASK | gdk::ModifierType::ALT_MASK)
+}
+
+// keys without a modifier are left to the key controller of the window,
+// so focused widgets such as the page overview still get arrow keys.
+pub fn set_accels_for_actions(app: &gtk::Application, key_bindings: &[KeyBinding]) {
+    for key_binding in key_bindings {
+        let accels: Vec<&str> = key_binding
+            .accels
+            .iter()
+            .map(|v| v.as_str())
+            .filter(|v| has_modifier(v))
+            .collect();
+        app.set_accels_for_action(&get_detailed_action_name(key_binding.action_name), &accels);
+    }
+}
+
+fn is_matched_accel(
+    accel: &str,
+    keyval: gdk::Key,
+    state: gdk::ModifierType,
+    is_strict: bool,
+) -> bool {
+    let Some((key, modifier)) = gtk::accelerator_parse(accel) else {
+        return false;
+    };
+
+    is_matched_key(key, modifier, keyval, state, is_strict)
+}
+
+fn is_matched_key(
+    key: gdk::Key,
+    modifier: gdk::ModifierType,
+    keyval: gdk::Key,
+    state: gdk::ModifierType,
+    is_strict: bool,
+) -> bool {
+    let modifier_mask = gdk::ModifierType::CONTROL_MASK
+        | gdk::ModifierType::ALT_MASK
+        | gdk::ModifierType::SUPER_MASK
+        | gdk::ModifierType::SHIFT_MASK;
+    // shift is a part of keys such as "question", so it may be ignored.
+    let modifier_mask = if is_strict || modifier.contains(gdk::ModifierType::SHIFT_MASK) {
+        modifier_mask
+    } else {
+        modifier_mask - gdk::ModifierType::SHIFT_MASK
+    };
+
+    key.to_lower() == keyval.to_lower() && (modifier & modifier_mask) == (state & modifier_mask)
+}
+
+pub fn find_action_name(
+    key_bindings: &[KeyBinding],
+    keyval: gdk::Key,
+    state: gdk::ModifierType,
+) -> Option<&'static str> {
+    find_matched_action_name(key_bindings, |accel, is_strict| {
+        is_matched_accel(accel, keyval, state, is_strict)
+    })
+}
+
+// an accel matching with shift is preferred to one which only matches by ignoring it.
+fn find_matched_action_name(
+    key_bindings: &[KeyBinding],
+    is_matched: impl Fn(&str, bool) -> bool,
+) -> Option<&'static str> {
+    let find = |is_strict: bool| {
+        key_bindings
+            .iter()
+            .find(|v| v.accels.iter().any(|accel| is_matched(accel, is_strict)))
+            .map(|v| v.action_name)
+    };
+
+    find(true).or_else(|| find(false))
+}
+
+fn create_shortcuts_window_ui(key_bindings: &[KeyBinding]) -> String {
+    let mut groups: Vec<&str> = vec![];
+    for key_binding in key_bindings {
+        if !groups.contains(&key_binding.group) {
+            groups.push(key_binding.group);
+        }
+    }
+
+    let mut groups_ui = String::new();
+    for group in groups {
+        let mut shortcuts_ui = String::new();
+        for key_binding in key_bindings
+            .iter()
+            .filter(|v| v.group == group && !v.accels.is_empty())
+        {
+            shortcuts_ui += &format!(
+                r#"<child><object class="GtkShortcutsShortcut"><property name="title">{}</property><property name="accelerator">{}</property></object></child>"#,
+                glib::markup_escape_text(key_binding.title),
+                glib::markup_escape_text(&key_binding.accels.join(" ")),
+            );
+        }
+        if shortcuts_ui.is_empty() {
+            continue;
+        }
+
+        groups_ui += &format!(
+            r#"<child><object class="GtkShortcutsGroup"><property name="title">{}</property>{}</object></child>"#,
+            glib::markup_escape_text(group),
+            shortcuts_ui,
+        );
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><interface><object class="GtkShortcutsWindow" id="shortcuts-window"><property name="modal">1</property><child><object class="GtkShortcutsSection"><property name="section-name">shortcuts</property><property name="max-height">12</property>{}</object></child></object></interface>"#,
+        groups_ui
+    )
+}
+
+pub fn create_shortcuts_window(key_bindings: &[KeyBinding]) -> Option<gtk::Window> {
+    let builder = gtk::Builder::new();
+    if let Err(e) = builder.add_from_string(&create_shortcuts_window_ui(key_bindings)) {
+        eprintln!("{}", e);
+        return None;
+    }
+
+    builder.object::<gtk::Window>("shortcuts-window")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // gtk::accelerator_parse needs an initialized gtk, so tests only parse plain
+    // accels such as "<Control><Shift>r".
+    fn parse_accel(accel: &str) -> Option<(gdk::Key, gdk::ModifierType)> {
+        let mut modifier = gdk::ModifierType::empty();
+        let mut rest = accel;
+        while let Some(v) = rest.strip_prefix('<') {
+            let (name, v) = v.split_once('>')?;
+            modifier |= match name {
+                "Control" => gdk::ModifierType::CONTROL_MASK,
+                "Shift" => gdk::ModifierType::SHIFT_MASK,
+                "Alt" => gdk::ModifierType::ALT_MASK,
+                _ => return None,
+            };
+            rest = v;
+        }
+
+        gdk::Key::from_name(rest).map(|key| (key, modifier))
+    }
+
+    fn is_valid_test_accel(_action_name: &str, accel: &str) -> bool {
+        parse_accel(accel).is_some()
+    }
+
+    fn find_test_action_name(
+        key_bindings: &[KeyBinding],
+        keyval: gdk::Key,
+        state: gdk::ModifierType,
+    ) -> Option<&'static str> {
+        find_matched_action_name(key_bindings, |accel, is_strict| {
+            parse_accel(accel).is_some_and(|(key, modifier)| {
+                is_matched_key(key, modifier, keyval, state, is_strict)
+            })
+        })
+    }
+
+    fn find_accels<'a>(key_bindings: &'a [KeyBinding], action_name: &str) -> &'a [String] {
+        &key_bindings
+            .iter()
+            .find(|v| v.action_name == action_name)
+            .unwrap()
+            .accels
+    }
+
+    fn create_test_key_bindings(data: &str) -> Vec<KeyBinding> {
+        let key_file = glib::KeyFile::new();
+        key_file
+            .load_from_data(data, glib::KeyFileFlags::NONE)
+            .unwrap();
+        create_key_bindings(Some(&key_file), is_valid_test_accel)
+    }
+
+    #[test]
+    fn key_bindings_fall_back_to_defaults() {
+        let key_bindings = create_key_bindings(None, is_valid_test_accel);
+        assert_eq!(key_bindings.len(), DEFAULT_KEY_BINDINGS.len());
+        assert_eq!(
+            find_accels(&key_bindings, "fullscreen"),
+            ["<Alt>Return", "F11"]
+        );
+
+        let key_bindings = create_test_key_bindings("[keybindings]\nauto_crop=x\n");
+        assert_eq!(find_accels(&key_bindings, "auto_crop"), ["x"]);
+        assert_eq!(find_accels(&key_bindings, "loupe"), ["z"]);
+    }
+
+    #[test]
+    fn key_bindings_drop_invalid_accels() {
+        let key_bindings =
+            create_test_key_bindings("[keybindings]\nauto_crop=<Bogus>c;x;\nloupe=\n");
+        assert_eq!(find_accels(&key_bindings, "auto_crop"), ["x"]);
+        assert!(find_accels(&key_bindings, "loupe").is_empty());
+    }
+
+    #[test]
+    fn key_ignores_shift_unless_strict() {
+        let control = gdk::ModifierType::CONTROL_MASK;
+        let shift = gdk::ModifierType::SHIFT_MASK;
+
+        assert!(is_matched_key(
+            gdk::Key::question,
+            control,
+            gdk::Key::question,
+            control | shift,
+            false
+        ));
+        assert!(!is_matched_key(
+            gdk::Key::question,
+            control,
+            gdk::Key::question,
+            control | shift,
+            true
+        ));
+        // shift written in the accel is always required.
+        assert!(!is_matched_key(
+            gdk::Key::r,
+            shift,
+            gdk::Key::r,
+            gdk::ModifierType::empty(),
+            false
+        ));
+        assert!(is_matched_key(gdk::Key::r, shift, gdk::Key::R, shift, true));
+        assert!(!is_matched_key(
+            gdk::Key::r,
+            gdk::ModifierType::empty(),
+            gdk::Key::r,
+            control,
+            false
+        ));
+    }
+
+    #[test]
+    fn action_is_found_strictly_before_loosely() {
+        let key_bindings = create_key_bindings(None, is_valid_test_accel);
+        let control = gdk::ModifierType::CONTROL_MASK;
+        let shift = gdk::ModifierType::SHIFT_MASK;
+
+        assert_eq!(
+            find_test_action_name(&key_bindings, gdk::Key::r, gdk::ModifierType::empty()),
+            Some("rotate_page_clockwise")
+        );
+        assert_eq!(
+            find_test_action_name(&key_bindings, gdk::Key::R, shift),
+            Some("rotate_page_counterclockwise")
+        );
+        assert_eq!(
+            find_test_action_name(&key_bindings, gdk::Key::R, control | shift),
+            Some("rotate_book_counterclockwise")
+        );
+        assert_eq!(
+            find_test_action_name(&key_bindings, gdk::Key::question, control | shift),
+            Some("shortcuts")
+        );
+        assert_eq!(
+            find_test_action_name(&key_bindings, gdk::Key::z, control),
+            None
+        );
+    }
+}
@@ -1,9 +1,23 @@
+mod adjustments_panel;
+mod auto_crop;
 mod file_history;
 mod image_container;
+mod image_filter;
 mod image_loader;
+mod image_scaler;
+mod key_bindings;
 mod main_window;
 mod natural_sort;
+mod page_export;
+mod page_layout;
+mod page_overview;
+mod page_print;
+mod page_progress;
+mod page_transform;
 mod pdf_loader;
+mod preferences;
+mod settings;
+mod status_bar;
 mod types;
 mod utils;
 
@@ -15,6 +29,6 @@ fn main() -> gtk::glib::ExitCode {
     let app_id_str: &str = "com.simple_comics_viewer";
     let app = Application::builder().application_id(app_id_str).build();
 
-    app.connect_activate(main_window::activate);
+    app.connect_startup(main_window::startup);
     app.run()
 }
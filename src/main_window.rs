@@ -1,35 +1,98 @@
 use std::str::FromStr;
 
 use gtk::gdk;
-use gtk::glib::object::Cast;
+use gtk::glib::object::{Cast, ObjectExt};
 use gtk4 as gtk;
 use gtk4::glib::VariantTy;
 use gtk4::glib::variant::{StaticVariantType, ToVariant};
 
 use gtk::glib::Propagation;
 use gtk::prelude::{
-    ActionMapExt, ActionMapExtManual, ApplicationExt, ApplicationWindowExt, BoxExt, DialogExt,
-    DrawingAreaExt, DrawingAreaExtManual, FileChooserExt, FileExt, GdkCairoContextExt,
-    GtkApplicationExt, GtkWindowExt, WidgetExt,
+    ActionExt, ActionMapExt, ActionMapExtManual, AdjustmentExt, ApplicationExt,
+    ApplicationWindowExt, BoxExt, CastNone, DialogExt, DisplayExt, DrawingAreaExt,
+    DrawingAreaExtManual, EventControllerExt, FileChooserExt, FileExt, GdkCairoContextExt,
+    GestureDragExt, GestureExt, GestureSingleExt, GridExt, GtkApplicationExt, GtkWindowExt,
+    ListModelExt, MonitorExt, NativeExt, PopoverExt, RangeExt, ScaleExt, WidgetExt,
+    WidgetExtManual,
 };
 use gtk::{Application, ApplicationWindow, DrawingArea, EventControllerKey, cairo, gio, glib};
 
 use anyhow::Result;
 
+use std::collections::BTreeMap;
 use std::fs::DirEntry;
+use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 
+use crate::auto_crop::{self, CropBox};
 use crate::file_history;
+use crate::image_filter::ImageAdjustments;
 use crate::image_loader;
+use crate::key_bindings::{self, KeyBinding};
 use crate::natural_sort::compare_by_natural;
-use crate::pdf_loader::PdfPixmap;
+use crate::page_transform::{self, PageTransform};
+use crate::pdf_loader::{PdfPageRenderer, PdfPageRequest, PdfPixmap};
+use crate::settings::{self, Settings, WindowState};
+use crate::status_bar::{StatusBar, StatusInfo};
 use crate::types;
 use crate::utils;
-use crate::{image_container, pdf_loader};
+use crate::{
+    adjustments_panel, image_container, page_export, page_layout, page_overview, page_print,
+    page_progress, pdf_loader, preferences,
+};
 
 use image_container::{ImageContainer, ImageContainerEx};
-use types::PageDirection;
+use page_layout::{calc_margin_for_dual, calc_margin_for_single};
+use page_overview::PageOverview;
+use types::{BackgroundMode, FitMode, PageDirection, PageTransition, WheelAction};
+
+const MAX_ZOOM_LEVEL: f64 = 4.0;
+const DOUBLE_TAP_ZOOM_LEVEL: f64 = 2.0;
+const SWIPE_VELOCITY_THRESHOLD: f64 = 600.0;
+const SLIDE_DISTANCE_RATIO: f64 = 0.2;
+const TOUCHPAD_SWIPE_THRESHOLD: f64 = 10.0;
+const CURSOR_HIDE_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
+// zoom of the loupe is in device pixels for each pixel of the original.
+const DEFAULT_LOUPE_ZOOM: f64 = 2.0;
+const MIN_LOUPE_ZOOM: f64 = 2.0;
+const MAX_LOUPE_ZOOM: f64 = 4.0;
+const LOUPE_ZOOM_STEP: f64 = 0.5;
+const DEFAULT_LOUPE_RADIUS: f64 = 120.0;
+const MIN_LOUPE_RADIUS: f64 = 40.0;
+const MAX_LOUPE_RADIUS: f64 = 400.0;
+const LOUPE_RADIUS_STEP: f64 = 20.0;
+const BLURRED_BACKGROUND_DIMMING: f64 = 0.35;
+const PAGE_TRANSITION_DURATION_US: f64 = 250_000.0;
+// about two full hd screens, larger surfaces are not worth their memory for a quarter second.
+const MAX_TRANSITION_PIXELS: i64 = 4_000_000;
+// height of the area at the top which shows the menu bar in fullscreen.
+const TOP_EDGE_HEIGHT: f64 = 8.0;
+// height of the area at the bottom which shows the page slider.
+const BOTTOM_EDGE_HEIGHT: f64 = 48.0;
+const RESIZE_SCALE_DELAY: std::time::Duration = std::time::Duration::from_millis(100);
+// action name, whether the whole book is changed and the change of the transform.
+const TRANSFORM_ACTIONS: &[(&str, bool, fn(PageTransform) -> PageTransform)] = &[
+    ("rotate_page_clockwise", false, |v| v.rotate(90)),
+    ("rotate_page_180", false, |v| v.rotate(180)),
+    ("rotate_page_counterclockwise", false, |v| v.rotate(270)),
+    ("flip_page_horizontally", false, |v| v.flip_horizontally()),
+    ("flip_page_vertically", false, |v| v.flip_vertically()),
+    ("reset_page_transform", false, |_| PageTransform::default()),
+    ("rotate_book_clockwise", true, |v| v.rotate(90)),
+    ("rotate_book_180", true, |v| v.rotate(180)),
+    ("rotate_book_counterclockwise", true, |v| v.rotate(270)),
+    ("flip_book_horizontally", true, |v| v.flip_horizontally()),
+    ("flip_book_vertically", true, |v| v.flip_vertically()),
+    ("reset_book_transform", true, |_| PageTransform::default()),
+];
+const COMPARE_ACTION_NAMES: &[&str] = &[
+    "compare_close",
+    "compare_difference",
+    "compare_offset_forward",
+    "compare_offset_backward",
+];
 
 #[derive(Default)]
 struct PagesInfo {
@@ -37,11 +100,48 @@ struct PagesInfo {
     loaded_filename: Arc<Mutex<Option<String>>>,
     loaded_dirname: Arc<Mutex<Option<String>>>,
     page_direction: Arc<Mutex<PageDirection>>,
+    // first page indices of the chapters after the first one.
+    chapter_starts: Arc<Mutex<Vec<usize>>>,
+    // rotation and flip of the whole book, and of single pages on top of it.
+    book_transform: Arc<Mutex<PageTransform>>,
+    page_transforms: Arc<Mutex<BTreeMap<usize, PageTransform>>>,
+    // crop boxes set by hand for the whole book and for single pages, they win over auto crop.
+    book_crop_box: Arc<Mutex<Option<CropBox>>>,
+    page_crop_boxes: Arc<Mutex<BTreeMap<usize, CropBox>>>,
+    // colour adjustments kept for this book instead of those of the settings.
+    book_adjustments: Arc<Mutex<Option<ImageAdjustments>>>,
+    compare_book: CompareBook,
+    // rotated pdf pages are rendered again in a thread, it is started by the first one.
+    pdf_page_renderer: Arc<Mutex<Option<PdfPageRenderer>>>,
+    // one timer takes scaled and rendered pages while any of them is running.
+    is_watching_page_jobs: Arc<Mutex<bool>>,
+    // showing the page slider again restarts this timer instead of adding another one.
+    pages_bar_hide_source_id: Arc<Mutex<Option<glib::SourceId>>>,
+    // device pixels between the pages of a spread, as the pages were scaled for it.
+    spread_gutter_width: Arc<Mutex<i32>>,
+    page_turn_transition: Arc<Mutex<Option<PageTurnTransition>>>,
+}
+
+// the pages shown before a page turn, drawn beside or under the new pages until it ends.
+struct PageTurnTransition {
+    kind: PageTransition,
+    // owned data keeps the surface sendable, it is lent back to cairo while drawing.
+    outgoing: Option<cairo::ImageSurfaceDataOwned>,
+    // 1 when the pages move to the right, -1 to the left.
+    direction: f64,
+    progress: f64,
+    is_cancelled: Arc<AtomicBool>,
 }
 
+// a second book shown beside the first one, such as a raw scan and its release.
 #[derive(Default)]
-struct Settings {
-    is_dual_mode: Arc<Mutex<bool>>,
+struct CompareBook {
+    image_container_list: Arc<Mutex<Vec<ImageContainer>>>,
+    loaded_filename: Arc<Mutex<Option<String>>>,
+    chapter_starts: Arc<Mutex<Vec<usize>>>,
+    // pages of the compared book ahead of the first book.
+    page_offset: Arc<Mutex<i64>>,
+    is_difference: Arc<Mutex<bool>>,
 }
 
 // #[derive(Default)]
@@ -55,19 +155,78 @@ struct Settings {
 //     app_status.is_file_opend.lock().unwrap().clone()
 // }
 
-#[derive(Default)]
-struct MarginData {
-    left_margin: i32,
-    top_margin: i32,
+// state of the view that is not saved, such as zoom and a slide by touch.
+struct ViewState {
+    zoom_level: Arc<Mutex<f64>>,
+    slide_offset_x: Arc<Mutex<f64>>,
+    is_page_turned_by_gesture: Arc<Mutex<bool>>,
+    touchpad_scroll_dx: Arc<Mutex<f64>>,
+    cursor_hide_source_id: Arc<Mutex<Option<glib::SourceId>>>,
+    // the loupe follows the pointer over the drawing area, the radius is logical.
+    is_loupe_shown: Arc<Mutex<bool>>,
+    loupe_zoom: Arc<Mutex<f64>>,
+    loupe_radius: Arc<Mutex<f64>>,
+    pointer_position: Arc<Mutex<Option<(f64, f64)>>>,
+    page_placements: Arc<Mutex<Vec<PagePlacement>>>,
+    // the slideshow runs while its timer is set, input holds it back for an interval.
+    slideshow_source_id: Arc<Mutex<Option<glib::SourceId>>>,
+    slideshow_last_input: Arc<Mutex<Option<std::time::Instant>>>,
+    // a tick callback scrolls a tall page down only while this is set.
+    is_slideshow_scrolling: Arc<Mutex<bool>>,
+    // a single tap toggles the ui when this runs out without a second tap.
+    ui_toggle_source_id: Arc<Mutex<Option<glib::SourceId>>>,
+    resize_scale_source_id: Arc<Mutex<Option<glib::SourceId>>>,
 }
 
-#[derive(Default)]
-struct MarginDataForDual {
-    left_margin: i32,
-    top_margin_for_left: i32,
-    top_margin_for_right: i32,
+impl Default for ViewState {
+    fn default() -> Self {
+        ViewState {
+            zoom_level: Arc::new(Mutex::new(1.0)),
+            slide_offset_x: Arc::new(Mutex::new(0.0)),
+            is_page_turned_by_gesture: Arc::new(Mutex::new(false)),
+            touchpad_scroll_dx: Arc::new(Mutex::new(0.0)),
+            cursor_hide_source_id: Arc::new(Mutex::new(None)),
+            is_loupe_shown: Arc::new(Mutex::new(false)),
+            loupe_zoom: Arc::new(Mutex::new(DEFAULT_LOUPE_ZOOM)),
+            loupe_radius: Arc::new(Mutex::new(DEFAULT_LOUPE_RADIUS)),
+            pointer_position: Arc::new(Mutex::new(None)),
+            page_placements: Arc::new(Mutex::new(vec![])),
+            slideshow_source_id: Arc::new(Mutex::new(None)),
+            slideshow_last_input: Arc::new(Mutex::new(None)),
+            is_slideshow_scrolling: Arc::new(Mutex::new(false)),
+            ui_toggle_source_id: Arc::new(Mutex::new(None)),
+            resize_scale_source_id: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+// where a page was painted in device pixels, so the loupe finds the page under the pointer.
+#[derive(Clone, Copy)]
+struct PagePlacement {
+    is_compare: bool,
+    page_index: usize,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
 }
 
+impl PagePlacement {
+    fn new(page_index: usize, x: f64, y: f64, pixbuf: &gtk::gdk_pixbuf::Pixbuf) -> Self {
+        PagePlacement {
+            is_compare: false,
+            page_index,
+            x,
+            y,
+            width: f64::from(pixbuf.width()),
+            height: f64::from(pixbuf.height()),
+        }
+    }
+
+    fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
 enum ResultLoadFilesWithMultiThread {
     Success,
     Failed,
@@ -79,6 +238,7 @@ struct MainWindow {
     image_container_list: Arc<Mutex<Vec<ImageContainer>>>,
     pages_info: std::sync::Arc<PagesInfo>,
     settings: std::sync::Arc<Settings>,
+    view_state: Arc<ViewState>,
     view_window: gtk::ScrolledWindow,
 }
 
@@ -91,71 +251,9 @@ fn update_window_title(window: &gtk::ApplicationWindow, title_text: &str) {
     window.set_title(Some(&new_title));
 }
 
-fn calc_margin_for_single(
-    pixbuf_data: &gtk::gdk_pixbuf::Pixbuf,
-    target_width: i32,
-    target_height: i32,
-) -> MarginData {
-    let pic_height = pixbuf_data.height();
-    let pic_width = pixbuf_data.width();
-
-    let width_diff = target_width - pic_width;
-    let left_margin = if width_diff < 0 || width_diff == 0 {
-        0
-    } else {
-        width_diff / 2
-    };
-
-    let height_diff = target_height - pic_height;
-    let top_margin = if height_diff < 0 || height_diff == 0 {
-        0
-    } else {
-        height_diff / 2
-    };
-
-    MarginData {
-        left_margin,
-        top_margin,
-    }
-}
-
-fn calc_margin_for_dual(
-    left: &gtk::gdk_pixbuf::Pixbuf,
-    right: &gtk::gdk_pixbuf::Pixbuf,
-    target_width: i32,
-    target_height: i32,
-) -> MarginDataForDual {
-    let left_height = left.height();
-    let left_width = left.width();
-    let right_height = right.height();
-    let right_width = right.width();
-
-    let width_diff = target_width - (left_width + right_width);
-    let left_margin = if width_diff <= 0 { 0 } else { width_diff / 2 };
-
-    let left_height_diff = target_height - left_height;
-    let top_margin_for_left = if left_height_diff <= 0 {
-        0
-    } else {
-        left_height_diff / 2
-    };
-
-    let right_height_diff = target_height - right_height;
-    let top_margin_for_right = if right_height_diff <= 0 {
-        0
-    } else {
-        right_height_diff / 2
-    };
-
-    MarginDataForDual {
-        left_margin,
-        top_margin_for_left,
-        top_margin_for_right,
-    }
-}
-
 fn scale_page_for_single(
     image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    settings: &Settings,
     current_page_index: usize,
     target_width: i32,
     target_height: i32,
@@ -176,11 +274,15 @@ fn scale_page_for_single(
         target_width,
         target_height,
         false,
+        *settings.fit_mode.lock().unwrap(),
+        *settings.interpolation.lock().unwrap(),
+        *settings.sharpen_amount.lock().unwrap(),
     );
 }
 
 fn scale_page_for_dual(
     image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    settings: &Settings,
     current_page_index: usize,
     target_width: i32,
     target_height: i32,
@@ -199,22 +301,499 @@ fn scale_page_for_dual(
     let _image_container_list_len = image_container_list_ptr.len();
 
     let final_target_width = target_width / 2;
-    image_container_list_ptr[current_page_index].scale(final_target_width, target_height, true);
+    let fit_mode = *settings.fit_mode.lock().unwrap();
+    let interpolation = *settings.interpolation.lock().unwrap();
+    let sharpen_amount = *settings.sharpen_amount.lock().unwrap();
+    image_container_list_ptr[current_page_index].scale(
+        final_target_width,
+        target_height,
+        true,
+        fit_mode,
+        interpolation,
+        sharpen_amount,
+    );
 
     if next_index < _image_container_list_len {
-        image_container_list_ptr[next_index].scale(final_target_width, target_height, true);
+        image_container_list_ptr[next_index].scale(
+            final_target_width,
+            target_height,
+            true,
+            fit_mode,
+            interpolation,
+            sharpen_amount,
+        );
+    }
+}
+
+// pages larger than the view by the fit mode make the drawing area scrollable.
+fn update_drawing_area_content_size(
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    settings: &Settings,
+    current_page_index: usize,
+    pane_count: i32,
+    drawing_area: &DrawingArea,
+) {
+    let fit_mode = *settings.fit_mode.lock().unwrap();
+    if fit_mode == FitMode::Auto || fit_mode == FitMode::Page {
+        drawing_area.set_content_width(0);
+        drawing_area.set_content_height(0);
+        return;
+    }
+
+    let page_count = if *settings.is_dual_mode.lock().unwrap() {
+        2
+    } else {
+        1
+    };
+    let (content_width, content_height, shown_count) = image_container_list
+        .lock()
+        .unwrap()
+        .iter()
+        .skip(current_page_index)
+        .take(page_count)
+        .filter_map(|v| v.get_modified_pixbuf_data())
+        .fold((0, 0, 0), |(width, height, count), v| {
+            (width + v.width(), height.max(v.height()), count + 1)
+        });
+
+    // the pages have device pixels and the content size is logical.
+    let scale_factor = drawing_area.scale_factor();
+    let gutter_width = if shown_count == 2 {
+        *settings.spread_gutter.lock().unwrap() as i32
+    } else {
+        0
+    };
+    let (content_width, content_height) = (
+        content_width / scale_factor + gutter_width,
+        content_height / scale_factor,
+    );
+
+    let is_width_scrollable = fit_mode == FitMode::Height || fit_mode == FitMode::Original;
+    let is_height_scrollable = fit_mode == FitMode::Width || fit_mode == FitMode::Original;
+    drawing_area.set_content_width(if is_width_scrollable {
+        content_width * pane_count
+    } else {
+        0
+    });
+    drawing_area.set_content_height(if is_height_scrollable {
+        content_height
+    } else {
+        0
+    });
+}
+
+// following pages are queued for the scaling threads, so turning to them does not wait for scaling.
+fn prefetch_pages(
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    settings: &Settings,
+    current_page_index: usize,
+    target_width: i32,
+    target_height: i32,
+) {
+    let prefetch_count = *settings.prefetch_count.lock().unwrap();
+    if prefetch_count == 0 || target_width < 1 || target_height < 1 {
+        return;
+    }
+
+    let is_dual = *settings.is_dual_mode.lock().unwrap();
+    let fit_mode = *settings.fit_mode.lock().unwrap();
+    let interpolation = *settings.interpolation.lock().unwrap();
+    let sharpen_amount = *settings.sharpen_amount.lock().unwrap();
+    let (target_width, first_index) = if is_dual {
+        (target_width / 2, current_page_index + 2)
+    } else {
+        (target_width, current_page_index + 1)
+    };
+
+    for image_container in image_container_list
+        .lock()
+        .unwrap()
+        .iter()
+        .skip(first_index)
+        .take(prefetch_count)
+    {
+        image_container.prefetch(
+            target_width,
+            target_height,
+            is_dual,
+            fit_mode,
+            interpolation,
+            sharpen_amount,
+        );
+    }
+}
+
+fn scale_pages(
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    settings: &Settings,
+    current_page_index: usize,
+    target_width: i32,
+    target_height: i32,
+) {
+    if *settings.is_dual_mode.lock().unwrap() {
+        scale_page_for_dual(
+            image_container_list,
+            settings,
+            current_page_index,
+            target_width,
+            target_height,
+        );
+    } else {
+        scale_page_for_single(
+            image_container_list,
+            settings,
+            current_page_index,
+            target_width,
+            target_height,
+        );
+    }
+}
+
+fn is_compare_mode(pages_info: &PagesInfo) -> bool {
+    !pages_info
+        .compare_book
+        .image_container_list
+        .lock()
+        .unwrap()
+        .is_empty()
+}
+
+fn get_compare_page_index(pages_info: &PagesInfo, page_index: usize) -> Option<usize> {
+    let compare_book = &pages_info.compare_book;
+    let max_size = compare_book.image_container_list.lock().unwrap().len();
+    let page_offset = *compare_book.page_offset.lock().unwrap();
+    page_progress::get_compare_page_index(page_index, page_offset, max_size)
+}
+
+// each book has a half of the view in compare mode, unless they are blended into one.
+fn get_compare_pane_count(pages_info: &PagesInfo) -> i32 {
+    if is_compare_mode(pages_info) && !*pages_info.compare_book.is_difference.lock().unwrap() {
+        2
+    } else {
+        1
+    }
+}
+
+fn get_page_transform(pages_info: &PagesInfo, page_index: usize) -> PageTransform {
+    let book_transform = *pages_info.book_transform.lock().unwrap();
+    let page_transform = pages_info
+        .page_transforms
+        .lock()
+        .unwrap()
+        .get(&page_index)
+        .copied()
+        .unwrap_or_default();
+    book_transform.then(page_transform)
+}
+
+// the transform is set on the shown and prefetched pages just before they are scaled,
+// shown pdf pages are rendered again with the rotation instead of rotating the pixels.
+fn apply_page_transforms(
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    settings: &Settings,
+    pages_info: &PagesInfo,
+    current_page_index: usize,
+    drawing_area: &DrawingArea,
+) {
+    let shown_page_count = if *settings.is_dual_mode.lock().unwrap() {
+        2
+    } else {
+        1
+    };
+    let prefetch_count = *settings.prefetch_count.lock().unwrap();
+    let loaded_filename = pages_info.loaded_filename.lock().unwrap().clone();
+
+    let mut is_requested = false;
+    for (page_index, image_container) in image_container_list
+        .lock()
+        .unwrap()
+        .iter()
+        .enumerate()
+        .skip(current_page_index)
+        .take(shown_page_count + prefetch_count)
+    {
+        image_container.set_transform(get_page_transform(pages_info, page_index));
+        if page_index >= current_page_index + shown_page_count {
+            continue;
+        }
+
+        let (Some(rotation), Some(file_path)) = (
+            image_container.get_pending_native_rotation(),
+            loaded_filename.as_deref(),
+        ) else {
+            continue;
+        };
+        // the page is shown turned by its pixels until it is rendered again.
+        let request = PdfPageRequest {
+            file_path: file_path.to_string(),
+            page_index,
+            rotation,
+            ppi: utils::get_dpi_for_widget(drawing_area),
+        };
+        pages_info
+            .pdf_page_renderer
+            .lock()
+            .unwrap()
+            .get_or_insert_with(PdfPageRenderer::new)
+            .request(request);
+        is_requested = true;
+    }
+
+    if is_requested {
+        watch_page_jobs(image_container_list, pages_info, drawing_area);
+    }
+}
+
+fn set_rendered_pdf_pages(
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pdf_page_renderer: &Mutex<Option<PdfPageRenderer>>,
+    loaded_filename: &Mutex<Option<String>>,
+) -> (bool, bool) {
+    let mut pdf_page_renderer = pdf_page_renderer.lock().unwrap();
+    let Some(pdf_page_renderer) = pdf_page_renderer.as_mut() else {
+        return (false, false);
+    };
+
+    let loaded_filename = loaded_filename.lock().unwrap().clone();
+    let image_container_list = image_container_list.lock().unwrap();
+    let mut is_set = false;
+    for rendered in pdf_page_renderer.take_rendered_pages() {
+        let request = &rendered.request;
+        // pages of another book or turned again meanwhile are left out.
+        let Some(image_container) = image_container_list.get(request.page_index) else {
+            continue;
+        };
+        if loaded_filename.as_deref() != Some(request.file_path.as_str())
+            || image_container.get_pending_native_rotation() != Some(request.rotation)
+        {
+            continue;
+        }
+        if let Some(pdf_pixmap) = rendered.pdf_pixmap {
+            image_container.set_rendered_pdf_pixmap(&pdf_pixmap);
+            is_set = true;
+        }
+    }
+    (is_set, pdf_page_renderer.is_rendering())
+}
+
+// pages are cropped in spreads like they are shown, detected margins of a spread are unified
+// so both pages keep the same height.
+fn apply_crop_boxes(
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    settings: &Settings,
+    pages_info: &PagesInfo,
+    current_page_index: usize,
+) {
+    let shown_page_count = if *settings.is_dual_mode.lock().unwrap() {
+        2
+    } else {
+        1
+    };
+    let prefetch_count = *settings.prefetch_count.lock().unwrap();
+    let is_auto_crop = *settings.is_auto_crop.lock().unwrap();
+    let tolerance = *settings.auto_crop_tolerance.lock().unwrap();
+    let book_crop_box = *pages_info.book_crop_box.lock().unwrap();
+    let page_crop_boxes = pages_info.page_crop_boxes.lock().unwrap();
+
+    let image_container_list = image_container_list.lock().unwrap();
+    let end_index =
+        (current_page_index + shown_page_count + prefetch_count).min(image_container_list.len());
+    for spread_start in (current_page_index..end_index).step_by(shown_page_count) {
+        let spread = spread_start..(spread_start + shown_page_count).min(end_index);
+        let detected_crop_box = spread
+            .clone()
+            .filter(|v| is_auto_crop && book_crop_box.is_none() && !page_crop_boxes.contains_key(v))
+            .filter_map(|v| image_container_list[v].detect_crop_box(tolerance))
+            .reduce(CropBox::union);
+
+        for page_index in spread {
+            let crop_box = page_crop_boxes
+                .get(&page_index)
+                .copied()
+                .or(book_crop_box)
+                .or(detected_crop_box)
+                .unwrap_or_default();
+            image_container_list[page_index].set_crop_box(crop_box);
+        }
+    }
+}
+
+fn get_image_adjustments(settings: &Settings, pages_info: &PagesInfo) -> ImageAdjustments {
+    pages_info
+        .book_adjustments
+        .lock()
+        .unwrap()
+        .unwrap_or_else(|| *settings.image_adjustments.lock().unwrap())
+}
+
+fn apply_image_adjustments(
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    settings: &Settings,
+    pages_info: &PagesInfo,
+    current_page_index: usize,
+) {
+    let shown_page_count = if *settings.is_dual_mode.lock().unwrap() {
+        2
+    } else {
+        1
+    };
+    let prefetch_count = *settings.prefetch_count.lock().unwrap();
+    let adjustments = get_image_adjustments(settings, pages_info);
+    for image_container in image_container_list
+        .lock()
+        .unwrap()
+        .iter()
+        .skip(current_page_index)
+        .take(shown_page_count + prefetch_count)
+    {
+        image_container.set_adjustments(adjustments);
+    }
+}
+
+fn scale_pages_to_drawing_area(
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    settings: &Settings,
+    pages_info: &PagesInfo,
+    current_page_index: usize,
+    drawing_area: &DrawingArea,
+) {
+    let pane_count = get_compare_pane_count(pages_info);
+    let scale_factor = drawing_area.scale_factor();
+    let gutter_width = if *settings.is_dual_mode.lock().unwrap() {
+        *settings.spread_gutter.lock().unwrap() as i32 * scale_factor
+    } else {
+        0
+    };
+    *pages_info.spread_gutter_width.lock().unwrap() = gutter_width;
+    // the pages of a spread share what is left beside the gutter.
+    let width = drawing_area.allocated_width() * scale_factor / pane_count - gutter_width;
+    let height = drawing_area.allocated_height() * scale_factor;
+    apply_page_transforms(
+        image_container_list,
+        settings,
+        pages_info,
+        current_page_index,
+        drawing_area,
+    );
+    apply_crop_boxes(
+        image_container_list,
+        settings,
+        pages_info,
+        current_page_index,
+    );
+    apply_image_adjustments(
+        image_container_list,
+        settings,
+        pages_info,
+        current_page_index,
+    );
+    scale_pages(
+        image_container_list,
+        settings,
+        current_page_index,
+        width,
+        height,
+    );
+    if let Some(compare_page_index) = get_compare_page_index(pages_info, current_page_index) {
+        scale_pages(
+            &pages_info.compare_book.image_container_list,
+            settings,
+            compare_page_index,
+            width,
+            height,
+        );
+    }
+
+    update_drawing_area_content_size(
+        image_container_list,
+        settings,
+        current_page_index,
+        pane_count,
+        drawing_area,
+    );
+    prefetch_pages(
+        image_container_list,
+        settings,
+        current_page_index,
+        width,
+        height,
+    );
+    watch_page_jobs(image_container_list, pages_info, drawing_area);
+}
+
+fn take_scaled_pages(image_container_list: &Arc<Mutex<Vec<ImageContainer>>>) -> (bool, bool) {
+    let image_container_list = image_container_list.lock().unwrap();
+    // every finished page is taken, not only the first one.
+    let taken_count = image_container_list
+        .iter()
+        .filter(|v| v.take_scaled_pixbuf_data())
+        .count();
+    let is_scaling = image_container_list.iter().any(|v| v.is_scaling());
+    (taken_count > 0, is_scaling)
+}
+
+// pages are scaled and pdf pages rendered again in threads,
+// one timer shows their results as soon as they are done.
+fn watch_page_jobs(
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &PagesInfo,
+    drawing_area: &DrawingArea,
+) {
+    if *pages_info.is_watching_page_jobs.lock().unwrap() {
+        return;
     }
+    *pages_info.is_watching_page_jobs.lock().unwrap() = true;
+
+    let image_container_list = Arc::clone(image_container_list);
+    let compare_image_container_list = Arc::clone(&pages_info.compare_book.image_container_list);
+    let pdf_page_renderer = Arc::clone(&pages_info.pdf_page_renderer);
+    let loaded_filename = Arc::clone(&pages_info.loaded_filename);
+    let is_watching_page_jobs = Arc::clone(&pages_info.is_watching_page_jobs);
+    glib::timeout_add_local(
+        std::time::Duration::from_millis(16),
+        glib::clone!(
+            #[weak]
+            drawing_area,
+            #[upgrade_or]
+            glib::ControlFlow::Break,
+            move || {
+                // rendered pages are scaled in threads again, so they are set before taking.
+                let (is_set, is_rendering) = set_rendered_pdf_pages(
+                    &image_container_list,
+                    &pdf_page_renderer,
+                    &loaded_filename,
+                );
+                let (is_taken, is_scaling) = take_scaled_pages(&image_container_list);
+                let (is_compare_taken, is_compare_scaling) =
+                    take_scaled_pages(&compare_image_container_list);
+                if is_set || is_taken || is_compare_taken {
+                    drawing_area.queue_draw();
+                }
+                if is_rendering || is_scaling || is_compare_scaling {
+                    return glib::ControlFlow::Continue;
+                }
+
+                *is_watching_page_jobs.lock().unwrap() = false;
+                glib::ControlFlow::Break
+            }
+        ),
+    );
 }
 
 fn append_image_container_from_file(
     file: &gio::File,
     image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
-    page_index: usize,
 ) {
     let image_container = ImageContainer::default();
+    if let Some(file_name) = file.basename() {
+        image_container.set_file_name(&file_name.to_string_lossy());
+    }
+    // a broken page is shown as a placeholder, the other pages are still readable.
+    if let Err(e) = image_container.set_pixbuf_from_file(file) {
+        eprintln!("{}: {}", file.parse_name(), e);
+        image_container.set_placeholder_pixbuf(&e.to_string());
+    }
     (*image_container_list.lock().unwrap()).push(image_container);
-
-    (*image_container_list.lock().unwrap())[page_index].set_pixbuf_from_file(file);
 }
 
 // fn scale_images(
@@ -252,31 +831,27 @@ fn get_file_path_from_file_desc(file: &gio::File) -> Option<String> {
     Some(pathname.to_owned())
 }
 
-fn allocate_drawing_area_and_scale_init_from_image_container(
-    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
-    settings: &Arc<Settings>,
-    pos: usize,
-    drawing_area_ref: &DrawingArea,
-) {
-    let width = drawing_area_ref.allocated_width();
-    let height = drawing_area_ref.allocated_height();
-    let is_dual_model = settings.is_dual_mode.lock().unwrap();
-    if *is_dual_model {
-        scale_page_for_dual(image_container_list, pos, width, height);
-    } else {
-        scale_page_for_single(image_container_list, 0, width, height);
-    }
-}
-
 fn open_and_set_image_to_image_container_from_zip(
     pathname: &String,
     image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    chapter_starts: &Arc<Mutex<Vec<usize>>>,
 ) -> bool {
     match image_loader::load_from_compressed_file_to_memory(pathname) {
         Ok(extracted) => {
+            let file_names: Vec<&str> = extracted
+                .iter()
+                .map(|v| v.file_info.file_name.as_str())
+                .collect();
+            *chapter_starts.lock().unwrap() = page_progress::find_chapter_starts(&file_names);
+
             extracted.into_iter().for_each(|v| {
                 let image_container = ImageContainer::default();
-                image_container.set_pixbuf_from_bytes(&v.value);
+                image_container.set_file_name(&v.file_info.file_name);
+                // a broken page is shown as a placeholder, the other pages are still readable.
+                if let Err(e) = image_container.set_pixbuf_from_bytes(&v.value) {
+                    eprintln!("{}: {}", v.file_info.file_name, e);
+                    image_container.set_placeholder_pixbuf(&e.to_string());
+                }
                 (*image_container_list.lock().unwrap()).push(image_container);
             });
             true
@@ -302,12 +877,11 @@ fn set_image_to_image_container_from_pdf_pixmaps(
 fn open_and_set_image_to_image_container_from_file(
     file: &gio::File,
     image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
-    page_index: usize,
 ) -> bool {
     match utils::detect_file_type_from_file(&file) {
         utils::FileType::NONE => false,
         _ => {
-            append_image_container_from_file(file, image_container_list, page_index);
+            append_image_container_from_file(file, image_container_list);
             true
         }
     }
@@ -342,7 +916,6 @@ fn read_dir_and_set_images(
                         let r = open_and_set_image_to_image_container_from_file(
                             &tmp_file,
                             &image_container_list,
-                            count,
                         );
                         if r {
                             count = count + 1;
@@ -360,10 +933,11 @@ fn read_dir_and_set_images(
 fn set_open_file_history_menu(
     menu: &Arc<Mutex<gio::Menu>>,
     db_manager: &Arc<Mutex<file_history::DbManager>>,
+    history_size: usize,
 ) {
     let unlock_db_manager = db_manager.lock().unwrap();
 
-    let open_file_history_list = unlock_db_manager.get_history();
+    let open_file_history_list = unlock_db_manager.get_history(history_size);
     if open_file_history_list.is_empty() {
         return;
     }
@@ -371,7 +945,7 @@ fn set_open_file_history_menu(
     let unlock_menu = menu.lock().unwrap();
     unlock_menu.remove_all();
     for i in open_file_history_list {
-        let item = gio::MenuItem::new(Some(&i.location_path), Some("app.open_file_from_history"));
+        let item = gio::MenuItem::new(Some(&i.location_path), Some("win.open_file_from_history"));
         item.set_attribute_value("target", Some(&i.location_path.to_variant()));
         unlock_menu.append_item(&item);
     }
@@ -394,6 +968,7 @@ fn update_open_file_history_menu(
     db_manager: &Arc<Mutex<file_history::DbManager>>,
     file_path: &str,
     pages_info: &Arc<PagesInfo>,
+    history_size: usize,
 ) {
     let unixtime = utils::get_current_unixtime().expect("failed get unixtime");
     let unixtime_i64 = i64::try_from(unixtime).expect("failed convert u64 to i64 at unixtime");
@@ -424,7 +999,7 @@ fn update_open_file_history_menu(
         unlock_db_manager.add_history(file_path.to_owned(), unixtime_i64, page_direction);
     }
 
-    let open_file_history_list = unlock_db_manager.get_history();
+    let open_file_history_list = unlock_db_manager.get_history(history_size);
     if open_file_history_list.is_empty() {
         return;
     }
@@ -432,7 +1007,7 @@ fn update_open_file_history_menu(
     let unlock_menu = menu.lock().unwrap();
     unlock_menu.remove_all();
     for i in open_file_history_list {
-        let item = gio::MenuItem::new(Some(&i.location_path), Some("app.open_file_from_history"));
+        let item = gio::MenuItem::new(Some(&i.location_path), Some("win.open_file_from_history"));
         item.set_attribute_value("target", Some(&i.location_path.to_variant()));
         unlock_menu.append_item(&item);
     }
@@ -440,11 +1015,11 @@ fn update_open_file_history_menu(
 
 // open file action
 fn open_file_for_action(
-    app: &gtk::Application,
     window: &gtk::ApplicationWindow,
     image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
     drawing_area_ref: &DrawingArea,
-    pages_bar: &gtk::ProgressBar,
+    pages_bar: &gtk::Scale,
+    status_bar: &StatusBar,
     settings: &Arc<Settings>,
     pages_info: &Arc<PagesInfo>,
     spinner: &gtk::Spinner,
@@ -452,22 +1027,39 @@ fn open_file_for_action(
     db_manager: &Arc<Mutex<file_history::DbManager>>,
     file: &gio::File,
 ) {
+    // a directory from the history is opened from its first image.
+    let file = match file.path().filter(|v| v.is_dir()) {
+        Some(dir_path) => match find_first_image_in_dir(&dir_path) {
+            Some(v) => gio::File::for_path(v),
+            None => return,
+        },
+        None => file.clone(),
+    };
+
     (*image_container_list.lock().unwrap()).clear();
     *pages_info.current_page_index.lock().unwrap() = 0;
-    match utils::detect_file_type_from_file(&file) {
+    *pages_info.loaded_filename.lock().unwrap() = None;
+    *pages_info.loaded_dirname.lock().unwrap() = None;
+    pages_info.chapter_starts.lock().unwrap().clear();
+    *pages_info.book_transform.lock().unwrap() = PageTransform::default();
+    pages_info.page_transforms.lock().unwrap().clear();
+    *pages_info.book_crop_box.lock().unwrap() = None;
+    pages_info.page_crop_boxes.lock().unwrap().clear();
+    *pages_info.book_adjustments.lock().unwrap() = None;
+
+    let (tx, rx) = std::sync::mpsc::sync_channel::<ResultLoadFilesWithMultiThread>(1);
+    let image_container_list_arc_cloned = Arc::clone(&image_container_list);
+    let (book_path, is_dir) = match utils::detect_file_type_from_file(&file) {
         utils::FileType::ZIP => {
             let pathname = get_file_path_from_file_desc(&file).unwrap();
             let pathname_cloned = pathname.clone();
-            let (tx, rx) = std::sync::mpsc::sync_channel::<ResultLoadFilesWithMultiThread>(1);
-            let image_container_list_arc_cloned = Arc::clone(&image_container_list);
-
-            spinner.show();
-            spinner.start();
+            let chapter_starts_arc_cloned = Arc::clone(&pages_info.chapter_starts);
 
             let _ = std::thread::spawn(move || {
                 let r = open_and_set_image_to_image_container_from_zip(
                     &pathname_cloned,
                     &image_container_list_arc_cloned,
+                    &chapter_starts_arc_cloned,
                 );
                 if !r {
                     tx.send(ResultLoadFilesWithMultiThread::Failed).unwrap();
@@ -475,216 +1067,28 @@ fn open_file_for_action(
                     tx.send(ResultLoadFilesWithMultiThread::Success).unwrap();
                 }
             });
-
-            glib::spawn_future_local(glib::clone!(
-                #[weak]
-                app,
-                #[weak]
-                window,
-                #[strong]
-                image_container_list,
-                #[strong]
-                settings,
-                #[weak]
-                drawing_area_ref,
-                #[strong]
-                pages_info,
-                #[weak]
-                pages_bar,
-                #[weak]
-                spinner,
-                #[weak]
-                db_manager,
-                #[weak]
-                open_file_history_menu,
-                async move {
-                    update_window_title(&window, "Now Loading...");
-
-                    let _source_id = glib::idle_add_local(glib::clone!(
-                        #[strong]
-                        image_container_list,
-                        #[strong]
-                        settings,
-                        #[strong]
-                        drawing_area_ref,
-                        #[strong]
-                        pages_bar,
-                        move || {
-                            match rx.try_recv() {
-                                Ok(v) => {
-                                    match v {
-                                        ResultLoadFilesWithMultiThread::Success => {
-                                            allocate_drawing_area_and_scale_init_from_image_container(&image_container_list, &settings, 0, &drawing_area_ref);
-                                            *pages_info.loaded_filename.lock().unwrap() =
-                                                Some(pathname.clone());
-                                            update_window_title(&window, &pathname);
-
-                                            spinner.stop();
-                                            spinner.hide();
-
-                                            pages_bar.set_fraction(0.0);
-                                            pages_bar.set_inverted(true);
-
-                                            let max_size = image_container_list.lock().unwrap().len();
-                                            restore_pages_info(&db_manager, &pages_info, &pathname, max_size);
-                                            sync_page_direction_action_state(&app, &pages_info);
-
-                                            let restored_page_index =
-                                                *pages_info.current_page_index.lock().unwrap();
-                                            set_page(
-                                                restored_page_index,
-                                                &settings,
-                                                &drawing_area_ref,
-                                                &image_container_list,
-                                                &pages_info,
-                                                &db_manager,
-                                            );
-
-                                            drawing_area_ref.queue_draw();
-
-                                            update_open_file_history_menu(
-                                                &open_file_history_menu,
-                                                &db_manager,
-                                                &pathname,
-                                                &pages_info,
-                                            );
-
-                                            return glib::ControlFlow::Break;
-                                        }
-                                        ResultLoadFilesWithMultiThread::Failed => {
-                                            spinner.stop();
-                                            spinner.hide();
-
-                                            update_window_title(&window, "Failed");
-                                            return glib::ControlFlow::Break;
-                                        }
-                                    }
-                                }
-                                Err(_) => {
-                                    return glib::ControlFlow::Continue;
-                                }
-                            }
-                        }
-                    ));
-                }
-            ));
+            (pathname, false)
         }
         utils::FileType::PDF => {
             let pathname = get_file_path_from_file_desc(&file).unwrap();
             let pathname_cloned = pathname.clone();
-            update_window_title(&window, "Now Loading...");
-            spinner.show();
-            spinner.start();
-            let (tx, rx) = std::sync::mpsc::sync_channel::<ResultLoadFilesWithMultiThread>(1);
-            let pdf_pixmaps_arc: Arc<Mutex<Vec<PdfPixmap>>> = Arc::new(Mutex::new(vec![]));
-            let pdf_pixmaps_arc_clone = Arc::clone(&pdf_pixmaps_arc);
+            let ppi = utils::get_dpi_for_widget(window);
             let _ = std::thread::spawn(move || {
-                match pdf_loader::load_pdf(&pathname_cloned, &pdf_pixmaps_arc_clone) {
-                    Ok(_) => tx.send(ResultLoadFilesWithMultiThread::Success).unwrap(),
+                let pdf_pixmaps_arc: Arc<Mutex<Vec<PdfPixmap>>> = Arc::new(Mutex::new(vec![]));
+                match pdf_loader::load_pdf(&pathname_cloned, ppi, &pdf_pixmaps_arc) {
+                    Ok(_) => {
+                        set_image_to_image_container_from_pdf_pixmaps(
+                            &image_container_list_arc_cloned,
+                            &pdf_pixmaps_arc,
+                        );
+                        tx.send(ResultLoadFilesWithMultiThread::Success).unwrap()
+                    }
                     Err(_) => tx.send(ResultLoadFilesWithMultiThread::Failed).unwrap(),
                 }
             });
-
-            glib::spawn_future_local(glib::clone!(
-                #[weak]
-                app,
-                #[weak]
-                window,
-                #[weak]
-                image_container_list,
-                #[strong]
-                settings,
-                #[weak]
-                drawing_area_ref,
-                #[strong]
-                pages_info,
-                #[weak]
-                pages_bar,
-                #[weak]
-                spinner,
-                #[weak]
-                db_manager,
-                #[weak]
-                open_file_history_menu,
-                async move {
-                    let _source_id = glib::idle_add_local(glib::clone!(
-                        #[strong]
-                        image_container_list,
-                        #[strong]
-                        settings,
-                        #[strong]
-                        drawing_area_ref,
-                        #[strong]
-                        pages_bar,
-                        move || {
-                            match rx.try_recv() {
-                                Ok(v) => {
-                                    match v {
-                                        ResultLoadFilesWithMultiThread::Success => {
-                                            set_image_to_image_container_from_pdf_pixmaps(
-                                                &image_container_list,
-                                                &pdf_pixmaps_arc,
-                                            );
-                                            allocate_drawing_area_and_scale_init_from_image_container(&image_container_list,
-                                                                                              &settings,
-                                                                                              0,
-                                                                                              &drawing_area_ref);
-                                            *pages_info.loaded_filename.lock().unwrap() =
-                                                Some(pathname.clone());
-                                            update_window_title(&window, &pathname);
-
-                                            spinner.stop();
-                                            spinner.hide();
-
-                                            pages_bar.set_fraction(0.0);
-                                            pages_bar.set_inverted(true);
-
-                                            let max_size = image_container_list.lock().unwrap().len();
-                                            restore_pages_info(&db_manager, &pages_info, &pathname, max_size);
-                                            sync_page_direction_action_state(&app, &pages_info);
-
-                                            let restored_page_index =
-                                                *pages_info.current_page_index.lock().unwrap();
-                                            set_page(
-                                                restored_page_index,
-                                                &settings,
-                                                &drawing_area_ref,
-                                                &image_container_list,
-                                                &pages_info,
-                                                &db_manager,
-                                            );
-
-
-                                            drawing_area_ref.queue_draw();
-                                            update_open_file_history_menu(
-                                                &open_file_history_menu,
-                                                &db_manager,
-                                                &pathname,
-                                                &pages_info,
-                                            );
-
-                                            return glib::ControlFlow::Break;
-                                        }
-                                        ResultLoadFilesWithMultiThread::Failed => {
-                                            spinner.stop();
-                                            spinner.hide();
-
-                                            update_window_title(&window, "Failed");
-                                            return glib::ControlFlow::Break;
-                                        }
-                                    }
-                                }
-                                Err(_) => {
-                                    return glib::ControlFlow::Continue;
-                                }
-                            }
-                        }
-                    ));
-                }
-            ));
+            (pathname, false)
         }
         _ => {
-            let pathname = get_file_path_from_file_desc(&file).unwrap();
             let Some(path) = file.path() else { return };
             let Some(dir_path) = path.parent() else {
                 eprintln!("Failed get parent directory from path");
@@ -697,16 +1101,7 @@ fn open_file_for_action(
             };
             let dir_path_string = dir_path_str.to_string();
 
-            update_window_title(&window, "Now Loading...");
-            spinner.show();
-            spinner.start();
-
-            // update_window_title(&window, dir_path_str);
-            // *pages_info.loaded_dirname.lock().unwrap() = Some(dir_path_str.to_owned());
-
-            let image_container_list_arc_cloned = Arc::clone(&image_container_list);
             let file_cloned = file.clone();
-            let (tx, rx) = std::sync::mpsc::sync_channel::<ResultLoadFilesWithMultiThread>(1);
             let _ = std::thread::spawn(move || {
                 if read_dir_and_set_images(&file_cloned, &image_container_list_arc_cloned) {
                     tx.send(ResultLoadFilesWithMultiThread::Success).unwrap();
@@ -714,110 +1109,246 @@ fn open_file_for_action(
                     tx.send(ResultLoadFilesWithMultiThread::Failed).unwrap();
                 }
             });
+            (dir_path_string, true)
+        }
+    };
 
-            glib::spawn_future_local(glib::clone!(
-                #[weak]
-                app,
-                #[weak]
-                window,
-                #[weak]
-                image_container_list,
-                #[strong]
-                settings,
-                #[weak]
-                drawing_area_ref,
-                #[strong]
-                pages_info,
-                #[weak]
-                pages_bar,
-                #[weak]
-                spinner,
-                #[weak]
-                db_manager,
-                #[weak]
-                open_file_history_menu,
-                async move {
-                    let _source_id = glib::idle_add_local(glib::clone!(
-                        #[strong]
-                        image_container_list,
-                        #[strong]
-                        settings,
-                        #[strong]
-                        drawing_area_ref,
-                        #[strong]
-                        pages_bar,
-                        move || {
-                            match rx.try_recv() {
-                                Ok(v) => {
-                                    match v {
-                                        ResultLoadFilesWithMultiThread::Success => {
-                                            allocate_drawing_area_and_scale_init_from_image_container(&image_container_list,
-                                                                                              &settings,
-                                                                                              0,
-                                                                                              &drawing_area_ref);
-                                            *pages_info.loaded_dirname.lock().unwrap() =
-                                                Some(dir_path_string.to_owned());
-                                            update_window_title(&window, &pathname);
-
-                                            spinner.stop();
-                                            spinner.hide();
-
-                                            pages_bar.set_fraction(0.0);
-                                            pages_bar.set_inverted(true);
-                                            
-                                            let max_size = image_container_list.lock().unwrap().len();
-                                            restore_pages_info(&db_manager, &pages_info, &pathname, max_size);
-
-                                            sync_page_direction_action_state(&app, &pages_info);
-
-                                            let restored_page_index =
-                                                *pages_info.current_page_index.lock().unwrap();
-                                            set_page(
-                                                restored_page_index,
-                                                &settings,
-                                                &drawing_area_ref,
-                                                &image_container_list,
-                                                &pages_info,
-                                                &db_manager,
-                                            );
-
-                                            drawing_area_ref.queue_draw();
-                                            update_open_file_history_menu(
-                                                &open_file_history_menu,
-                                                &db_manager,
-                                                &pathname,
-                                                &pages_info,
-                                            );
-
-                                            return glib::ControlFlow::Break;
-                                        }
-                                        ResultLoadFilesWithMultiThread::Failed => {
-                                            spinner.stop();
-                                            spinner.hide();
-
-                                            update_window_title(&window, "Failed");
-                                            return glib::ControlFlow::Break;
-                                        }
-                                    }
-                                }
-                                Err(_) => {
-                                    return glib::ControlFlow::Continue;
-                                }
-                            }
-                        }
-                    ));
-                }
-            ));
+    update_window_title(&window, "Now Loading...");
+    spinner.show();
+    spinner.start();
+
+    let _source_id = glib::idle_add_local(glib::clone!(
+        #[weak]
+        window,
+        #[strong]
+        image_container_list,
+        #[strong]
+        settings,
+        #[weak_allow_none]
+        drawing_area_ref,
+        #[strong]
+        pages_info,
+        #[weak_allow_none]
+        pages_bar,
+        #[strong]
+        status_bar,
+        #[weak_allow_none]
+        spinner,
+        #[strong]
+        db_manager,
+        #[strong]
+        open_file_history_menu,
+        #[upgrade_or]
+        glib::ControlFlow::Break,
+        move || {
+            let Ok(v) = rx.try_recv() else {
+                return glib::ControlFlow::Continue;
+            };
+            let (Some(drawing_area_ref), Some(pages_bar), Some(spinner)) =
+                (drawing_area_ref, pages_bar, spinner)
+            else {
+                return glib::ControlFlow::Break;
+            };
+
+            spinner.stop();
+            spinner.hide();
+            match v {
+                ResultLoadFilesWithMultiThread::Success => finish_loading_book(
+                    &book_path,
+                    is_dir,
+                    &window,
+                    &image_container_list,
+                    &drawing_area_ref,
+                    &pages_bar,
+                    &status_bar,
+                    &settings,
+                    &pages_info,
+                    &open_file_history_menu,
+                    &db_manager,
+                ),
+                ResultLoadFilesWithMultiThread::Failed => update_window_title(&window, "Failed"),
+            }
+            glib::ControlFlow::Break
         }
+    ));
+}
+
+// the book is keyed in the history by the archive or pdf file, or by the directory of images.
+fn finish_loading_book(
+    book_path: &str,
+    is_dir: bool,
+    window: &gtk::ApplicationWindow,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    drawing_area_ref: &DrawingArea,
+    pages_bar: &gtk::Scale,
+    status_bar: &StatusBar,
+    settings: &Arc<Settings>,
+    pages_info: &Arc<PagesInfo>,
+    open_file_history_menu: &Arc<Mutex<gio::Menu>>,
+    db_manager: &Arc<Mutex<file_history::DbManager>>,
+) {
+    if is_dir {
+        *pages_info.loaded_dirname.lock().unwrap() = Some(book_path.to_owned());
+    } else {
+        *pages_info.loaded_filename.lock().unwrap() = Some(book_path.to_owned());
+    }
+    update_window_title(window, book_path);
+
+    // the page, direction and transforms are restored before the first page is scaled.
+    let max_size = image_container_list.lock().unwrap().len();
+    restore_pages_info(db_manager, settings, pages_info, book_path, max_size);
+    sync_page_direction_action_state(window, pages_info);
+
+    let restored_page_index = *pages_info.current_page_index.lock().unwrap();
+    set_page(
+        restored_page_index,
+        settings,
+        drawing_area_ref,
+        image_container_list,
+        pages_info,
+        db_manager,
+        status_bar,
+    );
+    update_pages_bar(pages_bar, settings, pages_info, max_size);
+
+    drawing_area_ref.queue_draw();
+
+    update_open_file_history_menu(
+        open_file_history_menu,
+        db_manager,
+        book_path,
+        pages_info,
+        *settings.history_size.lock().unwrap(),
+    );
+}
+
+// a book is an archive or pdf file, or a directory of images, and is saved by that path.
+fn get_book_key(pages_info: &PagesInfo) -> Option<String> {
+    if let Some(dirname) = pages_info.loaded_dirname.lock().unwrap().clone() {
+        return Some(dirname);
+    }
+
+    pages_info.loaded_filename.lock().unwrap().clone()
+}
+
+fn get_current_book_path(pages_info: &Arc<PagesInfo>) -> Option<std::path::PathBuf> {
+    get_book_key(pages_info).map(std::path::PathBuf::from)
+}
+
+fn is_book_file(path: &std::path::Path) -> bool {
+    matches!(
+        utils::detect_file_type_from_path(path),
+        utils::FileType::ZIP | utils::FileType::PDF
+    )
+}
+
+fn find_first_image_in_dir(dir_path: &std::path::Path) -> Option<std::path::PathBuf> {
+    let mut entries: Vec<std::path::PathBuf> = dir_path
+        .read_dir()
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|v| v.path())
+        .filter(|v| v.is_file())
+        .collect();
+    entries.sort_by(|a, b| compare_by_natural(&a.to_string_lossy(), &b.to_string_lossy()));
+
+    entries.into_iter().find(|v| {
+        matches!(
+            utils::detect_file_type_from_path(v),
+            utils::FileType::PNG | utils::FileType::JPG
+        )
+    })
+}
+
+fn list_sibling_books(book_path: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let Some(parent) = book_path.parent() else {
+        return vec![];
+    };
+    let Ok(tmp_entries) = parent.read_dir() else {
+        return vec![];
+    };
+
+    let is_dir_book = book_path.is_dir();
+    let mut books: Vec<std::path::PathBuf> = tmp_entries
+        .filter_map(Result::ok)
+        .map(|v| v.path())
+        .filter(|v| {
+            if is_dir_book {
+                v.is_dir() && (v == book_path || find_first_image_in_dir(v).is_some())
+            } else {
+                v.is_file() && (v == book_path || is_book_file(v))
+            }
+        })
+        .collect();
+    books.sort_by(|a, b| {
+        let a_str = a.file_name().unwrap_or_default().to_string_lossy();
+        let b_str = b.file_name().unwrap_or_default().to_string_lossy();
+        compare_by_natural(&a_str, &b_str)
+    });
+
+    books
+}
+
+fn find_adjacent_book(book_path: &std::path::Path, is_next: bool) -> Option<std::path::PathBuf> {
+    let books = list_sibling_books(book_path);
+    let position = books.iter().position(|v| v == book_path)?;
+    let target = if is_next {
+        position + 1
+    } else {
+        position.checked_sub(1)?
+    };
+    let target_path = books.get(target)?;
+
+    if target_path.is_dir() {
+        find_first_image_in_dir(target_path)
+    } else {
+        Some(target_path.clone())
+    }
+}
+
+fn open_adjacent_book(
+    is_next: bool,
+    window: &gtk::ApplicationWindow,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    drawing_area_ref: &DrawingArea,
+    pages_bar: &gtk::Scale,
+    status_bar: &StatusBar,
+    settings: &Arc<Settings>,
+    pages_info: &Arc<PagesInfo>,
+    spinner: &gtk::Spinner,
+    open_file_history_menu: &Arc<Mutex<gio::Menu>>,
+    db_manager: &Arc<Mutex<file_history::DbManager>>,
+) -> bool {
+    let Some(book_path) = get_current_book_path(pages_info) else {
+        return false;
+    };
+    let Some(target_path) = find_adjacent_book(&book_path, is_next) else {
+        return false;
     };
+
+    let file = gio::File::for_path(target_path);
+    open_file_for_action(
+        window,
+        image_container_list,
+        drawing_area_ref,
+        pages_bar,
+        status_bar,
+        settings,
+        pages_info,
+        spinner,
+        open_file_history_menu,
+        db_manager,
+        &file,
+    );
+    true
 }
 
 fn open_file_action_with_dialog(
-    app: &gtk::Application,
     window: &gtk::ApplicationWindow,
     image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
     drawing_area_ref: &DrawingArea,
-    pages_bar: &gtk::ProgressBar,
+    pages_bar: &gtk::Scale,
+    status_bar: &StatusBar,
     settings: &Arc<Settings>,
     pages_info: &Arc<PagesInfo>,
     spinner: &gtk::Spinner,
@@ -842,8 +1373,6 @@ fn open_file_action_with_dialog(
     dialog.add_filter(&file_filter);
 
     dialog.connect_response(glib::clone!(
-        #[weak]
-        app,
         #[weak]
         window,
         #[strong]
@@ -854,6 +1383,8 @@ fn open_file_action_with_dialog(
         drawing_area_ref,
         #[weak]
         pages_bar,
+        #[strong]
+        status_bar,
         #[weak]
         spinner,
         #[strong]
@@ -873,11 +1404,11 @@ fn open_file_action_with_dialog(
                 }
 
                 open_file_for_action(
-                    &app,
                     &window,
                     &image_container_list,
                     &drawing_area_ref,
                     &pages_bar,
+                    &status_bar,
                     &settings,
                     &pages_info,
                     &spinner,
@@ -893,48 +1424,1436 @@ fn open_file_action_with_dialog(
     dialog.show();
 }
 
-fn create_action_entry_for_menu(
+fn refresh_compare_view(
     window: &gtk::ApplicationWindow,
     image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    drawing_area: &DrawingArea,
+    status_bar: &StatusBar,
+    settings: &Settings,
+    pages_info: &Arc<PagesInfo>,
+) {
+    let page_index = *pages_info.current_page_index.lock().unwrap();
+    scale_pages_to_drawing_area(
+        image_container_list,
+        settings,
+        pages_info,
+        page_index,
+        drawing_area,
+    );
+    update_status_bar(status_bar, image_container_list, settings, pages_info);
+    drawing_area.queue_draw();
+    sync_compare_action_state(window, pages_info);
+}
+
+// the compared book is loaded in the background like the first one,
+// it is not added to the history and it is shown once all pages are loaded.
+fn open_compare_book(
+    file: &gio::File,
+    window: &gtk::ApplicationWindow,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    drawing_area: &DrawingArea,
+    status_bar: &StatusBar,
+    settings: &Arc<Settings>,
     pages_info: &Arc<PagesInfo>,
-    drawing_area_ref: &DrawingArea,
-    pages_bar: &gtk::ProgressBar,
-    settings: &std::sync::Arc<Settings>,
     spinner: &gtk::Spinner,
-    open_file_history_menu: &Arc<Mutex<gio::Menu>>,
-    db_manager: &Arc<Mutex<file_history::DbManager>>,
-) -> Vec<gio::ActionEntry<gtk::Application>> {
-    let open_file_action_entry: gio::ActionEntry<gtk::Application> =
-        gio::ActionEntry::builder("file_open")
-            .activate(glib::clone!(
-                #[weak]
-                window,
-                #[strong]
-                image_container_list,
-                #[strong]
-                pages_info,
-                #[strong]
-                settings,
-                #[strong]
-                drawing_area_ref,
-                #[weak]
-                pages_bar,
-                #[weak]
-                spinner,
-                #[weak]
-                open_file_history_menu,
-                #[weak]
-                db_manager,
-                move |app: &gtk::Application,
-                      _action: &gio::SimpleAction,
-                      _user_data: Option<&glib::Variant>| {
-                    open_file_action_with_dialog(
-                        app,
-                        &window,
-                        &image_container_list,
-                        &drawing_area_ref,
-                        &pages_bar,
-                        &settings,
+) {
+    let Some(pathname) = get_file_path_from_file_desc(file) else {
+        return;
+    };
+    let file_type = utils::detect_file_type_from_file(file);
+    let is_pdf = matches!(file_type, utils::FileType::PDF);
+
+    spinner.show();
+    spinner.start();
+
+    let (tx, rx) = std::sync::mpsc::sync_channel::<ResultLoadFilesWithMultiThread>(1);
+    let loaded_list: Arc<Mutex<Vec<ImageContainer>>> = Arc::new(Mutex::new(vec![]));
+    let loaded_list_cloned = Arc::clone(&loaded_list);
+    let chapter_starts: Arc<Mutex<Vec<usize>>> = Arc::new(Mutex::new(vec![]));
+    let chapter_starts_cloned = Arc::clone(&chapter_starts);
+    let pdf_pixmaps_arc: Arc<Mutex<Vec<PdfPixmap>>> = Arc::new(Mutex::new(vec![]));
+    let pdf_pixmaps_arc_cloned = Arc::clone(&pdf_pixmaps_arc);
+    let pathname_cloned = pathname.clone();
+    let file_cloned = file.clone();
+    let ppi = utils::get_dpi_for_widget(window);
+    let _ = std::thread::spawn(move || {
+        let is_loaded = match file_type {
+            utils::FileType::ZIP => open_and_set_image_to_image_container_from_zip(
+                &pathname_cloned,
+                &loaded_list_cloned,
+                &chapter_starts_cloned,
+            ),
+            utils::FileType::PDF => {
+                pdf_loader::load_pdf(&pathname_cloned, ppi, &pdf_pixmaps_arc_cloned).is_ok()
+            }
+            _ => read_dir_and_set_images(&file_cloned, &loaded_list_cloned),
+        };
+        if is_loaded {
+            tx.send(ResultLoadFilesWithMultiThread::Success).unwrap();
+        } else {
+            tx.send(ResultLoadFilesWithMultiThread::Failed).unwrap();
+        }
+    });
+
+    let _source_id = glib::idle_add_local(glib::clone!(
+        #[weak]
+        window,
+        #[strong]
+        image_container_list,
+        #[weak]
+        drawing_area,
+        #[strong]
+        status_bar,
+        #[strong]
+        settings,
+        #[strong]
+        pages_info,
+        #[weak]
+        spinner,
+        #[upgrade_or]
+        glib::ControlFlow::Break,
+        move || {
+            let Ok(result) = rx.try_recv() else {
+                return glib::ControlFlow::Continue;
+            };
+            spinner.stop();
+            spinner.hide();
+
+            match result {
+                ResultLoadFilesWithMultiThread::Success => {
+                    if is_pdf {
+                        set_image_to_image_container_from_pdf_pixmaps(
+                            &loaded_list,
+                            &pdf_pixmaps_arc,
+                        );
+                    }
+                    let compare_book = &pages_info.compare_book;
+                    *compare_book.image_container_list.lock().unwrap() =
+                        std::mem::take(&mut *loaded_list.lock().unwrap());
+                    *compare_book.loaded_filename.lock().unwrap() = Some(pathname.clone());
+                    *compare_book.chapter_starts.lock().unwrap() =
+                        std::mem::take(&mut *chapter_starts.lock().unwrap());
+                    *compare_book.page_offset.lock().unwrap() = 0;
+                }
+                ResultLoadFilesWithMultiThread::Failed => {
+                    eprintln!("failed to open {} for comparison", pathname);
+                }
+            }
+
+            refresh_compare_view(
+                &window,
+                &image_container_list,
+                &drawing_area,
+                &status_bar,
+                &settings,
+                &pages_info,
+            );
+            glib::ControlFlow::Break
+        }
+    ));
+}
+
+fn open_compare_book_with_dialog(
+    window: &gtk::ApplicationWindow,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    drawing_area: &DrawingArea,
+    status_bar: &StatusBar,
+    settings: &Arc<Settings>,
+    pages_info: &Arc<PagesInfo>,
+    spinner: &gtk::Spinner,
+) {
+    let dialog = gtk::FileChooserDialog::new(
+        Some("Compare With"),
+        Some(window),
+        gtk::FileChooserAction::Open,
+        &[
+            ("Open", gtk::ResponseType::Ok),
+            ("Cancel", gtk::ResponseType::Cancel),
+        ],
+    );
+
+    let file_filter = gtk::FileFilter::new();
+    file_filter.add_pattern("*.zip");
+    file_filter.add_pattern("*.jpg");
+    file_filter.add_pattern("*.png");
+    file_filter.add_pattern("*.pdf");
+    dialog.add_filter(&file_filter);
+
+    dialog.connect_response(glib::clone!(
+        #[weak]
+        window,
+        #[strong]
+        image_container_list,
+        #[weak]
+        drawing_area,
+        #[strong]
+        status_bar,
+        #[strong]
+        settings,
+        #[strong]
+        pages_info,
+        #[weak]
+        spinner,
+        move |file_dialog, response| {
+            if response == gtk::ResponseType::Ok {
+                let Some(file) = file_dialog.file() else {
+                    return;
+                };
+                let Some(path) = file.path() else { return };
+                if !path.is_file() {
+                    return;
+                }
+
+                open_compare_book(
+                    &file,
+                    &window,
+                    &image_container_list,
+                    &drawing_area,
+                    &status_bar,
+                    &settings,
+                    &pages_info,
+                    &spinner,
+                );
+            }
+            file_dialog.close();
+        }
+    ));
+
+    dialog.show();
+}
+
+fn get_current_page_pixbuf(
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &Arc<PagesInfo>,
+) -> Option<gtk::gdk_pixbuf::Pixbuf> {
+    let index = *pages_info.current_page_index.lock().unwrap();
+    let image_container_list_ptr = image_container_list.lock().unwrap();
+    image_container_list_ptr
+        .get(index)?
+        .get_export_pixbuf_data()
+}
+
+fn get_current_spread_pixbuf(
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &Arc<PagesInfo>,
+    settings: &Arc<Settings>,
+) -> Option<gtk::gdk_pixbuf::Pixbuf> {
+    if !*settings.is_dual_mode.lock().unwrap() {
+        return get_current_page_pixbuf(image_container_list, pages_info);
+    }
+
+    let index = *pages_info.current_page_index.lock().unwrap();
+    let page_direction = *pages_info.page_direction.lock().unwrap();
+    let image_container_list_ptr = image_container_list.lock().unwrap();
+    let first = image_container_list_ptr
+        .get(index)?
+        .get_export_pixbuf_data()?;
+    let second = image_container_list_ptr
+        .get(index + 1)
+        .and_then(|v| v.get_export_pixbuf_data());
+
+    page_export::compose_spread(
+        &first,
+        second.as_ref(),
+        page_direction,
+        *settings.spread_gutter.lock().unwrap() as i32,
+        *settings.is_spread_aligned_at_spine.lock().unwrap(),
+    )
+}
+
+fn get_export_file_stem(pages_info: &Arc<PagesInfo>) -> String {
+    let loaded_path = pages_info
+        .loaded_filename
+        .lock()
+        .unwrap()
+        .clone()
+        .or_else(|| pages_info.loaded_dirname.lock().unwrap().clone());
+    loaded_path
+        .as_deref()
+        .and_then(|v| std::path::Path::new(v).file_stem())
+        .and_then(|v| v.to_str())
+        .unwrap_or("page")
+        .to_owned()
+}
+
+fn create_export_file_name(pages_info: &Arc<PagesInfo>, is_spread: bool) -> String {
+    let index = *pages_info.current_page_index.lock().unwrap();
+    let stem = get_export_file_stem(pages_info);
+
+    if is_spread {
+        format!("{}_{:03}-{:03}.png", stem, index + 1, index + 2)
+    } else {
+        format!("{}_{:03}.png", stem, index + 1)
+    }
+}
+
+// the whole book is exported, so the name has no page number.
+fn create_pdf_export_file_name(pages_info: &Arc<PagesInfo>) -> String {
+    format!("{}.pdf", get_export_file_stem(pages_info))
+}
+
+fn save_pixbuf_action_with_dialog(
+    window: &gtk::ApplicationWindow,
+    pixbuf: gtk::gdk_pixbuf::Pixbuf,
+    file_name: &str,
+) {
+    let dialog = gtk::FileChooserDialog::new(
+        Some("Save Page"),
+        Some(window),
+        gtk::FileChooserAction::Save,
+        &[
+            ("Save", gtk::ResponseType::Ok),
+            ("Cancel", gtk::ResponseType::Cancel),
+        ],
+    );
+
+    let file_filter = gtk::FileFilter::new();
+    file_filter.add_pattern("*.png");
+    file_filter.add_pattern("*.jpg");
+    file_filter.add_pattern("*.jpeg");
+    dialog.add_filter(&file_filter);
+    dialog.set_current_name(file_name);
+
+    dialog.connect_response(move |file_dialog, response| {
+        if response == gtk::ResponseType::Ok {
+            if let Some(path) = file_dialog.file().and_then(|v| v.path()) {
+                if let Err(e) = page_export::save_pixbuf_to_file(&pixbuf, &path) {
+                    eprintln!("{}", e);
+                }
+            }
+        }
+        file_dialog.close();
+    });
+
+    dialog.show();
+}
+
+fn create_print_job(
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &Arc<PagesInfo>,
+    settings: &Arc<Settings>,
+) -> Option<page_print::PrintJob> {
+    // a page which was not loaded keeps its place, so the indices stay those of the book.
+    let pixbufs: Vec<Option<gtk::gdk_pixbuf::Pixbuf>> = image_container_list
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|v| v.get_export_pixbuf_data())
+        .collect();
+    if pixbufs.iter().all(|v| v.is_none()) {
+        return None;
+    }
+
+    Some(page_print::PrintJob {
+        pixbufs,
+        page_direction: *pages_info.page_direction.lock().unwrap(),
+        current_page_index: *pages_info.current_page_index.lock().unwrap(),
+        is_spread: *settings.is_dual_mode.lock().unwrap(),
+        spread_gutter_width: *settings.spread_gutter.lock().unwrap() as i32,
+        is_spread_aligned_at_spine: *settings.is_spread_aligned_at_spine.lock().unwrap(),
+    })
+}
+
+fn export_pdf_action_with_dialog(
+    window: &gtk::ApplicationWindow,
+    print_job: page_print::PrintJob,
+    file_name: &str,
+) {
+    let dialog = gtk::FileChooserDialog::new(
+        Some("Export to PDF"),
+        Some(window),
+        gtk::FileChooserAction::Save,
+        &[
+            ("Export", gtk::ResponseType::Ok),
+            ("Cancel", gtk::ResponseType::Cancel),
+        ],
+    );
+
+    let file_filter = gtk::FileFilter::new();
+    file_filter.add_pattern("*.pdf");
+    dialog.add_filter(&file_filter);
+    dialog.set_current_name(file_name);
+
+    let print_job = std::cell::RefCell::new(Some(print_job));
+    dialog.connect_response(glib::clone!(
+        #[weak]
+        window,
+        move |file_dialog, response| {
+            if response == gtk::ResponseType::Ok {
+                let path = file_dialog.file().and_then(|v| v.path());
+                if let (Some(path), Some(print_job)) = (path, print_job.take()) {
+                    page_print::run_print_operation(&window, print_job, Some(&path));
+                }
+            }
+            file_dialog.close();
+        }
+    ));
+
+    dialog.show();
+}
+
+// the crop box of the reader is found over a whole spread, a thumbnail finds its own.
+fn get_overview_page_view(
+    settings: &Settings,
+    pages_info: &PagesInfo,
+    page_index: usize,
+) -> page_overview::PageView {
+    let crop_box = pages_info
+        .page_crop_boxes
+        .lock()
+        .unwrap()
+        .get(&page_index)
+        .copied()
+        .or(*pages_info.book_crop_box.lock().unwrap());
+    let auto_crop_tolerance = if *settings.is_auto_crop.lock().unwrap() {
+        Some(*settings.auto_crop_tolerance.lock().unwrap())
+    } else {
+        None
+    };
+
+    page_overview::PageView {
+        transform: get_page_transform(pages_info, page_index),
+        crop_box,
+        auto_crop_tolerance,
+    }
+}
+
+fn toggle_page_overview(
+    stack: &gtk::Stack,
+    page_overview: &PageOverview,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &Arc<PagesInfo>,
+    settings: &Settings,
+) {
+    if stack.visible_child_name().as_deref() == Some("overview") {
+        stack.set_visible_child_name("reader");
+        return;
+    }
+
+    if image_container_list.lock().unwrap().is_empty() {
+        return;
+    }
+
+    let book_name = pages_info
+        .loaded_filename
+        .lock()
+        .unwrap()
+        .clone()
+        .or_else(|| pages_info.loaded_dirname.lock().unwrap().clone())
+        .unwrap_or_default();
+    let current_page_index = *pages_info.current_page_index.lock().unwrap();
+    let max_size = image_container_list.lock().unwrap().len();
+    let page_views = (0..max_size)
+        .map(|v| get_overview_page_view(settings, pages_info, v))
+        .collect();
+
+    page_overview.set_page_direction(*pages_info.page_direction.lock().unwrap());
+    page_overview.update(image_container_list, &book_name, page_views);
+    stack.set_visible_child_name("overview");
+    page_overview.set_current_page(current_page_index);
+}
+
+fn apply_settings_change(
+    change: preferences::SettingsChange,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &Arc<PagesInfo>,
+    drawing_area: &DrawingArea,
+    pages_bar: &gtk::Scale,
+    status_bar: &StatusBar,
+    settings: &Settings,
+    open_file_history_menu: &Arc<Mutex<gio::Menu>>,
+    db_manager: &Arc<Mutex<file_history::DbManager>>,
+) {
+    match change {
+        preferences::SettingsChange::Layout | preferences::SettingsChange::Scaling => {
+            if image_container_list.lock().unwrap().is_empty() {
+                return;
+            }
+            // the page of single mode may be odd, it goes to the start of its spread.
+            let page_index = *pages_info.current_page_index.lock().unwrap();
+            set_page(
+                page_index,
+                settings,
+                drawing_area,
+                image_container_list,
+                pages_info,
+                db_manager,
+                status_bar,
+            );
+            let max_size = image_container_list.lock().unwrap().len();
+            update_pages_bar(pages_bar, settings, pages_info, max_size);
+            drawing_area.queue_draw();
+        }
+        preferences::SettingsChange::ChapterMarks => {
+            let max_size = image_container_list.lock().unwrap().len();
+            update_pages_bar(pages_bar, settings, pages_info, max_size);
+        }
+        preferences::SettingsChange::Background => drawing_area.queue_draw(),
+        preferences::SettingsChange::HistorySize => set_open_file_history_menu(
+            open_file_history_menu,
+            db_manager,
+            *settings.history_size.lock().unwrap(),
+        ),
+        preferences::SettingsChange::Other => {}
+    }
+}
+
+// the settings are shared, so a change in the preferences of any window is applied by all.
+fn connect_settings_changed(
+    app: &Application,
+    window: &gtk::ApplicationWindow,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &Arc<PagesInfo>,
+    drawing_area: &DrawingArea,
+    pages_bar: &gtk::Scale,
+    status_bar: &StatusBar,
+    settings: &Arc<Settings>,
+    open_file_history_menu: &Arc<Mutex<gio::Menu>>,
+    db_manager: &Arc<Mutex<file_history::DbManager>>,
+) {
+    let Some(action) = app
+        .lookup_action("settings_changed")
+        .and_downcast::<gio::SimpleAction>()
+    else {
+        return;
+    };
+
+    let handler_id = action.connect_activate(glib::clone!(
+        #[strong]
+        image_container_list,
+        #[strong]
+        pages_info,
+        #[weak]
+        drawing_area,
+        #[weak]
+        pages_bar,
+        #[strong]
+        status_bar,
+        #[strong]
+        settings,
+        #[strong]
+        open_file_history_menu,
+        #[strong]
+        db_manager,
+        move |_action: &gio::SimpleAction, parameter: Option<&glib::Variant>| {
+            let Some(change) = parameter
+                .and_then(|v| v.str())
+                .and_then(|v| preferences::SettingsChange::from_str(v).ok())
+            else {
+                return;
+            };
+            apply_settings_change(
+                change,
+                &image_container_list,
+                &pages_info,
+                &drawing_area,
+                &pages_bar,
+                &status_bar,
+                &settings,
+                &open_file_history_menu,
+                &db_manager,
+            );
+        }
+    ));
+
+    // the action stays with the app, so the handler is removed with the window.
+    let handler_id = std::cell::RefCell::new(Some(handler_id));
+    window.connect_destroy(move |_window| {
+        if let Some(handler_id) = handler_id.take() {
+            action.disconnect(handler_id);
+        }
+    });
+}
+
+fn restore_last_book(
+    window: &gtk::ApplicationWindow,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    drawing_area_ref: &DrawingArea,
+    pages_bar: &gtk::Scale,
+    status_bar: &StatusBar,
+    settings: &Arc<Settings>,
+    pages_info: &Arc<PagesInfo>,
+    spinner: &gtk::Spinner,
+    open_file_history_menu: &Arc<Mutex<gio::Menu>>,
+    db_manager: &Arc<Mutex<file_history::DbManager>>,
+) {
+    if !*settings.is_restore_session.lock().unwrap() {
+        return;
+    }
+
+    let last_history = db_manager.lock().unwrap().get_history(1).into_iter().next();
+    let Some(last_history) = last_history else {
+        return;
+    };
+    let path = std::path::Path::new(&last_history.location_path);
+    if !path.exists() {
+        return;
+    }
+
+    // the saved page is restored from the history while opening.
+    let file = gio::File::for_path(path);
+    open_file_for_action(
+        window,
+        image_container_list,
+        drawing_area_ref,
+        pages_bar,
+        status_bar,
+        settings,
+        pages_info,
+        spinner,
+        open_file_history_menu,
+        db_manager,
+        &file,
+    );
+}
+
+fn create_move_page_action_entry(
+    action_name: &str,
+    get_move_page_number_fn: fn(&Settings, &Arc<PagesInfo>) -> i64,
+    window: &gtk::ApplicationWindow,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &Arc<PagesInfo>,
+    drawing_area_ref: &DrawingArea,
+    pages_bar: &gtk::Scale,
+    status_bar: &StatusBar,
+    settings: &Arc<Settings>,
+    spinner: &gtk::Spinner,
+    open_file_history_menu: &Arc<Mutex<gio::Menu>>,
+    db_manager: &Arc<Mutex<file_history::DbManager>>,
+) -> gio::ActionEntry<gtk::ApplicationWindow> {
+    gio::ActionEntry::builder(action_name)
+        .activate(glib::clone!(
+            #[weak]
+            window,
+            #[strong]
+            image_container_list,
+            #[strong]
+            pages_info,
+            #[weak]
+            drawing_area_ref,
+            #[weak]
+            pages_bar,
+            #[strong]
+            status_bar,
+            #[strong]
+            settings,
+            #[weak]
+            spinner,
+            #[strong]
+            open_file_history_menu,
+            #[strong]
+            db_manager,
+            move |_window: &gtk::ApplicationWindow,
+                  _action: &gio::SimpleAction,
+                  _user_data: Option<&glib::Variant>| {
+                if image_container_list.lock().unwrap().is_empty() {
+                    return;
+                }
+
+                move_page_or_open_next_book(
+                    get_move_page_number_fn(&settings, &pages_info),
+                    &window,
+                    &image_container_list,
+                    &drawing_area_ref,
+                    &pages_bar,
+                    &status_bar,
+                    &settings,
+                    &pages_info,
+                    &spinner,
+                    &open_file_history_menu,
+                    &db_manager,
+                );
+            }
+        ))
+        .build()
+}
+
+// a change of a single page is kept on top of the transform of the book.
+fn create_transform_action_entry(
+    name: &str,
+    is_book: bool,
+    change: fn(PageTransform) -> PageTransform,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &Arc<PagesInfo>,
+    drawing_area_ref: &DrawingArea,
+    status_bar: &StatusBar,
+    settings: &Arc<Settings>,
+    db_manager: &Arc<Mutex<file_history::DbManager>>,
+) -> gio::ActionEntry<gtk::ApplicationWindow> {
+    gio::ActionEntry::builder(name)
+        .activate(glib::clone!(
+            #[strong]
+            image_container_list,
+            #[strong]
+            pages_info,
+            #[weak]
+            drawing_area_ref,
+            #[strong]
+            status_bar,
+            #[strong]
+            settings,
+            #[strong]
+            db_manager,
+            move |_window: &gtk::ApplicationWindow,
+                  _action: &gio::SimpleAction,
+                  _user_data: Option<&glib::Variant>| {
+                if image_container_list.lock().unwrap().is_empty() {
+                    return;
+                }
+
+                let page_index = *pages_info.current_page_index.lock().unwrap();
+                if is_book {
+                    let mut book_transform = pages_info.book_transform.lock().unwrap();
+                    *book_transform = change(*book_transform);
+                } else {
+                    let mut page_transforms = pages_info.page_transforms.lock().unwrap();
+                    let page_transform = page_transforms.entry(page_index).or_default();
+                    *page_transform = change(*page_transform);
+                }
+                save_page_transforms(&db_manager, &pages_info);
+
+                scale_pages_to_drawing_area(
+                    &image_container_list,
+                    &settings,
+                    &pages_info,
+                    page_index,
+                    &drawing_area_ref,
+                );
+                update_status_bar(&status_bar, &image_container_list, &settings, &pages_info);
+                drawing_area_ref.queue_draw();
+            }
+        ))
+        .build()
+}
+
+// the offset moves only the compared book, for books whose page numbering does not match.
+fn create_compare_offset_action_entry(
+    name: &str,
+    step: i64,
+    window: &gtk::ApplicationWindow,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &Arc<PagesInfo>,
+    drawing_area_ref: &DrawingArea,
+    status_bar: &StatusBar,
+    settings: &Arc<Settings>,
+) -> gio::ActionEntry<gtk::ApplicationWindow> {
+    gio::ActionEntry::builder(name)
+        .activate(glib::clone!(
+            #[weak]
+            window,
+            #[strong]
+            image_container_list,
+            #[strong]
+            pages_info,
+            #[weak]
+            drawing_area_ref,
+            #[strong]
+            status_bar,
+            #[strong]
+            settings,
+            move |_window: &gtk::ApplicationWindow,
+                  _action: &gio::SimpleAction,
+                  _user_data: Option<&glib::Variant>| {
+                let compare_book = &pages_info.compare_book;
+                let max_size = compare_book.image_container_list.lock().unwrap().len();
+                let page_offset = *compare_book.page_offset.lock().unwrap() + step;
+                *compare_book.page_offset.lock().unwrap() =
+                    page_progress::clamp_compare_page_offset(page_offset, max_size);
+                refresh_compare_view(
+                    &window,
+                    &image_container_list,
+                    &drawing_area_ref,
+                    &status_bar,
+                    &settings,
+                    &pages_info,
+                );
+            }
+        ))
+        .build()
+}
+
+fn create_action_entry_for_menu(
+    window: &gtk::ApplicationWindow,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &Arc<PagesInfo>,
+    drawing_area_ref: &DrawingArea,
+    pages_bar: &gtk::Scale,
+    status_bar: &StatusBar,
+    settings: &std::sync::Arc<Settings>,
+    spinner: &gtk::Spinner,
+    open_file_history_menu: &Arc<Mutex<gio::Menu>>,
+    db_manager: &Arc<Mutex<file_history::DbManager>>,
+    stack: &gtk::Stack,
+    page_overview: &PageOverview,
+    key_bindings: &Rc<Vec<KeyBinding>>,
+) -> Vec<gio::ActionEntry<gtk::ApplicationWindow>> {
+    let page_left_action_entry = create_move_page_action_entry(
+        "page_left",
+        |settings, pages_info| get_move_page_number_by_side(true, settings, pages_info),
+        window,
+        image_container_list,
+        pages_info,
+        drawing_area_ref,
+        pages_bar,
+        status_bar,
+        settings,
+        spinner,
+        open_file_history_menu,
+        db_manager,
+    );
+    let page_right_action_entry = create_move_page_action_entry(
+        "page_right",
+        |settings, pages_info| get_move_page_number_by_side(false, settings, pages_info),
+        window,
+        image_container_list,
+        pages_info,
+        drawing_area_ref,
+        pages_bar,
+        status_bar,
+        settings,
+        spinner,
+        open_file_history_menu,
+        db_manager,
+    );
+    let next_page_action_entry = create_move_page_action_entry(
+        "next_page",
+        |settings, _pages_info| get_move_page_number_by_order(true, settings),
+        window,
+        image_container_list,
+        pages_info,
+        drawing_area_ref,
+        pages_bar,
+        status_bar,
+        settings,
+        spinner,
+        open_file_history_menu,
+        db_manager,
+    );
+    let previous_page_action_entry = create_move_page_action_entry(
+        "previous_page",
+        |settings, _pages_info| get_move_page_number_by_order(false, settings),
+        window,
+        image_container_list,
+        pages_info,
+        drawing_area_ref,
+        pages_bar,
+        status_bar,
+        settings,
+        spinner,
+        open_file_history_menu,
+        db_manager,
+    );
+
+    let fullscreen_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("fullscreen")
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[weak]
+                pages_bar,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    fullscreen(&window, &pages_bar);
+                }
+            ))
+            .build();
+
+    let leave_fullscreen_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("leave_fullscreen")
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[weak]
+                pages_bar,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    if window.is_fullscreen() {
+                        fullscreen(&window, &pages_bar);
+                    }
+                }
+            ))
+            .build();
+
+    let compare_open_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("compare_open")
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[weak]
+                drawing_area_ref,
+                #[strong]
+                status_bar,
+                #[strong]
+                settings,
+                #[weak]
+                spinner,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    open_compare_book_with_dialog(
+                        &window,
+                        &image_container_list,
+                        &drawing_area_ref,
+                        &status_bar,
+                        &settings,
+                        &pages_info,
+                        &spinner,
+                    );
+                }
+            ))
+            .build();
+
+    let compare_close_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("compare_close")
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[weak]
+                drawing_area_ref,
+                #[strong]
+                status_bar,
+                #[strong]
+                settings,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    let compare_book = &pages_info.compare_book;
+                    compare_book.image_container_list.lock().unwrap().clear();
+                    *compare_book.loaded_filename.lock().unwrap() = None;
+                    compare_book.chapter_starts.lock().unwrap().clear();
+                    *compare_book.page_offset.lock().unwrap() = 0;
+                    refresh_compare_view(
+                        &window,
+                        &image_container_list,
+                        &drawing_area_ref,
+                        &status_bar,
+                        &settings,
+                        &pages_info,
+                    );
+                }
+            ))
+            .build();
+
+    let compare_difference_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("compare_difference")
+            .state(false.to_variant())
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[weak]
+                drawing_area_ref,
+                #[strong]
+                status_bar,
+                #[strong]
+                settings,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    {
+                        let mut is_difference =
+                            pages_info.compare_book.is_difference.lock().unwrap();
+                        *is_difference = !*is_difference;
+                    }
+                    refresh_compare_view(
+                        &window,
+                        &image_container_list,
+                        &drawing_area_ref,
+                        &status_bar,
+                        &settings,
+                        &pages_info,
+                    );
+                }
+            ))
+            .build();
+
+    let compare_offset_forward_action_entry = create_compare_offset_action_entry(
+        "compare_offset_forward",
+        1,
+        window,
+        image_container_list,
+        pages_info,
+        drawing_area_ref,
+        status_bar,
+        settings,
+    );
+    let compare_offset_backward_action_entry = create_compare_offset_action_entry(
+        "compare_offset_backward",
+        -1,
+        window,
+        image_container_list,
+        pages_info,
+        drawing_area_ref,
+        status_bar,
+        settings,
+    );
+
+    let preferences_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("preferences")
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[strong]
+                settings,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    preferences::show_preferences_window(
+                        &window,
+                        &settings,
+                        glib::clone!(
+                            #[weak]
+                            window,
+                            move |change: preferences::SettingsChange| {
+                                let Some(app) = window.application() else {
+                                    return;
+                                };
+                                gio::prelude::ActionGroupExt::activate_action(
+                                    &app,
+                                    "settings_changed",
+                                    Some(&change.as_str().to_variant()),
+                                );
+                            }
+                        ),
+                    );
+                }
+            ))
+            .build();
+
+    let shortcuts_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("shortcuts")
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[strong]
+                key_bindings,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    let Some(shortcuts_window) =
+                        key_bindings::create_shortcuts_window(&key_bindings)
+                    else {
+                        return;
+                    };
+                    shortcuts_window.set_transient_for(Some(&window));
+                    shortcuts_window.present();
+                }
+            ))
+            .build();
+
+    let open_file_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("file_open")
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[strong]
+                settings,
+                #[strong]
+                drawing_area_ref,
+                #[weak]
+                pages_bar,
+                #[strong]
+                status_bar,
+                #[weak]
+                spinner,
+                #[weak]
+                open_file_history_menu,
+                #[weak]
+                db_manager,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    open_file_action_with_dialog(
+                        &window,
+                        &image_container_list,
+                        &drawing_area_ref,
+                        &pages_bar,
+                        &status_bar,
+                        &settings,
+                        &pages_info,
+                        &spinner,
+                        &open_file_history_menu,
+                        &db_manager,
+                    );
+                }
+            ))
+            .build();
+
+    let open_file_from_history_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("open_file_from_history")
+            .parameter_type(Some(VariantTy::STRING))
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[strong]
+                settings,
+                #[strong]
+                drawing_area_ref,
+                #[weak]
+                pages_bar,
+                #[strong]
+                status_bar,
+                #[weak]
+                spinner,
+                #[weak]
+                open_file_history_menu,
+                #[weak]
+                db_manager,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    let path_str = _user_data
+                        .expect("failed get userdata in open_file_from_history_action_entry")
+                        .get::<String>()
+                        .expect("failed get variant in open_file_from_history_action_entry");
+                    let file = gio::File::for_path(path_str);
+                    open_file_for_action(
+                        &window,
+                        &image_container_list,
+                        &drawing_area_ref,
+                        &pages_bar,
+                        &status_bar,
+                        &settings,
+                        &pages_info,
+                        &spinner,
+                        &open_file_history_menu,
+                        &db_manager,
+                        &file,
+                    );
+                }
+            ))
+            .build();
+
+    let save_page_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("save_page")
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    let Some(pixbuf) = get_current_page_pixbuf(&image_container_list, &pages_info)
+                    else {
+                        return;
+                    };
+                    let file_name = create_export_file_name(&pages_info, false);
+                    save_pixbuf_action_with_dialog(&window, pixbuf, &file_name);
+                }
+            ))
+            .build();
+
+    let save_spread_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("save_spread")
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[strong]
+                settings,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    let Some(pixbuf) =
+                        get_current_spread_pixbuf(&image_container_list, &pages_info, &settings)
+                    else {
+                        return;
+                    };
+                    let is_spread = *settings.is_dual_mode.lock().unwrap();
+                    let file_name = create_export_file_name(&pages_info, is_spread);
+                    save_pixbuf_action_with_dialog(&window, pixbuf, &file_name);
+                }
+            ))
+            .build();
+
+    let copy_page_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("copy_page")
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    let Some(pixbuf) = get_current_page_pixbuf(&image_container_list, &pages_info)
+                    else {
+                        return;
+                    };
+                    page_export::copy_pixbuf_to_clipboard(&window, &pixbuf);
+                }
+            ))
+            .build();
+
+    let copy_spread_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("copy_spread")
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[strong]
+                settings,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    let Some(pixbuf) =
+                        get_current_spread_pixbuf(&image_container_list, &pages_info, &settings)
+                    else {
+                        return;
+                    };
+                    page_export::copy_pixbuf_to_clipboard(&window, &pixbuf);
+                }
+            ))
+            .build();
+
+    let print_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("print")
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[strong]
+                settings,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    let Some(print_job) =
+                        create_print_job(&image_container_list, &pages_info, &settings)
+                    else {
+                        return;
+                    };
+                    page_print::run_print_operation(&window, print_job, None);
+                }
+            ))
+            .build();
+
+    let export_pdf_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("export_pdf")
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[strong]
+                settings,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    let Some(print_job) =
+                        create_print_job(&image_container_list, &pages_info, &settings)
+                    else {
+                        return;
+                    };
+                    let file_name = create_pdf_export_file_name(&pages_info);
+                    export_pdf_action_with_dialog(&window, print_job, &file_name);
+                }
+            ))
+            .build();
+
+    let go_to_page_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("go_to_page")
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[strong]
+                settings,
+                #[weak]
+                drawing_area_ref,
+                #[weak]
+                pages_bar,
+                #[strong]
+                status_bar,
+                #[strong]
+                db_manager,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    go_to_page_action_with_dialog(
+                        &window,
+                        &image_container_list,
+                        &pages_info,
+                        &drawing_area_ref,
+                        &pages_bar,
+                        &status_bar,
+                        &settings,
+                        &db_manager,
+                    );
+                }
+            ))
+            .build();
+
+    let first_page_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("first_page")
+            .activate(glib::clone!(
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[strong]
+                settings,
+                #[weak]
+                drawing_area_ref,
+                #[weak]
+                pages_bar,
+                #[strong]
+                status_bar,
+                #[strong]
+                db_manager,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    jump_to_page(
+                        0,
+                        &settings,
+                        &drawing_area_ref,
+                        &pages_bar,
+                        &status_bar,
+                        &image_container_list,
+                        &pages_info,
+                        &db_manager,
+                    );
+                }
+            ))
+            .build();
+
+    let last_page_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("last_page")
+            .activate(glib::clone!(
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[strong]
+                settings,
+                #[weak]
+                drawing_area_ref,
+                #[weak]
+                pages_bar,
+                #[strong]
+                status_bar,
+                #[strong]
+                db_manager,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    let max_size = image_container_list.lock().unwrap().len();
+                    let is_dual = *settings.is_dual_mode.lock().unwrap();
+                    jump_to_page(
+                        page_progress::get_last_page_index(max_size, is_dual),
+                        &settings,
+                        &drawing_area_ref,
+                        &pages_bar,
+                        &status_bar,
+                        &image_container_list,
+                        &pages_info,
+                        &db_manager,
+                    );
+                }
+            ))
+            .build();
+
+    let page_overview_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("page_overview")
+            .activate(glib::clone!(
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[strong]
+                settings,
+                #[weak]
+                stack,
+                #[strong]
+                page_overview,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    toggle_page_overview(
+                        &stack,
+                        &page_overview,
+                        &image_container_list,
+                        &pages_info,
+                        &settings,
+                    );
+                }
+            ))
+            .build();
+
+    let next_book_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("next_book")
+            .activate(glib::clone!(
+                #[weak]
+                window,
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[strong]
+                settings,
+                #[strong]
+                drawing_area_ref,
+                #[weak]
+                pages_bar,
+                #[strong]
+                status_bar,
+                #[weak]
+                spinner,
+                #[weak]
+                open_file_history_menu,
+                #[weak]
+                db_manager,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    open_adjacent_book(
+                        true,
+                        &window,
+                        &image_container_list,
+                        &drawing_area_ref,
+                        &pages_bar,
+                        &status_bar,
+                        &settings,
                         &pages_info,
                         &spinner,
                         &open_file_history_menu,
@@ -944,9 +2863,8 @@ fn create_action_entry_for_menu(
             ))
             .build();
 
-    let open_file_from_history_action_entry: gio::ActionEntry<gtk::Application> =
-        gio::ActionEntry::builder("open_file_from_history")
-            .parameter_type(Some(VariantTy::STRING))
+    let previous_book_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("previous_book")
             .activate(glib::clone!(
                 #[weak]
                 window,
@@ -960,59 +2878,253 @@ fn create_action_entry_for_menu(
                 drawing_area_ref,
                 #[weak]
                 pages_bar,
+                #[strong]
+                status_bar,
                 #[weak]
                 spinner,
                 #[weak]
                 open_file_history_menu,
                 #[weak]
                 db_manager,
-                move |_app: &gtk::Application,
+                move |_window: &gtk::ApplicationWindow,
                       _action: &gio::SimpleAction,
                       _user_data: Option<&glib::Variant>| {
-                    let path_str = _user_data
-                        .expect("failed get userdata in open_file_from_history_action_entry")
-                        .get::<String>()
-                        .expect("failed get variant in open_file_from_history_action_entry");
-                    let file = gio::File::for_path(path_str);
-                    open_file_for_action(
-                        _app,
+                    open_adjacent_book(
+                        false,
                         &window,
                         &image_container_list,
                         &drawing_area_ref,
                         &pages_bar,
+                        &status_bar,
+                        &settings,
+                        &pages_info,
+                        &spinner,
+                        &open_file_history_menu,
+                        &db_manager,
+                    );
+                }
+            ))
+            .build();
+
+    let auto_open_next_book_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("auto_open_next_book")
+            .state(settings.is_auto_open_next_book.lock().unwrap().to_variant())
+            .activate(glib::clone!(
+                #[strong]
+                settings,
+                move |_window: &gtk::ApplicationWindow,
+                      action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    let Some(state) = action.state().and_then(|v| v.get::<bool>()) else {
+                        return;
+                    };
+                    *settings.is_auto_open_next_book.lock().unwrap() = !state;
+                    settings.save();
+                    action.set_state(&(!state).to_variant());
+                }
+            ))
+            .build();
+
+    let status_bar_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("status_bar")
+            .state(settings.is_show_status_bar.lock().unwrap().to_variant())
+            .activate(glib::clone!(
+                #[strong]
+                settings,
+                #[strong]
+                status_bar,
+                move |_window: &gtk::ApplicationWindow,
+                      action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    let Some(state) = action.state().and_then(|v| v.get::<bool>()) else {
+                        return;
+                    };
+                    *settings.is_show_status_bar.lock().unwrap() = !state;
+                    settings.save();
+                    status_bar.widget.set_visible(!state);
+                    action.set_state(&(!state).to_variant());
+                }
+            ))
+            .build();
+
+    let auto_crop_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("auto_crop")
+            .state(settings.is_auto_crop.lock().unwrap().to_variant())
+            .activate(glib::clone!(
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[weak]
+                drawing_area_ref,
+                #[strong]
+                status_bar,
+                #[strong]
+                settings,
+                move |_window: &gtk::ApplicationWindow,
+                      action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    let Some(state) = action.state().and_then(|v| v.get::<bool>()) else {
+                        return;
+                    };
+                    *settings.is_auto_crop.lock().unwrap() = !state;
+                    settings.save();
+                    action.set_state(&(!state).to_variant());
+                    if !image_container_list.lock().unwrap().is_empty() {
+                        refresh_current_pages(
+                            &image_container_list,
+                            &pages_info,
+                            &drawing_area_ref,
+                            &status_bar,
+                            &settings,
+                        );
+                    }
+                }
+            ))
+            .build();
+
+    let edit_crop_box_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("edit_crop_box")
+            .activate(glib::clone!(
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[weak]
+                drawing_area_ref,
+                #[strong]
+                status_bar,
+                #[strong]
+                settings,
+                #[strong]
+                db_manager,
+                move |window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    edit_crop_box_with_dialog(
+                        window,
+                        &image_container_list,
+                        &pages_info,
+                        &drawing_area_ref,
+                        &status_bar,
+                        &settings,
+                        &db_manager,
+                    );
+                }
+            ))
+            .build();
+
+    let adjustments_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("adjustments")
+            .activate(glib::clone!(
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[weak]
+                drawing_area_ref,
+                #[strong]
+                status_bar,
+                #[strong]
+                settings,
+                #[strong]
+                db_manager,
+                move |window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    let is_book = pages_info.book_adjustments.lock().unwrap().is_some();
+                    let has_book = get_book_key(&pages_info).is_some();
+                    adjustments_panel::show_adjustments_window(
+                        window,
+                        get_image_adjustments(&settings, &pages_info),
+                        is_book,
+                        has_book,
+                        glib::clone!(
+                            #[strong]
+                            image_container_list,
+                            #[strong]
+                            pages_info,
+                            #[weak]
+                            drawing_area_ref,
+                            #[strong]
+                            status_bar,
+                            #[strong]
+                            settings,
+                            #[strong]
+                            db_manager,
+                            move |adjustments: ImageAdjustments, is_book: bool| {
+                                set_image_adjustments(
+                                    adjustments,
+                                    is_book,
+                                    &image_container_list,
+                                    &pages_info,
+                                    &drawing_area_ref,
+                                    &status_bar,
+                                    &settings,
+                                    &db_manager,
+                                );
+                            }
+                        ),
+                    );
+                }
+            ))
+            .build();
+
+    let invert_colors_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
+        gio::ActionEntry::builder("invert_colors")
+            .activate(glib::clone!(
+                #[strong]
+                image_container_list,
+                #[strong]
+                pages_info,
+                #[weak]
+                drawing_area_ref,
+                #[strong]
+                status_bar,
+                #[strong]
+                settings,
+                #[strong]
+                db_manager,
+                move |_window: &gtk::ApplicationWindow,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    let mut adjustments = get_image_adjustments(&settings, &pages_info);
+                    adjustments.is_inverted = !adjustments.is_inverted;
+                    let is_book = pages_info.book_adjustments.lock().unwrap().is_some();
+                    set_image_adjustments(
+                        adjustments,
+                        is_book,
+                        &image_container_list,
+                        &pages_info,
+                        &drawing_area_ref,
+                        &status_bar,
                         &settings,
-                        &pages_info,
-                        &spinner,
-                        &open_file_history_menu,
                         &db_manager,
-                        &file,
                     );
                 }
             ))
             .build();
 
-    let quit_action_entry: gio::ActionEntry<gtk::Application> = gio::ActionEntry::builder("quit")
-        .activate(
-            move |app: &gtk::Application,
-                  _action: &gio::SimpleAction,
-                  _user_data: Option<&glib::Variant>| {
-                app.quit();
-            },
-        )
-        .build();
-
-    let view_action_entry: gio::ActionEntry<gtk::Application> =
+    let view_action_entry: gio::ActionEntry<gtk::ApplicationWindow> =
         gio::ActionEntry::builder("page-direction")
             .state(PageDirection::RightToLeft.as_str().to_variant())
             .parameter_type(Some(&String::static_variant_type()))
             .change_state(glib::clone!(
+                #[strong]
+                image_container_list,
                 #[strong]
                 pages_info,
+                #[strong]
+                settings,
                 #[weak]
                 drawing_area_ref,
                 #[weak]
+                pages_bar,
+                #[strong]
+                status_bar,
+                #[weak]
                 db_manager,
-                move |_app: &gtk::Application,
+                move |_window: &gtk::ApplicationWindow,
                       action: &gio::SimpleAction,
                       _user_data: Option<&glib::Variant>| {
                     let Some(user_data) = _user_data else {
@@ -1027,284 +3139,1086 @@ fn create_action_entry_for_menu(
                         return;
                     };
 
-                    let loaded_filename = pages_info.loaded_filename.lock().unwrap().clone();
+                    let book_key = get_book_key(&pages_info);
                     change_page_direction(
                         &db_manager,
                         &pages_info,
                         page_direction,
-                        loaded_filename.as_deref(),
+                        book_key.as_deref(),
                     );
                     action.set_state(&page_direction.as_str().to_variant());
+                    let max_size = image_container_list.lock().unwrap().len();
+                    update_pages_bar(&pages_bar, &settings, &pages_info, max_size);
+                    update_status_bar(&status_bar, &image_container_list, &settings, &pages_info);
                     drawing_area_ref.queue_draw();
                 }
             ))
             .build();
 
-    let result: Vec<gio::ActionEntry<gtk::Application>> = vec![
-        open_file_action_entry,
-        open_file_from_history_action_entry,
-        quit_action_entry,
-        view_action_entry,
-    ];
-    result
+    let transform_action_entries = TRANSFORM_ACTIONS.iter().map(|(name, is_book, change)| {
+        create_transform_action_entry(
+            name,
+            *is_book,
+            *change,
+            image_container_list,
+            pages_info,
+            drawing_area_ref,
+            status_bar,
+            settings,
+            db_manager,
+        )
+    });
+
+    let mut result: Vec<gio::ActionEntry<gtk::ApplicationWindow>> = vec![
+        open_file_action_entry,
+        open_file_from_history_action_entry,
+        save_page_action_entry,
+        save_spread_action_entry,
+        copy_page_action_entry,
+        copy_spread_action_entry,
+        print_action_entry,
+        export_pdf_action_entry,
+        go_to_page_action_entry,
+        first_page_action_entry,
+        last_page_action_entry,
+        page_overview_action_entry,
+        page_left_action_entry,
+        page_right_action_entry,
+        next_page_action_entry,
+        previous_page_action_entry,
+        fullscreen_action_entry,
+        leave_fullscreen_action_entry,
+        status_bar_action_entry,
+        auto_crop_action_entry,
+        edit_crop_box_action_entry,
+        adjustments_action_entry,
+        invert_colors_action_entry,
+        compare_open_action_entry,
+        compare_close_action_entry,
+        compare_difference_action_entry,
+        compare_offset_forward_action_entry,
+        compare_offset_backward_action_entry,
+        preferences_action_entry,
+        shortcuts_action_entry,
+        next_book_action_entry,
+        previous_book_action_entry,
+        auto_open_next_book_action_entry,
+        view_action_entry,
+    ];
+    result.extend(transform_action_entries);
+    result
+}
+
+fn draw_single_page(
+    image_container_list: &Vec<ImageContainer>,
+    index: usize,
+    area_width: i32,
+    area_height: i32,
+    ctx: &cairo::Context,
+) -> Vec<PagePlacement> {
+    let Some(modified) = image_container_list[index].get_modified_pixbuf_data() else {
+        return vec![];
+    };
+    let format = if modified.has_alpha() {
+        cairo::Format::ARgb32
+    } else {
+        cairo::Format::Rgb24
+    };
+    let pix_w = modified.width();
+    let pix_h = modified.height();
+    let Ok(surface) = cairo::ImageSurface::create(format, pix_w, pix_h) else {
+        return vec![];
+    };
+
+    let margin = calc_margin_for_single(&modified, area_width, area_height);
+    let left_margin = f64::from(margin.left_margin);
+    let top_margin = f64::from(margin.top_margin);
+
+    let _ = ctx.set_source_surface(&surface, left_margin, top_margin);
+    let _ = ctx.set_source_pixbuf(&modified, left_margin, top_margin);
+    let _ = ctx.paint();
+    vec![PagePlacement::new(
+        index,
+        left_margin,
+        top_margin,
+        &modified,
+    )]
+}
+
+fn draw_right_to_left_when_dual(
+    image_container_list: &Vec<ImageContainer>,
+    pages_info: &PagesInfo,
+    settings: &Settings,
+    area_width: i32,
+    area_height: i32,
+    ctx: &cairo::Context,
+    right_index: usize,
+    left_index: usize,
+) -> Vec<PagePlacement> {
+    let half_area_width = area_width / 2;
+
+    let Some(right) = image_container_list[right_index].get_modified_pixbuf_data() else {
+        return vec![];
+    };
+    let right_format = if right.has_alpha() {
+        cairo::Format::ARgb32
+    } else {
+        cairo::Format::Rgb24
+    };
+    let pix_w = right.width();
+    let pix_h = right.height();
+    let Ok(surface_for_right) = cairo::ImageSurface::create(right_format, pix_w, pix_h) else {
+        return vec![];
+    };
+
+    let right_pos = if pix_w <= half_area_width {
+        f64::from((half_area_width - pix_w) + pix_w)
+    } else {
+        0.0
+    };
+
+    if left_index >= image_container_list.len() {
+        let margin = calc_margin_for_single(&right, area_width, area_height);
+        let top_margin = f64::from(margin.top_margin);
+
+        let _ = ctx.set_source_surface(&surface_for_right, right_pos, top_margin);
+        let _ = ctx.set_source_pixbuf(&right, right_pos, top_margin);
+        let _ = ctx.paint();
+        return vec![PagePlacement::new(
+            right_index,
+            right_pos,
+            top_margin,
+            &right,
+        )];
+    }
+
+    let Some(left) = image_container_list[left_index].get_modified_pixbuf_data() else {
+        let _ = ctx.set_source_surface(&surface_for_right, right_pos, 0.0);
+        let _ = ctx.set_source_pixbuf(&right, right_pos, 0.0);
+        let _ = ctx.paint();
+        return vec![PagePlacement::new(right_index, right_pos, 0.0, &right)];
+    };
+    let left_format = if left.has_alpha() {
+        cairo::Format::ARgb32
+    } else {
+        cairo::Format::Rgb24
+    };
+
+    let margin = calc_margin_for_dual(
+        &left,
+        &right,
+        area_width,
+        area_height,
+        *pages_info.spread_gutter_width.lock().unwrap(),
+        *settings.is_spread_aligned_at_spine.lock().unwrap(),
+    );
+    let final_left_margin = f64::from(margin.left_margin);
+    let right_margin = f64::from(margin.left_margin_for_right);
+    let top_margin_for_left = f64::from(margin.top_margin_for_left);
+    let top_margin_for_right = f64::from(margin.top_margin_for_right);
+
+    let _ = ctx.set_source_surface(&surface_for_right, right_margin, top_margin_for_right);
+    let _ = ctx.set_source_pixbuf(&right, right_margin, top_margin_for_right);
+    let _ = ctx.paint();
+    let right_placement =
+        PagePlacement::new(right_index, right_margin, top_margin_for_right, &right);
+
+    let Ok(surface_for_left) =
+        cairo::ImageSurface::create(left_format, left.width(), left.height())
+    else {
+        return vec![right_placement];
+    };
+    let _ = ctx.set_source_surface(&surface_for_left, final_left_margin, top_margin_for_left);
+    let _ = ctx.set_source_pixbuf(&left, final_left_margin, top_margin_for_left);
+    let _ = ctx.paint();
+    vec![
+        right_placement,
+        PagePlacement::new(left_index, final_left_margin, top_margin_for_left, &left),
+    ]
+}
+
+fn draw_left_to_right_when_dual(
+    image_container_list: &Vec<ImageContainer>,
+    pages_info: &PagesInfo,
+    settings: &Settings,
+    area_width: i32,
+    area_height: i32,
+    ctx: &cairo::Context,
+    right_index: usize,
+    left_index: usize,
+) -> Vec<PagePlacement> {
+    // initial val left: 0, right: 1
+    let Some(left) = image_container_list[left_index].get_modified_pixbuf_data() else {
+        return vec![];
+    };
+    let left_format = if left.has_alpha() {
+        cairo::Format::ARgb32
+    } else {
+        cairo::Format::Rgb24
+    };
+    let left_pix_w = left.width();
+    let left_pix_h = left.height();
+    let Ok(surface_for_left) = cairo::ImageSurface::create(left_format, left_pix_w, left_pix_h) else {
+        return vec![];
+    };
+
+    let left_pos = 0.0;
+
+    if right_index >= image_container_list.len() {
+        let margin = calc_margin_for_single(&left, area_width, area_height);
+        let top_margin = f64::from(margin.top_margin);
+
+        let _ = ctx.set_source_surface(&surface_for_left, left_pos, top_margin);
+        let _ = ctx.set_source_pixbuf(&left, left_pos, top_margin);
+        let _ = ctx.paint();
+        return vec![PagePlacement::new(left_index, left_pos, top_margin, &left)];
+    }
+
+    let Some(right) = image_container_list[right_index].get_modified_pixbuf_data() else {
+        let _ = ctx.set_source_surface(&surface_for_left, left_pos, 0.0);
+        let _ = ctx.set_source_pixbuf(&left, left_pos, 0.0);
+        let _ = ctx.paint();
+        return vec![PagePlacement::new(left_index, left_pos, 0.0, &left)];
+    };
+    let margin = calc_margin_for_dual(
+        &left,
+        &right,
+        area_width,
+        area_height,
+        *pages_info.spread_gutter_width.lock().unwrap(),
+        *settings.is_spread_aligned_at_spine.lock().unwrap(),
+    );
+    let final_left_margin_for_left = f64::from(margin.left_margin);
+    let left_margin_for_right = f64::from(margin.left_margin_for_right);
+    let top_margin_for_left = f64::from(margin.top_margin_for_left);
+    let top_margin_for_right = f64::from(margin.top_margin_for_right);
+
+    let _ = ctx.set_source_surface(&surface_for_left, final_left_margin_for_left, top_margin_for_left);
+    let _ = ctx.set_source_pixbuf(&left, final_left_margin_for_left, top_margin_for_left);
+    let _ = ctx.paint();
+    let left_placement = PagePlacement::new(
+        left_index,
+        final_left_margin_for_left,
+        top_margin_for_left,
+        &left,
+    );
+
+    let right_format = if right.has_alpha() {
+        cairo::Format::ARgb32
+    } else {
+        cairo::Format::Rgb24
+    };
+    let Ok(surface_for_right) =
+        cairo::ImageSurface::create(right_format, right.width(), right.height())
+    else {
+        return vec![left_placement];
+    };
+    let _ = ctx.set_source_surface(&surface_for_right, left_margin_for_right, top_margin_for_right);
+    let _ = ctx.set_source_pixbuf(&right, left_margin_for_right, top_margin_for_right);
+    let _ = ctx.paint();
+    vec![
+        left_placement,
+        PagePlacement::new(
+            right_index,
+            left_margin_for_right,
+            top_margin_for_right,
+            &right,
+        ),
+    ]
+}
+
+fn draw_dual_page(
+    image_container_list: &Vec<ImageContainer>,
+    pages_info: &PagesInfo,
+    settings: &Settings,
+    index: usize,
+    area_width: i32,
+    area_height: i32,
+    ctx: &cairo::Context,
+) -> Vec<PagePlacement> {
+    let page_direction = pages_info.page_direction.lock().unwrap();
+    let right_index = match *page_direction {
+        PageDirection::RightToLeft => index,
+        PageDirection::LeftToRight => index + 1,
+    };
+    let left_index = match *page_direction {
+        PageDirection::RightToLeft => index + 1,
+        PageDirection::LeftToRight => index,
+    };
+
+    match *page_direction {
+        PageDirection::RightToLeft => draw_right_to_left_when_dual(
+            image_container_list,
+            pages_info,
+            settings,
+            area_width,
+            area_height,
+            ctx,
+            right_index,
+            left_index,
+        ),
+        PageDirection::LeftToRight => draw_left_to_right_when_dual(
+            image_container_list,
+            pages_info,
+            settings,
+            area_width,
+            area_height,
+            ctx,
+            right_index,
+            left_index,
+        ),
+    }
+}
+
+fn draw_pages(
+    image_container_list: &Vec<ImageContainer>,
+    pages_info: &PagesInfo,
+    settings: &Settings,
+    index: usize,
+    area_width: i32,
+    area_height: i32,
+    ctx: &cairo::Context,
+) -> Vec<PagePlacement> {
+    if *settings.is_dual_mode.lock().unwrap() {
+        draw_dual_page(
+            image_container_list,
+            pages_info,
+            settings,
+            index,
+            area_width,
+            area_height,
+            ctx,
+        )
+    } else {
+        draw_single_page(image_container_list, index, area_width, area_height, ctx)
+    }
+}
+
+// the first book is on the left and the compared book on the right, in difference blend
+// they are drawn on top of each other, so the pixels which are the same turn black.
+fn draw_compare_pages(
+    image_container_list: &Vec<ImageContainer>,
+    pages_info: &PagesInfo,
+    settings: &Settings,
+    index: usize,
+    area_width: i32,
+    area_height: i32,
+    ctx: &cairo::Context,
+) -> Vec<PagePlacement> {
+    let pane_count = get_compare_pane_count(pages_info);
+    let pane_width = area_width / pane_count;
+
+    let _ = ctx.save();
+    ctx.rectangle(0.0, 0.0, f64::from(pane_width), f64::from(area_height));
+    ctx.clip();
+    let mut placements = draw_pages(
+        image_container_list,
+        pages_info,
+        settings,
+        index,
+        pane_width,
+        area_height,
+        ctx,
+    );
+    let _ = ctx.restore();
+
+    let Some(compare_page_index) = get_compare_page_index(pages_info, index) else {
+        return placements;
+    };
+    let _ = ctx.save();
+    let pane_x = if pane_count == 1 {
+        ctx.set_operator(cairo::Operator::Difference);
+        0.0
+    } else {
+        ctx.translate(f64::from(pane_width), 0.0);
+        f64::from(pane_width)
+    };
+    ctx.rectangle(0.0, 0.0, f64::from(pane_width), f64::from(area_height));
+    ctx.clip();
+    let compare_placements = draw_pages(
+        &pages_info.compare_book.image_container_list.lock().unwrap(),
+        pages_info,
+        settings,
+        compare_page_index,
+        pane_width,
+        area_height,
+        ctx,
+    );
+    let _ = ctx.restore();
+
+    // the pages of the first book come first, so the loupe shows them in difference blend.
+    placements.extend(compare_placements.into_iter().map(|v| PagePlacement {
+        is_compare: true,
+        x: v.x + pane_x,
+        ..v
+    }));
+    placements
+}
+
+// the first page is stretched over the view from a few pixels, which smooths it into a blur.
+fn draw_blurred_background(
+    blurred: &gtk::gdk_pixbuf::Pixbuf,
+    area_width: f64,
+    area_height: f64,
+    ctx: &cairo::Context,
+) {
+    let scale =
+        (area_width / f64::from(blurred.width())).max(area_height / f64::from(blurred.height()));
+    let _ = ctx.save();
+    ctx.translate(
+        (area_width - f64::from(blurred.width()) * scale) / 2.0,
+        (area_height - f64::from(blurred.height()) * scale) / 2.0,
+    );
+    ctx.scale(scale, scale);
+    ctx.set_source_pixbuf(blurred, 0.0, 0.0);
+    let source = ctx.source();
+    source.set_extend(cairo::Extend::Pad);
+    source.set_filter(cairo::Filter::Good);
+    let _ = ctx.paint();
+    let _ = ctx.restore();
+
+    ctx.set_source_rgba(0.0, 0.0, 0.0, BLURRED_BACKGROUND_DIMMING);
+    let _ = ctx.paint();
 }
 
-fn draw_single_page(
-    image_container_list: &Vec<ImageContainer>,
+fn draw_background(
+    image_container_list: &[ImageContainer],
     pages_info: &PagesInfo,
-    area: &DrawingArea,
+    settings: &Settings,
+    area_width: f64,
+    area_height: f64,
     ctx: &cairo::Context,
 ) {
-    // let _index = _pages_info.current_page_index.as_ref().borrow().clone();
-    let index = pages_info.current_page_index.lock().unwrap().clone();
-
-    let Some(modified) = image_container_list[index].get_modified_pixbuf_data() else {
-        return;
-    };
-    let format = if modified.has_alpha() {
-        cairo::Format::ARgb32
+    let background_color = *settings.background_color.lock().unwrap();
+    let index = *pages_info.current_page_index.lock().unwrap();
+    let page_count = if *settings.is_dual_mode.lock().unwrap() {
+        2
     } else {
-        cairo::Format::Rgb24
-    };
-    let pix_w = modified.width();
-    let pix_h = modified.height();
-    let Ok(surface) = cairo::ImageSurface::create(format, pix_w, pix_h) else {
-        return;
+        1
     };
+    let mut shown_pages = image_container_list.iter().skip(index).take(page_count);
 
-    let margin = calc_margin_for_single(&modified, area.allocated_width(), area.allocated_height());
-    let left_margin = f64::from(margin.left_margin);
-    let top_margin = f64::from(margin.top_margin);
-
-    let _ = ctx.set_source_surface(&surface, left_margin, top_margin);
-    let _ = ctx.set_source_pixbuf(&modified, left_margin, top_margin);
-    let _ = ctx.paint();
+    match *settings.background_mode.lock().unwrap() {
+        BackgroundMode::Solid => {
+            if let Some(background_color) = background_color {
+                ctx.set_source_color(&background_color);
+                let _ = ctx.paint();
+            }
+        }
+        BackgroundMode::PageBorder => {
+            let colors: Vec<[u8; 3]> = shown_pages.filter_map(|v| v.get_border_color()).collect();
+            if colors.is_empty() {
+                if let Some(background_color) = background_color {
+                    ctx.set_source_color(&background_color);
+                    let _ = ctx.paint();
+                }
+                return;
+            }
+            // the pages of a spread share one background.
+            let channel = |i: usize| {
+                colors.iter().map(|v| f64::from(v[i])).sum::<f64>() / (colors.len() as f64 * 255.0)
+            };
+            ctx.set_source_rgb(channel(0), channel(1), channel(2));
+            let _ = ctx.paint();
+        }
+        BackgroundMode::Blurred => {
+            if let Some(blurred) = shown_pages.find_map(|v| v.get_blurred_background()) {
+                draw_blurred_background(&blurred, area_width, area_height, ctx);
+            }
+        }
+    }
 }
 
-fn draw_right_to_left_when_dual(
+fn draw_current_pages(
     image_container_list: &Vec<ImageContainer>,
-    _pages_info: &PagesInfo,
-    _settings: &Settings,
-    area: &DrawingArea,
+    pages_info: &PagesInfo,
+    settings: &Settings,
+    area_width: i32,
+    area_height: i32,
     ctx: &cairo::Context,
-    right_index: usize,
-    left_index: usize,
-) {
-    let half_area_width = area.allocated_width() / 2;
-
-    let Some(right) = image_container_list[right_index].get_modified_pixbuf_data() else {
-        return;
-    };
-    let right_format = if right.has_alpha() {
-        cairo::Format::ARgb32
+) -> Vec<PagePlacement> {
+    let index = *pages_info.current_page_index.lock().unwrap();
+    if is_compare_mode(pages_info) {
+        draw_compare_pages(
+            image_container_list,
+            pages_info,
+            settings,
+            index,
+            area_width,
+            area_height,
+            ctx,
+        )
     } else {
-        cairo::Format::Rgb24
-    };
-    let pix_w = right.width();
-    let pix_h = right.height();
-    let Ok(surface_for_right) = cairo::ImageSurface::create(right_format, pix_w, pix_h) else {
-        return;
+        draw_pages(
+            image_container_list,
+            pages_info,
+            settings,
+            index,
+            area_width,
+            area_height,
+            ctx,
+        )
+    }
+}
+
+// the pages before the turn were drawn into a surface, the new pages are drawn as usual.
+fn draw_page_turn_transition(
+    transition: &mut PageTurnTransition,
+    image_container_list: &Vec<ImageContainer>,
+    pages_info: &PagesInfo,
+    settings: &Settings,
+    area_width: i32,
+    area_height: i32,
+    ctx: &cairo::Context,
+) -> Vec<PagePlacement> {
+    let Some(outgoing) = transition.outgoing.take().map(|v| v.into_inner()) else {
+        return draw_current_pages(
+            image_container_list,
+            pages_info,
+            settings,
+            area_width,
+            area_height,
+            ctx,
+        );
     };
 
-    let right_pos = if pix_w <= half_area_width {
-        f64::from((half_area_width - pix_w) + pix_w)
-    } else {
-        0.0
+    // ease out, so the turn starts fast and settles softly.
+    let progress = 1.0 - (1.0 - transition.progress).powi(3);
+    let _ = ctx.save();
+    let page_placements = match transition.kind {
+        PageTransition::Slide => {
+            let outgoing_x = transition.direction * progress * f64::from(area_width);
+            let _ = ctx.set_source_surface(&outgoing, outgoing_x, 0.0);
+            let _ = ctx.paint();
+
+            let incoming_x = outgoing_x - transition.direction * f64::from(area_width);
+            ctx.translate(incoming_x, 0.0);
+            draw_current_pages(
+                image_container_list,
+                pages_info,
+                settings,
+                area_width,
+                area_height,
+                ctx,
+            )
+            .into_iter()
+            .map(|v| PagePlacement {
+                x: v.x + incoming_x,
+                ..v
+            })
+            .collect()
+        }
+        PageTransition::Crossfade | PageTransition::None => {
+            let _ = ctx.set_source_surface(&outgoing, 0.0, 0.0);
+            let _ = ctx.paint_with_alpha(1.0 - progress);
+
+            ctx.push_group();
+            let page_placements = draw_current_pages(
+                image_container_list,
+                pages_info,
+                settings,
+                area_width,
+                area_height,
+                ctx,
+            );
+            let _ = ctx.pop_group_to_source();
+            let _ = ctx.paint_with_alpha(progress);
+            page_placements
+        }
     };
+    let _ = ctx.restore();
+    // the context has to let go of the surface before its data can be taken back.
+    ctx.set_source_rgba(0.0, 0.0, 0.0, 0.0);
+    transition.outgoing = outgoing.take_data().ok();
+    page_placements
+}
 
-    if left_index >= image_container_list.len() {
-        let margin =
-            calc_margin_for_single(&right, area.allocated_width(), area.allocated_height());
-        let top_margin = f64::from(margin.top_margin);
+// the shown pages are kept as they are drawn, and the frame clock of the drawing area moves
+// them out. the new pages come from the prefetched pages, so they are usually scaled already.
+fn start_page_turn_transition(
+    is_forward: bool,
+    settings: &Settings,
+    drawing_area: &DrawingArea,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &Arc<PagesInfo>,
+) {
+    if let Some(transition) = pages_info.page_turn_transition.lock().unwrap().take() {
+        transition.is_cancelled.store(true, Ordering::Relaxed);
+    }
 
-        let _ = ctx.set_source_surface(&surface_for_right, right_pos, top_margin);
-        let _ = ctx.set_source_pixbuf(&right, right_pos, top_margin);
-        let _ = ctx.paint();
+    let kind = *settings.page_transition.lock().unwrap();
+    if kind == PageTransition::None
+        || !drawing_area.settings().is_gtk_enable_animations()
+        || !drawing_area.is_mapped()
+    {
         return;
     }
 
-    let Some(left) = image_container_list[left_index].get_modified_pixbuf_data() else {
-        let _ = ctx.set_source_surface(&surface_for_right, right_pos, 0.0);
-        let _ = ctx.set_source_pixbuf(&right, right_pos, 0.0);
-        let _ = ctx.paint();
+    let scale_factor = drawing_area.scale_factor();
+    let area_width = drawing_area.allocated_width() * scale_factor;
+    let area_height = drawing_area.allocated_height() * scale_factor;
+    // a zoomed page is much larger than the window, it is turned at once.
+    if area_width < 1
+        || area_height < 1
+        || i64::from(area_width) * i64::from(area_height) > MAX_TRANSITION_PIXELS
+    {
+        return;
+    }
+    let Ok(outgoing) = cairo::ImageSurface::create(cairo::Format::ARgb32, area_width, area_height)
+    else {
         return;
     };
-    let left_format = if left.has_alpha() {
-        cairo::Format::ARgb32
-    } else {
-        cairo::Format::Rgb24
+    let Ok(ctx) = cairo::Context::new(&outgoing) else {
+        return;
     };
-
-    let margin = calc_margin_for_dual(
-        &left,
-        &right,
-        area.allocated_width(),
-        area.allocated_height(),
+    // the background goes with the pages, the new one is only uncovered as they move away.
+    draw_background(
+        &image_container_list.lock().unwrap(),
+        pages_info,
+        settings,
+        f64::from(area_width),
+        f64::from(area_height),
+        &ctx,
     );
-    let left_margin = f64::from(margin.left_margin);
-    let top_margin_for_left = f64::from(margin.top_margin_for_left);
-    let top_margin_for_right = f64::from(margin.top_margin_for_right);
-
-    let left_pic_width = left.width();
-    let final_left_margin = if left_pic_width > half_area_width || left_pic_width == half_area_width
-    {
-        0.0
-    } else {
-        left_margin
+    draw_current_pages(
+        &image_container_list.lock().unwrap(),
+        pages_info,
+        settings,
+        area_width,
+        area_height,
+        &ctx,
+    );
+    drop(ctx);
+    let Ok(outgoing) = outgoing.take_data() else {
+        return;
     };
 
-    let right_margin = if left_pic_width > half_area_width {
-        final_left_margin + f64::from(left_pic_width)
-    } else {
-        left_margin + f64::from(left_pic_width)
+    // the next page of a right to left book comes from the left, so the pages move right.
+    let is_moving_right = match *pages_info.page_direction.lock().unwrap() {
+        PageDirection::RightToLeft => is_forward,
+        PageDirection::LeftToRight => !is_forward,
     };
+    let is_cancelled = Arc::new(AtomicBool::new(false));
+    let start_time = std::cell::OnceCell::new();
+    drawing_area.add_tick_callback(glib::clone!(
+        #[strong]
+        pages_info,
+        #[strong]
+        is_cancelled,
+        move |drawing_area: &DrawingArea, frame_clock: &gdk::FrameClock| {
+            if is_cancelled.load(Ordering::Relaxed) {
+                return glib::ControlFlow::Break;
+            }
+            let frame_time = frame_clock.frame_time();
+            let start_time = *start_time.get_or_init(|| frame_time);
+            let progress = (frame_time - start_time) as f64 / PAGE_TRANSITION_DURATION_US;
+            drawing_area.queue_draw();
+
+            let mut transition = pages_info.page_turn_transition.lock().unwrap();
+            if progress >= 1.0 {
+                *transition = None;
+                return glib::ControlFlow::Break;
+            }
+            if let Some(transition) = transition.as_mut() {
+                transition.progress = progress;
+            }
+            glib::ControlFlow::Continue
+        }
+    ));
 
-    let _ = ctx.set_source_surface(&surface_for_right, right_margin, top_margin_for_right);
-    let _ = ctx.set_source_pixbuf(&right, right_margin, top_margin_for_right);
-    let _ = ctx.paint();
-
-    let Ok(surface_for_left) =
-        cairo::ImageSurface::create(left_format, left.width(), left.height())
-    else {
-        return;
-    };
-    let _ = ctx.set_source_surface(&surface_for_left, final_left_margin, top_margin_for_left);
-    let _ = ctx.set_source_pixbuf(&left, final_left_margin, top_margin_for_left);
-    let _ = ctx.paint();
+    *pages_info.page_turn_transition.lock().unwrap() = Some(PageTurnTransition {
+        kind,
+        outgoing: Some(outgoing),
+        direction: if is_moving_right { 1.0 } else { -1.0 },
+        progress: 0.0,
+        is_cancelled,
+    });
 }
 
-fn draw_left_to_right_when_dual(
-    image_container_list: &Vec<ImageContainer>,
-    _pages_info: &PagesInfo,
-    _settings: &Settings,
-    area: &DrawingArea,
+// a circle under the pointer shows the original pixels of the page beneath it,
+// x and y are in device pixels like the placements of the pages.
+fn draw_loupe(
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &PagesInfo,
+    view_state: &ViewState,
+    x: f64,
+    y: f64,
+    scale_factor: f64,
     ctx: &cairo::Context,
-    right_index: usize,
-    left_index: usize,
 ) {
-    // initial val left: 0, right: 1
-    let half_area_width = area.allocated_width() / 2;
-
-    let Some(left) = image_container_list[left_index].get_modified_pixbuf_data() else {
+    let Some(placement) = view_state
+        .page_placements
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|v| v.contains(x, y))
+        .copied()
+    else {
         return;
     };
-    let left_format = if left.has_alpha() {
-        cairo::Format::ARgb32
+
+    let zoom = *view_state.loupe_zoom.lock().unwrap();
+    let radius = *view_state.loupe_radius.lock().unwrap() * scale_factor;
+    let region_size = (radius * 2.0 / zoom).ceil() as i32 + 2;
+    let image_container_list = if placement.is_compare {
+        &pages_info.compare_book.image_container_list
     } else {
-        cairo::Format::Rgb24
+        image_container_list
     };
-    let left_pix_w = left.width();
-    let left_pix_h = left.height();
-    let Ok(surface_for_left) = cairo::ImageSurface::create(left_format, left_pix_w, left_pix_h) else {
+    let Some((region, center_x, center_y)) = image_container_list
+        .lock()
+        .unwrap()
+        .get(placement.page_index)
+        .and_then(|v| {
+            v.get_orig_region(
+                (x - placement.x) / placement.width,
+                (y - placement.y) / placement.height,
+                region_size,
+                region_size,
+            )
+        })
+    else {
         return;
     };
 
-    let left_pos = 0.0;
-
-    if right_index >= image_container_list.len() {
-        let margin = calc_margin_for_single(&left, area.allocated_width(), area.allocated_height());
-        let top_margin = f64::from(margin.top_margin);
+    let _ = ctx.save();
+    ctx.set_operator(cairo::Operator::Over);
+    ctx.arc(x, y, radius, 0.0, std::f64::consts::TAU);
+    ctx.clip();
+    ctx.set_source_rgb(0.5, 0.5, 0.5);
+    let _ = ctx.paint();
 
-        let _ = ctx.set_source_surface(&surface_for_left, left_pos, top_margin);
-        let _ = ctx.set_source_pixbuf(&left, left_pos, top_margin);
-        let _ = ctx.paint();
-        return;
-    }
+    // nearest keeps screentone dots and the edges of letters as they are in the file.
+    ctx.translate(x, y);
+    ctx.scale(zoom, zoom);
+    ctx.set_source_pixbuf(&region, -center_x, -center_y);
+    ctx.source().set_filter(cairo::Filter::Nearest);
+    let _ = ctx.paint();
+    let _ = ctx.restore();
 
-    let Some(right) = image_container_list[right_index].get_modified_pixbuf_data() else {
-        let _ = ctx.set_source_surface(&surface_for_left, left_pos, 0.0);
-        let _ = ctx.set_source_pixbuf(&left, left_pos, 0.0);
-        let _ = ctx.paint();
-        return;
-    };
-    let margin = calc_margin_for_dual(
-        &left,
-        &right,
-        area.allocated_width(),
-        area.allocated_height(),
-    );
-    let left_margin_for_left = f64::from(margin.left_margin);
-    let top_margin_for_left = f64::from(margin.top_margin_for_left);
-    let top_margin_for_right = f64::from(margin.top_margin_for_right);
+    ctx.arc(x, y, radius, 0.0, std::f64::consts::TAU);
+    ctx.set_source_rgba(0.0, 0.0, 0.0, 0.6);
+    ctx.set_line_width(2.0 * scale_factor);
+    let _ = ctx.stroke();
+}
 
-    let left_pic_width = left.width();
-    let final_left_margin_for_left = if left_pic_width > half_area_width || left_pic_width == half_area_width
-    {
-        0.0
+fn set_loupe_shown(is_shown: bool, view_state: &ViewState, drawing_area: &DrawingArea) {
+    *view_state.is_loupe_shown.lock().unwrap() = is_shown;
+    if is_shown {
+        drawing_area.set_cursor_from_name(Some("none"));
     } else {
-        left_margin_for_left
-    };
+        drawing_area.set_cursor(None);
+    }
+    drawing_area.queue_draw();
+}
 
-    let left_margin_for_right = if left_pic_width > half_area_width {
-        final_left_margin_for_left + f64::from(left_pic_width)
+// the wheel zooms the loupe, and resizes it with control.
+fn change_loupe_by_wheel(
+    dy: f64,
+    state: gdk::ModifierType,
+    view_state: &ViewState,
+    drawing_area: &DrawingArea,
+) {
+    let direction = if dy < 0.0 { 1.0 } else { -1.0 };
+    if state.contains(gdk::ModifierType::CONTROL_MASK) {
+        let mut loupe_radius = view_state.loupe_radius.lock().unwrap();
+        *loupe_radius = (*loupe_radius + LOUPE_RADIUS_STEP * direction)
+            .clamp(MIN_LOUPE_RADIUS, MAX_LOUPE_RADIUS);
     } else {
-        left_margin_for_left + f64::from(left_pic_width)
+        let mut loupe_zoom = view_state.loupe_zoom.lock().unwrap();
+        *loupe_zoom =
+            (*loupe_zoom + LOUPE_ZOOM_STEP * direction).clamp(MIN_LOUPE_ZOOM, MAX_LOUPE_ZOOM);
+    }
+    drawing_area.queue_draw();
+}
+
+fn change_loupe_action_state(window: &gtk::ApplicationWindow, is_shown: bool) {
+    let Some(action) = window
+        .lookup_action("loupe")
+        .and_downcast::<gio::SimpleAction>()
+    else {
+        return;
     };
+    action.change_state(&is_shown.to_variant());
+}
 
-    let _ = ctx.set_source_surface(&surface_for_left, final_left_margin_for_left, top_margin_for_left);
-    let _ = ctx.set_source_pixbuf(&left, final_left_margin_for_left, top_margin_for_left);
-    let _ = ctx.paint();
+// the loupe is shown while its key or the middle button is held, the menu keeps it shown.
+fn create_loupe_action_entry(
+    view_state: &Arc<ViewState>,
+    drawing_area: &DrawingArea,
+) -> gio::ActionEntry<gtk::ApplicationWindow> {
+    gio::ActionEntry::builder("loupe")
+        .state(false.to_variant())
+        .change_state(glib::clone!(
+            #[strong]
+            view_state,
+            #[weak]
+            drawing_area,
+            move |_window: &gtk::ApplicationWindow,
+                  action: &gio::SimpleAction,
+                  value: Option<&glib::Variant>| {
+                let Some(is_shown) = value.and_then(|v| v.get::<bool>()) else {
+                    return;
+                };
+                action.set_state(&is_shown.to_variant());
+                set_loupe_shown(is_shown, &view_state, &drawing_area);
+            }
+        ))
+        .build()
+}
 
-    let right_format = if right.has_alpha() {
-        cairo::Format::ARgb32
-    } else {
-        cairo::Format::Rgb24
-    };
-    let Ok(surface_for_right) =
-        cairo::ImageSurface::create(right_format, right.width(), right.height())
+fn set_slideshow_action_state(window: &gtk::ApplicationWindow, is_running: bool) {
+    let Some(action) = window
+        .lookup_action("slideshow")
+        .and_downcast::<gio::SimpleAction>()
     else {
         return;
     };
-    let _ = ctx.set_source_surface(&surface_for_right, left_margin_for_right, top_margin_for_right);
-    let _ = ctx.set_source_pixbuf(&right, left_margin_for_right, top_margin_for_right);
-    let _ = ctx.paint();
+    action.set_state(&is_running.to_variant());
+}
+
+// a page read by hand keeps the slideshow waiting for another interval.
+fn pause_slideshow(view_state: &ViewState) {
+    if view_state.slideshow_source_id.lock().unwrap().is_some() {
+        *view_state.slideshow_last_input.lock().unwrap() = Some(std::time::Instant::now());
+    }
+}
+
+fn is_slideshow_paused(view_state: &ViewState, settings: &Settings) -> bool {
+    let interval = std::time::Duration::from_secs_f64(*settings.slideshow_interval.lock().unwrap());
+    view_state
+        .slideshow_last_input
+        .lock()
+        .unwrap()
+        .is_some_and(|v| v.elapsed() < interval)
+}
+
+// the scroll tick callback ends by itself once the slideshow is stopped.
+fn stop_slideshow(window: &gtk::ApplicationWindow, view_state: &ViewState) {
+    if let Some(source_id) = view_state.slideshow_source_id.lock().unwrap().take() {
+        source_id.remove();
+    }
+    set_slideshow_action_state(window, false);
+}
+
+// a page taller than the window is scrolled down by the frame clock, it ends at the bottom.
+fn scroll_slideshow_page(
+    view_window: &gtk::ScrolledWindow,
+    settings: &Arc<Settings>,
+    view_state: &Arc<ViewState>,
+) {
+    if *view_state.is_slideshow_scrolling.lock().unwrap() {
+        return;
+    }
+    *view_state.is_slideshow_scrolling.lock().unwrap() = true;
+
+    let last_frame_time = std::cell::Cell::new(None);
+    view_window.add_tick_callback(glib::clone!(
+        #[strong]
+        settings,
+        #[strong]
+        view_state,
+        move |view_window: &gtk::ScrolledWindow, frame_clock: &gdk::FrameClock| {
+            let frame_time = frame_clock.frame_time();
+            let elapsed_us = last_frame_time
+                .replace(Some(frame_time))
+                .map(|v| frame_time - v);
+
+            let adjustment = view_window.vadjustment();
+            let max_value = adjustment.upper() - adjustment.page_size();
+            let is_running = view_state.slideshow_source_id.lock().unwrap().is_some();
+            if !is_running || adjustment.value() >= max_value {
+                *view_state.is_slideshow_scrolling.lock().unwrap() = false;
+                return glib::ControlFlow::Break;
+            }
+            if is_slideshow_paused(&view_state, &settings) {
+                return glib::ControlFlow::Continue;
+            }
+
+            let speed = *settings.slideshow_scroll_speed.lock().unwrap();
+            let elapsed = elapsed_us.unwrap_or(0) as f64 / 1_000_000.0;
+            adjustment.set_value((adjustment.value() + speed * elapsed).min(max_value));
+            glib::ControlFlow::Continue
+        }
+    ));
 }
 
-fn draw_dual_page(
-    image_container_list: &Vec<ImageContainer>,
-    pages_info: &PagesInfo,
-    settings: &Settings,
-    area: &DrawingArea,
-    ctx: &cairo::Context,
+// pages taller than the window are scrolled down first, then the next page is turned
+// in reading order. at the end of the book it stops unless the next book is opened.
+fn start_slideshow(
+    window: &gtk::ApplicationWindow,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    drawing_area: &DrawingArea,
+    pages_bar: &gtk::Scale,
+    status_bar: &StatusBar,
+    settings: &Arc<Settings>,
+    pages_info: &Arc<PagesInfo>,
+    spinner: &gtk::Spinner,
+    open_file_history_menu: &Arc<Mutex<gio::Menu>>,
+    db_manager: &Arc<Mutex<file_history::DbManager>>,
+    view_state: &Arc<ViewState>,
+    view_window: &gtk::ScrolledWindow,
 ) {
-    // let _index = _pages_info.current_page_index.as_ref().borrow().clone();
-    let page_direction = pages_info.page_direction.lock().unwrap();
-    let index = pages_info.current_page_index.lock().unwrap().clone();
-    let right_index = match *page_direction {
-        PageDirection::RightToLeft => index,
-        PageDirection::LeftToRight => index + 1,
-    };
-    let left_index = match *page_direction {
-        PageDirection::RightToLeft => index + 1,
-        PageDirection::LeftToRight => index,
-    };
+    if view_state.slideshow_source_id.lock().unwrap().is_some() {
+        return;
+    }
+    *view_state.slideshow_last_input.lock().unwrap() = None;
 
-    match *page_direction {
-        PageDirection::RightToLeft => draw_right_to_left_when_dual(
+    let interval = std::time::Duration::from_secs_f64(*settings.slideshow_interval.lock().unwrap());
+    let source_id = glib::timeout_add_local(
+        interval,
+        glib::clone!(
+            #[weak]
+            window,
+            #[strong]
             image_container_list,
-            pages_info,
+            #[weak]
+            drawing_area,
+            #[weak]
+            pages_bar,
+            #[strong]
+            status_bar,
+            #[strong]
             settings,
-            area,
-            ctx,
-            right_index,
-            left_index,
+            #[strong]
+            pages_info,
+            #[weak]
+            spinner,
+            #[strong]
+            open_file_history_menu,
+            #[strong]
+            db_manager,
+            #[strong]
+            view_state,
+            #[weak]
+            view_window,
+            #[upgrade_or]
+            glib::ControlFlow::Break,
+            move || {
+                // the next book is being loaded or the page is still scrolled down.
+                if is_slideshow_paused(&view_state, &settings)
+                    || spinner.is_spinning()
+                    || *view_state.is_slideshow_scrolling.lock().unwrap()
+                {
+                    return glib::ControlFlow::Continue;
+                }
+
+                let adjustment = view_window.vadjustment();
+                if adjustment.value() < adjustment.upper() - adjustment.page_size() {
+                    scroll_slideshow_page(&view_window, &settings, &view_state);
+                    return glib::ControlFlow::Continue;
+                }
+
+                let page_count = image_container_list.lock().unwrap().len();
+                let n = get_move_page_number_by_order(true, &settings);
+                let is_last_page =
+                    *pages_info.current_page_index.lock().unwrap() + n as usize >= page_count;
+                if page_count == 0
+                    || (is_last_page && !*settings.is_auto_open_next_book.lock().unwrap())
+                {
+                    let _ = view_state.slideshow_source_id.lock().unwrap().take();
+                    set_slideshow_action_state(&window, false);
+                    return glib::ControlFlow::Break;
+                }
+
+                move_page_or_open_next_book(
+                    n,
+                    &window,
+                    &image_container_list,
+                    &drawing_area,
+                    &pages_bar,
+                    &status_bar,
+                    &settings,
+                    &pages_info,
+                    &spinner,
+                    &open_file_history_menu,
+                    &db_manager,
+                );
+                adjustment.set_value(0.0);
+                glib::ControlFlow::Continue
+            }
         ),
-        PageDirection::LeftToRight => draw_left_to_right_when_dual(
+    );
+    *view_state.slideshow_source_id.lock().unwrap() = Some(source_id);
+}
+
+fn create_slideshow_action_entry(
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    drawing_area: &DrawingArea,
+    pages_bar: &gtk::Scale,
+    status_bar: &StatusBar,
+    settings: &Arc<Settings>,
+    pages_info: &Arc<PagesInfo>,
+    spinner: &gtk::Spinner,
+    open_file_history_menu: &Arc<Mutex<gio::Menu>>,
+    db_manager: &Arc<Mutex<file_history::DbManager>>,
+    view_state: &Arc<ViewState>,
+    view_window: &gtk::ScrolledWindow,
+) -> gio::ActionEntry<gtk::ApplicationWindow> {
+    gio::ActionEntry::builder("slideshow")
+        .state(false.to_variant())
+        .change_state(glib::clone!(
+            #[strong]
             image_container_list,
-            pages_info,
+            #[weak]
+            drawing_area,
+            #[weak]
+            pages_bar,
+            #[strong]
+            status_bar,
+            #[strong]
             settings,
-            area,
-            ctx,
-            right_index,
-            left_index,
-        ),
-    }
+            #[strong]
+            pages_info,
+            #[weak]
+            spinner,
+            #[strong]
+            open_file_history_menu,
+            #[strong]
+            db_manager,
+            #[strong]
+            view_state,
+            #[weak]
+            view_window,
+            move |window: &gtk::ApplicationWindow,
+                  action: &gio::SimpleAction,
+                  value: Option<&glib::Variant>| {
+                let Some(is_running) = value.and_then(|v| v.get::<bool>()) else {
+                    return;
+                };
+                if !is_running {
+                    stop_slideshow(window, &view_state);
+                    return;
+                }
+                if image_container_list.lock().unwrap().is_empty() {
+                    return;
+                }
+
+                action.set_state(&true.to_variant());
+                start_slideshow(
+                    window,
+                    &image_container_list,
+                    &drawing_area,
+                    &pages_bar,
+                    &status_bar,
+                    &settings,
+                    &pages_info,
+                    &spinner,
+                    &open_file_history_menu,
+                    &db_manager,
+                    &view_state,
+                    &view_window,
+                );
+            }
+        ))
+        .build()
 }
 
-fn fullscreen(window: &gtk::ApplicationWindow, pages_bar: &gtk::ProgressBar) {
+fn fullscreen(window: &gtk::ApplicationWindow, pages_bar: &gtk::Scale) {
     if window.is_fullscreen() {
         window.unfullscreen();
         window.set_show_menubar(true);
+        window.set_cursor(None);
         pages_bar.show();
     } else {
         window.fullscreen();
@@ -1313,6 +4227,61 @@ fn fullscreen(window: &gtk::ApplicationWindow, pages_bar: &gtk::ProgressBar) {
     }
 }
 
+fn find_monitor_by_connector(display: &gdk::Display, connector: &str) -> Option<gdk::Monitor> {
+    let monitors = display.monitors();
+    (0..monitors.n_items())
+        .filter_map(|i| monitors.item(i).and_downcast::<gdk::Monitor>())
+        .find(|v| v.connector().is_some_and(|v| v == connector))
+}
+
+// gtk4 cannot move a window by itself, so the monitor is only used for fullscreen.
+fn restore_window_state(
+    window: &gtk::ApplicationWindow,
+    pages_bar: &gtk::Scale,
+    settings: &Settings,
+) {
+    if !*settings.is_restore_session.lock().unwrap() {
+        return;
+    }
+
+    let window_state = WindowState::load();
+    window.set_default_size(window_state.width, window_state.height);
+    if window_state.is_maximized {
+        window.maximize();
+    }
+    if window_state.is_fullscreen {
+        let monitor = window_state
+            .monitor
+            .as_deref()
+            .and_then(|v| find_monitor_by_connector(&window.display(), v));
+        match monitor {
+            Some(monitor) => window.fullscreen_on_monitor(&monitor),
+            None => window.fullscreen(),
+        }
+        window.set_show_menubar(false);
+        pages_bar.hide();
+    }
+}
+
+fn save_window_state(window: &gtk::Window) {
+    // the default size keeps the unmaximized size while maximized or fullscreen.
+    let (width, height) = window.default_size();
+    let monitor = window
+        .surface()
+        .and_then(|v| window.display().monitor_at_surface(&v))
+        .and_then(|v| v.connector())
+        .map(|v| v.to_string());
+
+    WindowState {
+        width,
+        height,
+        is_maximized: window.is_maximized(),
+        is_fullscreen: window.is_fullscreen(),
+        monitor,
+    }
+    .save();
+}
+
 fn change_page_direction(
     db_manager: &Arc<Mutex<file_history::DbManager>>,
     pages_info: &Arc<PagesInfo>,
@@ -1330,9 +4299,37 @@ fn change_page_direction(
     }
 }
 
-fn sync_page_direction_action_state(app: &gtk::Application, pages_info: &Arc<PagesInfo>) {
+// the slider fills in reading direction, from the right in right to left books.
+fn update_pages_bar(
+    pages_bar: &gtk::Scale,
+    settings: &Settings,
+    pages_info: &Arc<PagesInfo>,
+    max_size: usize,
+) {
+    let page_direction = *pages_info.page_direction.lock().unwrap();
+    pages_bar.set_inverted(page_direction == PageDirection::RightToLeft);
+
+    pages_bar.clear_marks();
+    if *settings.is_show_chapter_marks.lock().unwrap() {
+        for chapter_start in pages_info.chapter_starts.lock().unwrap().iter() {
+            pages_bar.add_mark(
+                page_progress::calc_chapter_mark_value(*chapter_start, max_size),
+                gtk::PositionType::Bottom,
+                None,
+            );
+        }
+    }
+
+    let page_index = *pages_info.current_page_index.lock().unwrap();
+    let is_dual = *settings.is_dual_mode.lock().unwrap();
+    pages_bar.set_value(page_progress::calc_progress_fraction(
+        page_index, max_size, is_dual,
+    ));
+}
+
+fn sync_page_direction_action_state(window: &gtk::ApplicationWindow, pages_info: &Arc<PagesInfo>) {
     let page_direction = *pages_info.page_direction.lock().unwrap();
-    let Some(action) = app.lookup_action("page-direction") else {
+    let Some(action) = window.lookup_action("page-direction") else {
         return;
     };
     let Ok(action) = action.downcast::<gio::SimpleAction>() else {
@@ -1342,8 +4339,27 @@ fn sync_page_direction_action_state(app: &gtk::Application, pages_info: &Arc<Pag
     action.set_state(&page_direction.as_str().to_variant());
 }
 
+// the actions of the compare mode do nothing until a book to compare is opened.
+fn sync_compare_action_state(window: &gtk::ApplicationWindow, pages_info: &Arc<PagesInfo>) {
+    let is_compare_mode = is_compare_mode(pages_info);
+    for action_name in COMPARE_ACTION_NAMES {
+        let Some(action) = window
+            .lookup_action(action_name)
+            .and_downcast::<gio::SimpleAction>()
+        else {
+            continue;
+        };
+        action.set_enabled(is_compare_mode);
+        if *action_name == "compare_difference" {
+            let is_difference = *pages_info.compare_book.is_difference.lock().unwrap();
+            action.set_state(&is_difference.to_variant());
+        }
+    }
+}
+
 fn restore_pages_info(
     db_manager: &Arc<Mutex<file_history::DbManager>>,
+    settings: &Settings,
     pages_info: &Arc<PagesInfo>,
     file_path: &str,
     max_size: usize,
@@ -1360,50 +4376,322 @@ fn restore_pages_info(
         *pages_info.page_direction.lock().unwrap() = page_direction;
     } else {
         *pages_info.current_page_index.lock().unwrap() = 0usize;
-        *pages_info.page_direction.lock().unwrap() = PageDirection::default();
+        *pages_info.page_direction.lock().unwrap() =
+            *settings.default_page_direction.lock().unwrap();
+    }
+
+    let (page_transform, page_transforms) = db.get_page_transforms(file_path).unwrap_or_default();
+    *pages_info.book_transform.lock().unwrap() = PageTransform::from_code(page_transform);
+    *pages_info.page_transforms.lock().unwrap() =
+        page_transform::decode_page_transforms(&page_transforms);
+
+    let (crop_box, crop_boxes) = db.get_crop_boxes(file_path).unwrap_or_default();
+    *pages_info.book_crop_box.lock().unwrap() = CropBox::from_text(&crop_box);
+    *pages_info.page_crop_boxes.lock().unwrap() = auto_crop::decode_crop_boxes(&crop_boxes);
+
+    *pages_info.book_adjustments.lock().unwrap() = db
+        .get_adjustments(file_path)
+        .and_then(|v| ImageAdjustments::from_text(&v));
+}
+
+fn save_page_transforms(
+    db_manager: &Arc<Mutex<file_history::DbManager>>,
+    pages_info: &Arc<PagesInfo>,
+) {
+    let Some(file_path) = get_book_key(pages_info) else {
+        return;
+    };
+
+    let db = db_manager.lock().unwrap();
+    if db.is_exists_file_path(&file_path) {
+        let book_transform = *pages_info.book_transform.lock().unwrap();
+        let page_transforms =
+            page_transform::encode_page_transforms(&pages_info.page_transforms.lock().unwrap());
+        db.update_page_transforms(&file_path, book_transform.to_code(), &page_transforms);
+    }
+}
+
+fn save_crop_boxes(db_manager: &Arc<Mutex<file_history::DbManager>>, pages_info: &Arc<PagesInfo>) {
+    let Some(file_path) = get_book_key(pages_info) else {
+        return;
+    };
+
+    let db = db_manager.lock().unwrap();
+    if db.is_exists_file_path(&file_path) {
+        let book_crop_box = pages_info
+            .book_crop_box
+            .lock()
+            .unwrap()
+            .map(|v| v.to_text())
+            .unwrap_or_default();
+        let page_crop_boxes =
+            auto_crop::encode_crop_boxes(&pages_info.page_crop_boxes.lock().unwrap());
+        db.update_crop_boxes(&file_path, &book_crop_box, &page_crop_boxes);
+    }
+}
+
+fn save_adjustments(db_manager: &Arc<Mutex<file_history::DbManager>>, pages_info: &Arc<PagesInfo>) {
+    let Some(file_path) = get_book_key(pages_info) else {
+        return;
+    };
+
+    let db = db_manager.lock().unwrap();
+    if db.is_exists_file_path(&file_path) {
+        let adjustments = pages_info
+            .book_adjustments
+            .lock()
+            .unwrap()
+            .map(|v| v.to_text())
+            .unwrap_or_default();
+        db.update_adjustments(&file_path, &adjustments);
+    }
+}
+
+// adjustments for the book are saved with it, the others are saved to the settings.
+fn set_image_adjustments(
+    adjustments: ImageAdjustments,
+    is_book: bool,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &Arc<PagesInfo>,
+    drawing_area: &DrawingArea,
+    status_bar: &StatusBar,
+    settings: &Arc<Settings>,
+    db_manager: &Arc<Mutex<file_history::DbManager>>,
+) {
+    let adjustments = adjustments.clamped();
+    if is_book {
+        *pages_info.book_adjustments.lock().unwrap() = Some(adjustments);
+    } else {
+        *pages_info.book_adjustments.lock().unwrap() = None;
+        *settings.image_adjustments.lock().unwrap() = adjustments;
+        settings.save_later();
+    }
+    save_adjustments(db_manager, pages_info);
+
+    if !image_container_list.lock().unwrap().is_empty() {
+        refresh_current_pages(
+            image_container_list,
+            pages_info,
+            drawing_area,
+            status_bar,
+            settings,
+        );
+    }
+}
+
+fn update_status_bar(
+    status_bar: &StatusBar,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    settings: &Settings,
+    pages_info: &Arc<PagesInfo>,
+) {
+    let image_container_list = image_container_list.lock().unwrap();
+    let max_size = image_container_list.len();
+    if max_size == 0 {
+        status_bar.update(&StatusInfo::default());
+        return;
+    }
+
+    let page_index = *pages_info.current_page_index.lock().unwrap();
+    let is_dual = *settings.is_dual_mode.lock().unwrap();
+    let shown_pages: Vec<&ImageContainer> = image_container_list
+        .iter()
+        .skip(page_index)
+        .take(if is_dual { 2 } else { 1 })
+        .collect();
+
+    let page_sizes = shown_pages
+        .iter()
+        .filter_map(|v| Some((v.get_orig_width()?, v.get_orig_height()?)))
+        .collect();
+    let entry_names = shown_pages
+        .iter()
+        .filter_map(|v| v.get_file_name())
+        .collect();
+    // the page is scaled to the zoomed size, so the scale of the pixbuf is the zoom.
+    let zoom_percent = shown_pages.first().and_then(|v| {
+        let orig_width = v.get_orig_width()?;
+        let modified_width = v.get_modified_width()?;
+        (orig_width > 0).then(|| f64::from(modified_width) / f64::from(orig_width) * 100.0)
+    });
+    let get_file_name =
+        |path: &std::path::Path| path.file_name().map(|v| v.to_string_lossy().to_string());
+    let file_name = get_current_book_path(pages_info).and_then(|v| get_file_name(&v));
+    // the compared book and its offset follow the name of the first book.
+    let compare_book = &pages_info.compare_book;
+    let compare_file_name = compare_book
+        .loaded_filename
+        .lock()
+        .unwrap()
+        .as_deref()
+        .and_then(|v| get_file_name(std::path::Path::new(v)));
+    let file_name = match (file_name, compare_file_name) {
+        (Some(file_name), Some(compare_file_name)) => Some(format!(
+            "{} | {} ({:+})",
+            file_name,
+            compare_file_name,
+            *compare_book.page_offset.lock().unwrap()
+        )),
+        (file_name, _) => file_name,
+    };
+
+    status_bar.update(&StatusInfo {
+        file_name,
+        page_text: Some(format_page_osd_text(page_index, max_size, is_dual)),
+        page_sizes,
+        entry_names,
+        zoom_percent,
+        page_direction: Some(*pages_info.page_direction.lock().unwrap()),
+    });
+}
+
+fn set_page(
+    page_index: usize,
+    settings: &Settings,
+    drawing_area: &DrawingArea,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &Arc<PagesInfo>,
+    db_manager: &Arc<Mutex<file_history::DbManager>>,
+    status_bar: &StatusBar,
+) {
+    let max_len = (*image_container_list.lock().unwrap()).len();
+    if page_index >= max_len {
+        return;
+    }
+
+    let page_index =
+        page_progress::get_spread_start(page_index, *settings.is_dual_mode.lock().unwrap());
+    *pages_info.current_page_index.lock().unwrap() = page_index;
+    if let Some(file_path) = get_book_key(pages_info) {
+        update_open_file_page_index(db_manager, &file_path, page_index as i64);
+    }
+
+    scale_pages_to_drawing_area(
+        image_container_list,
+        settings,
+        pages_info,
+        page_index,
+        drawing_area,
+    );
+    update_status_bar(status_bar, image_container_list, settings, pages_info);
+}
+
+fn get_move_page_number(is_left_key: bool, pages_info: &Arc<PagesInfo>) -> i64 {
+    let page_direction = pages_info.page_direction.lock().unwrap();
+
+    if is_left_key {
+        match *page_direction {
+            PageDirection::RightToLeft => 2,
+            PageDirection::LeftToRight => -2,
+        }
+    } else {
+        match *page_direction {
+            PageDirection::RightToLeft => -2,
+            PageDirection::LeftToRight => 2,
+        }
+    }
+}
+
+fn hide_pages_bar_later(pages_bar: &gtk::Scale, pages_info: &PagesInfo) {
+    if let Some(source_id) = pages_info.pages_bar_hide_source_id.lock().unwrap().take() {
+        source_id.remove();
+    }
+
+    let pages_bar_hide_source_id = Arc::clone(&pages_info.pages_bar_hide_source_id);
+    let source_id = glib::timeout_add_local_once(
+        std::time::Duration::from_secs(2),
+        glib::clone!(
+            #[weak]
+            pages_bar,
+            move || {
+                // the source is gone once this returns, so it must not be removed later.
+                *pages_bar_hide_source_id.lock().unwrap() = None;
+                // keep it while the pointer is on the slider.
+                if !pages_bar
+                    .state_flags()
+                    .intersects(gtk::StateFlags::PRELIGHT | gtk::StateFlags::ACTIVE)
+                {
+                    pages_bar.hide();
+                }
+            }
+        ),
+    );
+    *pages_info.pages_bar_hide_source_id.lock().unwrap() = Some(source_id);
+}
+
+fn format_page_osd_text(page_index: usize, max_size: usize, is_dual: bool) -> String {
+    if is_dual && page_index + 1 < max_size {
+        format!("{}–{} / {}", page_index + 1, page_index + 2, max_size)
+    } else {
+        format!("{} / {}", page_index + 1, max_size)
+    }
+}
+
+// the cursor comes back on the next motion, which also starts the time again.
+fn hide_cursor_later(window: &gtk::ApplicationWindow, view_state: &Arc<ViewState>) {
+    if let Some(source_id) = view_state.cursor_hide_source_id.lock().unwrap().take() {
+        source_id.remove();
     }
+
+    let cursor_hide_source_id = Arc::clone(&view_state.cursor_hide_source_id);
+    let source_id = glib::timeout_add_local_once(
+        CURSOR_HIDE_DELAY,
+        glib::clone!(
+            #[weak]
+            window,
+            move || {
+                // the source is gone once this returns, so it must not be removed later.
+                *cursor_hide_source_id.lock().unwrap() = None;
+                if window.is_fullscreen() {
+                    window.set_cursor_from_name(Some("none"));
+                }
+            }
+        ),
+    );
+    *view_state.cursor_hide_source_id.lock().unwrap() = Some(source_id);
 }
 
-fn set_page(
+fn jump_to_page(
     page_index: usize,
     settings: &Settings,
     drawing_area: &DrawingArea,
+    pages_bar: &gtk::Scale,
+    status_bar: &StatusBar,
     image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
     pages_info: &Arc<PagesInfo>,
     db_manager: &Arc<Mutex<file_history::DbManager>>,
 ) {
-    let max_len = (*image_container_list.lock().unwrap()).len();
-    if page_index > max_len {
+    let size = (*image_container_list.lock().unwrap()).len();
+    if size <= page_index {
         return;
     }
 
-    *pages_info.current_page_index.lock().unwrap() = page_index;
-    if let Some(file_path) = pages_info.loaded_filename.lock().unwrap().as_deref() {
-        update_open_file_page_index(db_manager, file_path, page_index as i64);
-    }
-
-    let height = drawing_area.allocated_height();
-    let width = drawing_area.allocated_width();
-    if *settings.is_dual_mode.lock().unwrap() {
-        scale_page_for_dual(&image_container_list, page_index, width, height);
-    } else {
-        scale_page_for_single(&image_container_list, page_index, width, height);
-    }
-}
+    let is_dual = *settings.is_dual_mode.lock().unwrap();
+    let page_index = page_progress::get_spread_start(page_index, is_dual);
+    let progress_fraction = page_progress::calc_progress_fraction(page_index, size, is_dual);
 
-fn get_move_page_number(is_left_key: bool, pages_info: &Arc<PagesInfo>) -> i64 {
-    let page_direction = pages_info.page_direction.lock().unwrap();
+    set_page(
+        page_index,
+        settings,
+        drawing_area,
+        image_container_list,
+        pages_info,
+        db_manager,
+        status_bar,
+    );
+    drawing_area.queue_draw();
 
-    if is_left_key {
-        match *page_direction {
-            PageDirection::RightToLeft => 2,
-            PageDirection::LeftToRight => -2,
-        }
-    } else {
-        match *page_direction {
-            PageDirection::RightToLeft => -2,
-            PageDirection::LeftToRight => 2,
-        }
+    pages_bar.set_value(progress_fraction);
+    pages_bar.show();
+    hide_pages_bar_later(pages_bar, pages_info);
+
+    // the slider is hidden in fullscreen, so the page number is shown over the page.
+    let is_fullscreen = drawing_area
+        .root()
+        .and_downcast::<gtk::Window>()
+        .is_some_and(|v| v.is_fullscreen());
+    if is_fullscreen {
+        status_bar.show_page_osd(&format_page_osd_text(page_index, size, is_dual));
     }
 }
 
@@ -1411,77 +4699,498 @@ fn move_page(
     n: i64,
     settings: &Settings,
     drawing_area: &DrawingArea,
-    pages_bar: &gtk::ProgressBar,
+    pages_bar: &gtk::Scale,
+    status_bar: &StatusBar,
     image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
     pages_info: &Arc<PagesInfo>,
     db_manager: &Arc<Mutex<file_history::DbManager>>,
-) {
+) -> bool {
     if n == 0 || (*image_container_list.lock().unwrap()).is_empty() {
-        return;
+        return false;
     }
 
-    let is_dual = *settings.is_dual_mode.lock().unwrap();
     let size = (*image_container_list.lock().unwrap()).len();
     let i = pages_info.current_page_index.lock().unwrap().clone();
     if i == 0 && n < 0 {
-        return;
+        return false;
     }
 
+    // a spread which starts at an odd page goes back to the first page.
     let finally_page_index = if n > -1 {
         i + (n as usize)
     } else {
-        i - (n.abs() as usize)
+        i.saturating_sub(n.unsigned_abs() as usize)
     };
     if size <= finally_page_index {
+        return false;
+    }
+
+    start_page_turn_transition(
+        n > 0,
+        settings,
+        drawing_area,
+        image_container_list,
+        pages_info,
+    );
+    jump_to_page(
+        finally_page_index,
+        settings,
+        drawing_area,
+        pages_bar,
+        status_bar,
+        image_container_list,
+        pages_info,
+        db_manager,
+    );
+    true
+}
+
+// same as a key press, it opens the next book at the end when it is enabled.
+fn move_page_or_open_next_book(
+    n: i64,
+    window: &gtk::ApplicationWindow,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    drawing_area: &DrawingArea,
+    pages_bar: &gtk::Scale,
+    status_bar: &StatusBar,
+    settings: &Arc<Settings>,
+    pages_info: &Arc<PagesInfo>,
+    spinner: &gtk::Spinner,
+    open_file_history_menu: &Arc<Mutex<gio::Menu>>,
+    db_manager: &Arc<Mutex<file_history::DbManager>>,
+) {
+    let is_moved = move_page(
+        n,
+        settings,
+        drawing_area,
+        pages_bar,
+        status_bar,
+        image_container_list,
+        pages_info,
+        db_manager,
+    );
+    if is_moved || n < 1 || !*settings.is_auto_open_next_book.lock().unwrap() {
         return;
     }
 
-    // if finally_page_index >= size {
-    //     finally_page_index = size - 1;
-    // }
+    open_adjacent_book(
+        true,
+        window,
+        image_container_list,
+        drawing_area,
+        pages_bar,
+        status_bar,
+        settings,
+        pages_info,
+        spinner,
+        open_file_history_menu,
+        db_manager,
+    );
+}
+
+// the left side goes forward in right to left books and backward in left to right books.
+fn get_move_page_number_by_side(
+    is_left: bool,
+    settings: &Settings,
+    pages_info: &Arc<PagesInfo>,
+) -> i64 {
+    let n = get_move_page_number(is_left, pages_info);
+    if *settings.is_dual_mode.lock().unwrap() {
+        n
+    } else {
+        n / 2
+    }
+}
 
-    let step_of_result = if is_dual { 2 } else { 1 };
-    let tmp_step_left = if is_dual {
-        (finally_page_index + step_of_result) as f64
+fn get_move_page_number_by_order(is_forward: bool, settings: &Settings) -> i64 {
+    let step = if *settings.is_dual_mode.lock().unwrap() {
+        2
     } else {
-        (finally_page_index + 1) as f64
+        1
     };
-    let tmp_step_right = size as f64;
-    let progress_step = if (finally_page_index + step_of_result) >= size {
+
+    if is_forward { step } else { -step }
+}
+
+fn is_scrollable_by_wheel(view_window: &gtk::ScrolledWindow, dy: f64) -> bool {
+    let adjustment = view_window.vadjustment();
+    let max_value = adjustment.upper() - adjustment.page_size();
+    if max_value <= 0.0 {
+        return false;
+    }
+
+    // pages are turned after the page has been scrolled to the edge.
+    if dy > 0.0 {
+        adjustment.value() < max_value
+    } else {
+        adjustment.value() > adjustment.lower()
+    }
+}
+
+fn toggle_ui(window: &gtk::ApplicationWindow, pages_bar: &gtk::Scale, pages_info: &PagesInfo) {
+    if window.shows_menubar() {
+        window.set_show_menubar(false);
+        pages_bar.hide();
+    } else {
+        window.set_show_menubar(true);
+        pages_bar.show();
+        hide_pages_bar_later(pages_bar, pages_info);
+    }
+}
+
+fn is_zoomed(view_state: &ViewState) -> bool {
+    *view_state.zoom_level.lock().unwrap() > 1.0
+}
+
+// the page is fitted to the view at 1.0, a larger drawing area is scrolled by the view.
+fn set_zoom_level(
+    zoom_level: f64,
+    view_state: &ViewState,
+    drawing_area: &DrawingArea,
+    view_window: &gtk::ScrolledWindow,
+) {
+    let zoom_level = zoom_level.clamp(1.0, MAX_ZOOM_LEVEL);
+    *view_state.zoom_level.lock().unwrap() = zoom_level;
+
+    if zoom_level <= 1.0 {
+        drawing_area.set_size_request(-1, -1);
+        return;
+    }
+
+    let width = f64::from(view_window.width()) * zoom_level;
+    let height = f64::from(view_window.height()) * zoom_level;
+    drawing_area.set_size_request(width as i32, height as i32);
+}
+
+fn toggle_zoom(
+    view_state: &ViewState,
+    drawing_area: &DrawingArea,
+    view_window: &gtk::ScrolledWindow,
+) {
+    let zoom_level = if is_zoomed(view_state) {
         1.0
-    } else if finally_page_index == 0 {
-        0.0
     } else {
-        tmp_step_left / tmp_step_right
+        DOUBLE_TAP_ZOOM_LEVEL
     };
+    set_zoom_level(zoom_level, view_state, drawing_area, view_window);
+}
 
-    pages_bar.set_fraction(progress_step);
-    pages_bar.show();
+fn reset_slide(view_state: &ViewState, drawing_area: &DrawingArea) {
+    *view_state.slide_offset_x.lock().unwrap() = 0.0;
+    drawing_area.queue_draw();
+}
+
+fn show_context_menu(drawing_area: &DrawingArea, menu_model: &gio::MenuModel, x: f64, y: f64) {
+    let popover_menu = gtk::PopoverMenu::from_model(Some(menu_model));
+    popover_menu.set_parent(drawing_area);
+    popover_menu.set_has_arrow(false);
+    popover_menu.set_halign(gtk::Align::Start);
+    popover_menu.set_pointing_to(Some(&gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+    let _ = popover_menu.connect_closed(|popover_menu: &gtk::PopoverMenu| {
+        // unparent after the close animation has finished.
+        glib::idle_add_local_once(glib::clone!(
+            #[weak]
+            popover_menu,
+            move || {
+                popover_menu.unparent();
+            }
+        ));
+    });
+    popover_menu.popup();
+}
+
+fn go_to_page_action_with_dialog(
+    window: &gtk::ApplicationWindow,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &Arc<PagesInfo>,
+    drawing_area_ref: &DrawingArea,
+    pages_bar: &gtk::Scale,
+    status_bar: &StatusBar,
+    settings: &Arc<Settings>,
+    db_manager: &Arc<Mutex<file_history::DbManager>>,
+) {
+    let max_size = image_container_list.lock().unwrap().len();
+    if max_size == 0 {
+        return;
+    }
+    let current_page_index = *pages_info.current_page_index.lock().unwrap();
+
+    let dialog = gtk::Dialog::with_buttons(
+        Some("Go to Page"),
+        Some(window),
+        gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+        &[
+            ("Go", gtk::ResponseType::Ok),
+            ("Cancel", gtk::ResponseType::Cancel),
+        ],
+    );
+    dialog.set_default_response(gtk::ResponseType::Ok);
+
+    let spin_button = gtk::SpinButton::with_range(1.0, max_size as f64, 1.0);
+    spin_button.set_value((current_page_index + 1) as f64);
+
+    let label = gtk::Label::new(Some(&format!("/ {}", max_size)));
+    let h_box = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+    h_box.set_margin_top(12);
+    h_box.set_margin_bottom(12);
+    h_box.set_margin_start(12);
+    h_box.set_margin_end(12);
+    h_box.append(&spin_button);
+    h_box.append(&label);
+    dialog.content_area().append(&h_box);
+
+    let spin_button_key_controller = EventControllerKey::builder()
+        .propagation_phase(gtk::PropagationPhase::Capture)
+        .build();
+    let _ = spin_button_key_controller.connect_key_pressed(glib::clone!(
+        #[weak]
+        dialog,
+        #[upgrade_or]
+        Propagation::Proceed,
+        move |_event_controller_key: &EventControllerKey,
+              keyval: gdk::Key,
+              _keycode: u32,
+              _state: gdk::ModifierType| {
+            if keyval == gdk::Key::Return || keyval == gdk::Key::KP_Enter {
+                dialog.response(gtk::ResponseType::Ok);
+                return Propagation::Stop;
+            }
+            Propagation::Proceed
+        }
+    ));
+    spin_button.add_controller(spin_button_key_controller);
 
-    glib::spawn_future_local(glib::clone!(
+    dialog.connect_response(glib::clone!(
+        #[strong]
+        image_container_list,
+        #[strong]
+        pages_info,
+        #[weak]
+        drawing_area_ref,
         #[weak]
         pages_bar,
-        async move {
-            glib::timeout_add_local(std::time::Duration::from_secs(2), move || {
-                pages_bar.hide();
-                glib::ControlFlow::Break
-            });
+        #[strong]
+        status_bar,
+        #[strong]
+        settings,
+        #[strong]
+        db_manager,
+        #[weak]
+        spin_button,
+        move |dialog, response| {
+            if response == gtk::ResponseType::Ok {
+                spin_button.update();
+                let page_index = (spin_button.value_as_int() - 1).max(0) as usize;
+                jump_to_page(
+                    page_index,
+                    &settings,
+                    &drawing_area_ref,
+                    &pages_bar,
+                    &status_bar,
+                    &image_container_list,
+                    &pages_info,
+                    &db_manager,
+                );
+            }
+            dialog.close();
         }
     ));
 
-    set_page(
-        finally_page_index,
+    dialog.show();
+}
+
+fn refresh_current_pages(
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &Arc<PagesInfo>,
+    drawing_area: &DrawingArea,
+    status_bar: &StatusBar,
+    settings: &Settings,
+) {
+    let page_index = *pages_info.current_page_index.lock().unwrap();
+    scale_pages_to_drawing_area(
+        image_container_list,
         settings,
+        pages_info,
+        page_index,
         drawing_area,
+    );
+    update_status_bar(status_bar, image_container_list, settings, pages_info);
+    drawing_area.queue_draw();
+}
+
+// the crop box is previewed on the current page while it is edited,
+// it starts from the margins the page is cropped with now.
+fn edit_crop_box_with_dialog(
+    window: &gtk::ApplicationWindow,
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    pages_info: &Arc<PagesInfo>,
+    drawing_area_ref: &DrawingArea,
+    status_bar: &StatusBar,
+    settings: &Arc<Settings>,
+    db_manager: &Arc<Mutex<file_history::DbManager>>,
+) {
+    let page_index = *pages_info.current_page_index.lock().unwrap();
+    let Some(crop_box) = image_container_list
+        .lock()
+        .unwrap()
+        .get(page_index)
+        .map(|v| v.get_crop_box())
+    else {
+        return;
+    };
+    let orig_page_crop_box = pages_info
+        .page_crop_boxes
+        .lock()
+        .unwrap()
+        .get(&page_index)
+        .copied();
+
+    let dialog = gtk::Dialog::with_buttons(
+        Some("Crop Margins"),
+        Some(window),
+        gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+        &[
+            ("Clear", gtk::ResponseType::Reject),
+            ("Cancel", gtk::ResponseType::Cancel),
+            ("Apply to All Pages", gtk::ResponseType::Apply),
+            ("Apply to Page", gtk::ResponseType::Ok),
+        ],
+    );
+    dialog.set_default_response(gtk::ResponseType::Ok);
+
+    let grid = gtk::Grid::new();
+    grid.set_row_spacing(8);
+    grid.set_column_spacing(12);
+    grid.set_margin_top(12);
+    grid.set_margin_bottom(12);
+    grid.set_margin_start(12);
+    grid.set_margin_end(12);
+
+    // margins are edited in percent of the page size.
+    let max_percent = auto_crop::MAX_CROP_RATIO * 100.0;
+    let spin_buttons: Rc<Vec<gtk::SpinButton>> = Rc::new(
+        [
+            ("Left (%)", crop_box.left),
+            ("Top (%)", crop_box.top),
+            ("Right (%)", crop_box.right),
+            ("Bottom (%)", crop_box.bottom),
+        ]
+        .iter()
+        .enumerate()
+        .map(|(row, (label, ratio))| {
+            let spin_button = gtk::SpinButton::with_range(0.0, max_percent, 0.5);
+            spin_button.set_digits(1);
+            spin_button.set_value(ratio * 100.0);
+            let label = gtk::Label::new(Some(label));
+            label.set_halign(gtk::Align::Start);
+            grid.attach(&label, 0, row as i32, 1, 1);
+            grid.attach(&spin_button, 1, row as i32, 1, 1);
+            spin_button
+        })
+        .collect(),
+    );
+    dialog.content_area().append(&grid);
+
+    let get_crop_box = |spin_buttons: &[gtk::SpinButton]| CropBox {
+        left: spin_buttons[0].value() / 100.0,
+        top: spin_buttons[1].value() / 100.0,
+        right: spin_buttons[2].value() / 100.0,
+        bottom: spin_buttons[3].value() / 100.0,
+    };
+
+    for spin_button in spin_buttons.iter() {
+        spin_button.connect_value_changed(glib::clone!(
+            #[strong]
+            image_container_list,
+            #[strong]
+            pages_info,
+            #[weak]
+            drawing_area_ref,
+            #[strong]
+            status_bar,
+            #[strong]
+            settings,
+            #[strong]
+            spin_buttons,
+            move |_spin_button: &gtk::SpinButton| {
+                pages_info
+                    .page_crop_boxes
+                    .lock()
+                    .unwrap()
+                    .insert(page_index, get_crop_box(&spin_buttons));
+                refresh_current_pages(
+                    &image_container_list,
+                    &pages_info,
+                    &drawing_area_ref,
+                    &status_bar,
+                    &settings,
+                );
+            }
+        ));
+    }
+
+    dialog.connect_response(glib::clone!(
+        #[strong]
         image_container_list,
+        #[strong]
         pages_info,
+        #[weak]
+        drawing_area_ref,
+        #[strong]
+        status_bar,
+        #[strong]
+        settings,
+        #[strong]
         db_manager,
-    );
-    drawing_area.queue_draw();
+        #[strong]
+        spin_buttons,
+        move |dialog, response| {
+            for spin_button in spin_buttons.iter() {
+                spin_button.update();
+            }
+            let crop_box = get_crop_box(&spin_buttons);
+            {
+                let mut page_crop_boxes = pages_info.page_crop_boxes.lock().unwrap();
+                match response {
+                    gtk::ResponseType::Ok => {
+                        page_crop_boxes.insert(page_index, crop_box);
+                    }
+                    // the box of the book takes over the boxes of single pages.
+                    gtk::ResponseType::Apply => {
+                        page_crop_boxes.clear();
+                        *pages_info.book_crop_box.lock().unwrap() = Some(crop_box);
+                    }
+                    gtk::ResponseType::Reject => {
+                        page_crop_boxes.remove(&page_index);
+                        *pages_info.book_crop_box.lock().unwrap() = None;
+                    }
+                    _ => match orig_page_crop_box {
+                        Some(v) => {
+                            page_crop_boxes.insert(page_index, v);
+                        }
+                        None => {
+                            page_crop_boxes.remove(&page_index);
+                        }
+                    },
+                }
+            }
+            save_crop_boxes(&db_manager, &pages_info);
+            refresh_current_pages(
+                &image_container_list,
+                &pages_info,
+                &drawing_area_ref,
+                &status_bar,
+                &settings,
+            );
+            dialog.close();
+        }
+    ));
+
+    dialog.show();
 }
 
 impl MainWindow {
-    fn new(_app: &Application) -> Self {
+    fn new(app_state: &AppState) -> Self {
         let window_ui_src = include_str!("window.ui");
 
         let builder = gtk::Builder::new();
@@ -1504,7 +5213,8 @@ impl MainWindow {
             v_box: gtk::Box::new(gtk::Orientation::Vertical, 1),
             image_container_list: Arc::new(Mutex::new(vec![])),
             pages_info: std::sync::Arc::new(PagesInfo::default()),
-            settings: std::sync::Arc::new(Settings::default()),
+            settings: app_state.settings.clone(),
+            view_state: Arc::new(ViewState::default()),
             view_window: gtk::ScrolledWindow::new(),
             // open_file_history_menu_arc: open_file_history_menu_arc,
         };
@@ -1512,7 +5222,7 @@ impl MainWindow {
         result
     }
 
-    fn init(&self, app: &Application, width: i32, height: i32) -> Result<()> {
+    fn init(&self, app: &Application, app_state: &AppState, width: i32, height: i32) -> Result<()> {
         // let _header_bar = gtk::HeaderBar::builder().build();
         // self.window.set_titlebar(Some(&_header_bar));
         self.window.set_title(Some("Simple Comics Viewer"));
@@ -1523,106 +5233,747 @@ impl MainWindow {
         let image_container_list = &self.image_container_list;
         let pages_info = &self.pages_info;
         let settings = &self.settings;
-        *settings.is_dual_mode.lock().unwrap() = true;
 
-        let xdg_config_path = utils::get_xdg_config_home();
-        let sqlite_name = "simple_comics_viewer.db";
-        let sqlite_path = xdg_config_path.clone() + "/" + sqlite_name;
-        utils::create_config_dir(&xdg_config_path);
-        let db_manager = file_history::DbManager::new(&sqlite_path);
-        db_manager.init();
-        let db_manager_arc = Arc::new(Mutex::new(db_manager));
-        let db_manager_arc_ref = &db_manager_arc;
+        // checked before the window is added to the application.
+        let is_first_window = app.windows().is_empty();
+        let db_manager_arc_ref = &app_state.db_manager;
+        let open_file_history_menu_arc_ref = &app_state.open_file_history_menu;
 
         let menu_ui_src = include_str!("menu.ui");
         let builder = gtk::Builder::new();
         builder.add_from_string(menu_ui_src)?;
 
-        let open_file_history_menu: gio::Menu = builder
-            .object("file-history")
-            .expect("failed get file-history section");
-        let open_file_history_menu_arc =
-            std::sync::Arc::new(std::sync::Mutex::new(open_file_history_menu));
-        let open_file_history_menu_arc_ref = &open_file_history_menu_arc;
-        set_open_file_history_menu(open_file_history_menu_arc_ref, db_manager_arc_ref);
+        let status_bar = StatusBar::new();
+        status_bar
+            .widget
+            .set_visible(*settings.is_show_status_bar.lock().unwrap());
+
+        let pages_bar = gtk::Scale::with_range(gtk::Orientation::Horizontal, 0.0, 1.0, 0.001);
+        pages_bar.set_draw_value(true);
+        pages_bar.set_value_pos(gtk::PositionType::Top);
+        pages_bar.set_has_tooltip(true);
+        pages_bar.hide();
+        pages_bar.set_valign(gtk::Align::End);
+        pages_bar.set_format_value_func(glib::clone!(
+            #[strong]
+            image_container_list,
+            #[strong]
+            settings,
+            move |_scale: &gtk::Scale, value: f64| {
+                let max_size = image_container_list.lock().unwrap().len();
+                let is_dual = *settings.is_dual_mode.lock().unwrap();
+                let page_index =
+                    page_progress::get_page_index_from_fraction(value, max_size, is_dual);
+                format_page_osd_text(page_index, max_size, is_dual)
+            }
+        ));
+        let _ = pages_bar.connect_query_tooltip(glib::clone!(
+            #[strong]
+            image_container_list,
+            #[strong]
+            settings,
+            move |scale: &gtk::Scale,
+                  x: i32,
+                  _y: i32,
+                  _keyboard_mode: bool,
+                  tooltip: &gtk::Tooltip| {
+                let max_size = image_container_list.lock().unwrap().len();
+                let width = scale.width();
+                if max_size == 0 || width < 1 {
+                    return false;
+                }
+
+                let fraction = f64::from(x) / f64::from(width);
+                let fraction = if scale.is_inverted() {
+                    1.0 - fraction
+                } else {
+                    fraction
+                };
+                let is_dual = *settings.is_dual_mode.lock().unwrap();
+                let page_index =
+                    page_progress::get_page_index_from_fraction(fraction, max_size, is_dual);
+                tooltip.set_text(Some(&format_page_osd_text(page_index, max_size, is_dual)));
+                true
+            }
+        ));
+        let pages_bar_motion_controller = gtk::EventControllerMotion::new();
+        let _ = pages_bar_motion_controller.connect_leave(glib::clone!(
+            #[weak]
+            pages_bar,
+            #[strong]
+            pages_info,
+            move |_controller: &gtk::EventControllerMotion| {
+                hide_pages_bar_later(&pages_bar, &pages_info);
+            }
+        ));
+        pages_bar.add_controller(pages_bar_motion_controller);
+
+        let spinner = gtk::Spinner::builder().build();
+        spinner.hide();
+        spinner.set_valign(gtk::Align::Center);
+
+        let drawing_area = gtk::DrawingArea::builder()
+            .hexpand_set(true)
+            .vexpand_set(true)
+            .halign(gtk::Align::Fill)
+            .valign(gtk::Align::Fill)
+            .build();
+        drawing_area.set_draw_func(glib::clone!(
+            #[strong]
+            image_container_list,
+            #[strong]
+            pages_info,
+            #[strong]
+            settings,
+            #[strong(rename_to = view_state)]
+            self.view_state,
+            move |area: &DrawingArea, ctx: &cairo::Context, _width: i32, _height: i32| {
+                if (*image_container_list.lock().unwrap()).is_empty() {
+                    return;
+                }
+
+                draw_background(
+                    &image_container_list.lock().unwrap(),
+                    &pages_info,
+                    &settings,
+                    f64::from(area.allocated_width()),
+                    f64::from(area.allocated_height()),
+                    ctx,
+                );
+
+                let slide_offset_x = *view_state.slide_offset_x.lock().unwrap();
+                if slide_offset_x != 0.0 {
+                    ctx.translate(slide_offset_x, 0.0);
+                }
+
+                // pages are scaled to device pixels, so they are painted 1:1 on a scaled display.
+                let scale_factor = area.scale_factor();
+                ctx.scale(1.0 / f64::from(scale_factor), 1.0 / f64::from(scale_factor));
+                let area_width = area.allocated_width() * scale_factor;
+                let area_height = area.allocated_height() * scale_factor;
+
+                let mut page_turn_transition = pages_info.page_turn_transition.lock().unwrap();
+                let page_placements = match page_turn_transition.as_mut() {
+                    Some(transition) => draw_page_turn_transition(
+                        transition,
+                        &image_container_list.lock().unwrap(),
+                        &pages_info,
+                        &settings,
+                        area_width,
+                        area_height,
+                        ctx,
+                    ),
+                    None => draw_current_pages(
+                        &image_container_list.lock().unwrap(),
+                        &pages_info,
+                        &settings,
+                        area_width,
+                        area_height,
+                        ctx,
+                    ),
+                };
+                drop(page_turn_transition);
+                *view_state.page_placements.lock().unwrap() = page_placements;
+
+                if *view_state.is_loupe_shown.lock().unwrap()
+                    && let Some((x, y)) = *view_state.pointer_position.lock().unwrap()
+                {
+                    let scale_factor = f64::from(scale_factor);
+                    draw_loupe(
+                        &image_container_list,
+                        &pages_info,
+                        &view_state,
+                        (x - slide_offset_x) * scale_factor,
+                        y * scale_factor,
+                        scale_factor,
+                        ctx,
+                    );
+                }
+            }
+        ));
+
+        // a zoom resizes the drawing area, so the zoom in the status bar is updated here.
+        // pages are scaled once the size stops changing, not for every step of a drag.
+        let _ = drawing_area.connect_resize(glib::clone!(
+            #[strong]
+            image_container_list,
+            #[strong]
+            pages_info,
+            #[strong]
+            settings,
+            #[strong]
+            status_bar,
+            #[strong(rename_to = view_state)]
+            self.view_state,
+            move |drawing_area: &DrawingArea, _width: i32, _height: i32| {
+                if (*image_container_list.lock().unwrap()).is_empty() {
+                    return;
+                }
+
+                if let Some(source_id) = view_state.resize_scale_source_id.lock().unwrap().take() {
+                    source_id.remove();
+                }
+                let resize_scale_source_id = Arc::clone(&view_state.resize_scale_source_id);
+                let source_id = glib::timeout_add_local_once(
+                    RESIZE_SCALE_DELAY,
+                    glib::clone!(
+                        #[weak]
+                        drawing_area,
+                        #[strong]
+                        image_container_list,
+                        #[strong]
+                        pages_info,
+                        #[strong]
+                        settings,
+                        #[strong]
+                        status_bar,
+                        move || {
+                            *resize_scale_source_id.lock().unwrap() = None;
+                            let index = *pages_info.current_page_index.lock().unwrap();
+                            scale_pages_to_drawing_area(
+                                &image_container_list,
+                                &settings,
+                                &pages_info,
+                                index,
+                                &drawing_area,
+                            );
+                            update_status_bar(
+                                &status_bar,
+                                &image_container_list,
+                                &settings,
+                                &pages_info,
+                            );
+                            drawing_area.queue_draw();
+                        }
+                    ),
+                );
+                *view_state.resize_scale_source_id.lock().unwrap() = Some(source_id);
+            }
+        ));
+
+        // moving the window to a monitor with another scale keeps the size but not the pixels.
+        let _ = drawing_area.connect_scale_factor_notify(glib::clone!(
+            #[strong]
+            image_container_list,
+            #[strong]
+            pages_info,
+            #[strong]
+            settings,
+            #[strong]
+            status_bar,
+            move |drawing_area: &DrawingArea| {
+                if (*image_container_list.lock().unwrap()).is_empty() {
+                    return;
+                }
+
+                let index = *pages_info.current_page_index.lock().unwrap();
+                scale_pages_to_drawing_area(
+                    &image_container_list,
+                    &settings,
+                    &pages_info,
+                    index,
+                    drawing_area,
+                );
+                update_status_bar(&status_bar, &image_container_list, &settings, &pages_info);
+                drawing_area.queue_draw();
+            }
+        ));
+
+        let _ = pages_bar.connect_change_value(glib::clone!(
+            #[strong]
+            status_bar,
+            #[strong]
+            image_container_list,
+            #[strong]
+            pages_info,
+            #[strong]
+            settings,
+            #[strong]
+            drawing_area,
+            #[strong]
+            db_manager_arc_ref,
+            move |scale: &gtk::Scale, _scroll_type: gtk::ScrollType, value: f64| {
+                let max_size = image_container_list.lock().unwrap().len();
+                if max_size == 0 {
+                    return Propagation::Stop;
+                }
+
+                let is_dual = *settings.is_dual_mode.lock().unwrap();
+                let page_index =
+                    page_progress::get_page_index_from_fraction(value, max_size, is_dual);
+                if page_index != *pages_info.current_page_index.lock().unwrap() {
+                    set_page(
+                        page_index,
+                        &settings,
+                        &drawing_area,
+                        &image_container_list,
+                        &pages_info,
+                        &db_manager_arc_ref,
+                        &status_bar,
+                    );
+                    drawing_area.queue_draw();
+                }
+                hide_pages_bar_later(scale, &pages_info);
+                Propagation::Proceed
+            }
+        ));
+
+        let stack = gtk::Stack::new();
+        stack.set_transition_type(gtk::StackTransitionType::Crossfade);
+
+        let key_bindings = &app_state.key_bindings;
+        let event_controller_key = EventControllerKey::builder().build();
+        let _ = event_controller_key.connect_key_pressed(glib::clone!(
+            #[weak]
+            window,
+            #[strong]
+            key_bindings,
+            #[weak]
+            stack,
+            #[strong(rename_to = view_state)]
+            self.view_state,
+            #[upgrade_or]
+            Propagation::Proceed,
+            move |_event_controller_key: &EventControllerKey,
+                  keyval: gdk::Key,
+                  _keycode: u32,
+                  state: gdk::ModifierType| {
+                pause_slideshow(&view_state);
+                let Some(action_name) =
+                    key_bindings::find_action_name(&key_bindings, keyval, state)
+                else {
+                    return Propagation::Proceed;
+                };
+
+                // only the toggle works while the page overview is shown.
+                if stack.visible_child_name().as_deref() == Some("overview")
+                    && action_name != "page_overview"
+                {
+                    return Propagation::Proceed;
+                }
+
+                // the loupe is shown while the key is held, the release hides it.
+                if action_name == "loupe" {
+                    change_loupe_action_state(&window, true);
+                    return Propagation::Stop;
+                }
+
+                let detailed_action_name = key_bindings::get_detailed_action_name(action_name);
+                let _ = window.activate_action(&detailed_action_name, None);
+                Propagation::Stop
+            }
+        ));
+        event_controller_key.connect_key_released(glib::clone!(
+            #[weak]
+            window,
+            #[strong]
+            key_bindings,
+            move |_event_controller_key: &EventControllerKey,
+                  keyval: gdk::Key,
+                  _keycode: u32,
+                  state: gdk::ModifierType| {
+                if key_bindings::find_action_name(&key_bindings, keyval, state) == Some("loupe") {
+                    change_loupe_action_state(&window, false);
+                }
+            }
+        ));
+        self.window.add_controller(event_controller_key);
+
+        let context_menu_model = builder
+            .object::<gio::MenuModel>("context-menu")
+            .expect("failed load context menu");
+        let gesture_click = gtk::GestureClick::builder().button(0).build();
+        let _ = gesture_click.connect_released(glib::clone!(
+            #[strong]
+            window,
+            #[strong]
+            image_container_list,
+            #[strong]
+            pages_info,
+            #[strong]
+            settings,
+            #[strong]
+            drawing_area,
+            #[strong]
+            pages_bar,
+            #[strong]
+            status_bar,
+            #[strong]
+            spinner,
+            #[strong]
+            open_file_history_menu_arc_ref,
+            #[strong]
+            db_manager_arc_ref,
+            #[strong(rename_to = view_window)]
+            self.view_window,
+            #[strong(rename_to = view_state)]
+            self.view_state,
+            move |gesture: &gtk::GestureClick, n_press: i32, x: f64, y: f64| {
+                pause_slideshow(&view_state);
+                if gesture.current_button() == gdk::BUTTON_SECONDARY {
+                    show_context_menu(&drawing_area, &context_menu_model, x, y);
+                    return;
+                }
+                if gesture.current_button() != gdk::BUTTON_PRIMARY || n_press > 2 {
+                    return;
+                }
+
+                // zones are relative to the visible part of the page.
+                let view_width = f64::from(view_window.width());
+                if view_width < 1.0 {
+                    return;
+                }
+                let view_x = x - view_window.hadjustment().value();
+                let zone_width = view_width * *settings.click_zone_ratio.lock().unwrap();
+
+                let is_left = view_x < zone_width;
+                let is_right = view_x > view_width - zone_width;
+                if (is_left || is_right) && *settings.is_click_to_turn.lock().unwrap() {
+                    if n_press != 1 {
+                        return;
+                    }
+                    if image_container_list.lock().unwrap().is_empty() {
+                        return;
+                    }
+                    let n = get_move_page_number_by_side(is_left, &settings, &pages_info);
+                    move_page_or_open_next_book(
+                        n,
+                        &window,
+                        &image_container_list,
+                        &drawing_area,
+                        &pages_bar,
+                        &status_bar,
+                        &settings,
+                        &pages_info,
+                        &spinner,
+                        &open_file_history_menu_arc_ref,
+                        &db_manager_arc_ref,
+                    );
+                } else if !is_left && !is_right {
+                    // a tap is only taken as single once no second tap can follow it.
+                    if let Some(source_id) = view_state.ui_toggle_source_id.lock().unwrap().take() {
+                        source_id.remove();
+                    }
+                    if n_press == 2 {
+                        toggle_zoom(&view_state, &drawing_area, &view_window);
+                        return;
+                    }
+                    if !*settings.is_center_click_toggle_ui.lock().unwrap() {
+                        return;
+                    }
+                    let double_click_time = drawing_area.settings().gtk_double_click_time();
+                    let ui_toggle_source_id = Arc::clone(&view_state.ui_toggle_source_id);
+                    let source_id = glib::timeout_add_local_once(
+                        std::time::Duration::from_millis(double_click_time.max(0) as u64),
+                        glib::clone!(
+                            #[weak]
+                            window,
+                            #[weak]
+                            pages_bar,
+                            #[strong]
+                            pages_info,
+                            move || {
+                                *ui_toggle_source_id.lock().unwrap() = None;
+                                toggle_ui(&window, &pages_bar, &pages_info);
+                            }
+                        ),
+                    );
+                    *view_state.ui_toggle_source_id.lock().unwrap() = Some(source_id);
+                }
+            }
+        ));
+        drawing_area.add_controller(gesture_click);
+
+        let loupe_gesture_click = gtk::GestureClick::builder()
+            .button(gdk::BUTTON_MIDDLE)
+            .build();
+        let _ = loupe_gesture_click.connect_pressed(glib::clone!(
+            #[weak]
+            window,
+            move |_gesture: &gtk::GestureClick, _n_press: i32, _x: f64, _y: f64| {
+                change_loupe_action_state(&window, true);
+            }
+        ));
+        let _ = loupe_gesture_click.connect_released(glib::clone!(
+            #[weak]
+            window,
+            move |_gesture: &gtk::GestureClick, _n_press: i32, _x: f64, _y: f64| {
+                change_loupe_action_state(&window, false);
+            }
+        ));
+        drawing_area.add_controller(loupe_gesture_click);
 
-        let menu_model = builder
-            .object::<gio::MenuModel>("menu")
-            .expect("failed load menu");
+        let loupe_motion_controller = gtk::EventControllerMotion::new();
+        let _ = loupe_motion_controller.connect_motion(glib::clone!(
+            #[weak]
+            drawing_area,
+            #[strong(rename_to = view_state)]
+            self.view_state,
+            move |_controller: &gtk::EventControllerMotion, x: f64, y: f64| {
+                *view_state.pointer_position.lock().unwrap() = Some((x, y));
+                if *view_state.is_loupe_shown.lock().unwrap() {
+                    drawing_area.queue_draw();
+                }
+            }
+        ));
+        let _ = loupe_motion_controller.connect_leave(glib::clone!(
+            #[weak]
+            drawing_area,
+            #[strong(rename_to = view_state)]
+            self.view_state,
+            move |_controller: &gtk::EventControllerMotion| {
+                *view_state.pointer_position.lock().unwrap() = None;
+                if *view_state.is_loupe_shown.lock().unwrap() {
+                    drawing_area.queue_draw();
+                }
+            }
+        ));
+        drawing_area.add_controller(loupe_motion_controller);
 
-        let popover_menu = gtk::PopoverMenu::from_model(Some(&menu_model));
-        app.set_menubar(Some(&popover_menu.menu_model().unwrap()));
+        let scroll_controller = gtk::EventControllerScroll::new(
+            gtk::EventControllerScrollFlags::VERTICAL | gtk::EventControllerScrollFlags::DISCRETE,
+        );
+        let _ = scroll_controller.connect_scroll(glib::clone!(
+            #[strong]
+            window,
+            #[strong]
+            image_container_list,
+            #[strong]
+            pages_info,
+            #[strong]
+            settings,
+            #[strong]
+            drawing_area,
+            #[strong]
+            pages_bar,
+            #[strong]
+            status_bar,
+            #[strong]
+            spinner,
+            #[strong]
+            open_file_history_menu_arc_ref,
+            #[strong]
+            db_manager_arc_ref,
+            #[strong(rename_to = view_window)]
+            self.view_window,
+            #[strong(rename_to = view_state)]
+            self.view_state,
+            move |controller: &gtk::EventControllerScroll, _dx: f64, dy: f64| {
+                if dy == 0.0 || image_container_list.lock().unwrap().is_empty() {
+                    return Propagation::Proceed;
+                }
+                pause_slideshow(&view_state);
+                if *view_state.is_loupe_shown.lock().unwrap() {
+                    let state = controller.current_event_state();
+                    change_loupe_by_wheel(dy, state, &view_state, &drawing_area);
+                    return Propagation::Stop;
+                }
 
-        let pages_bar = gtk::ProgressBar::builder().build();
-        pages_bar.hide();
-        pages_bar.set_valign(gtk::Align::End);
+                let wheel_action = *settings.wheel_action.lock().unwrap();
+                let is_scroll = match wheel_action {
+                    WheelAction::Auto => is_scrollable_by_wheel(&view_window, dy),
+                    WheelAction::Page => false,
+                    WheelAction::Scroll => true,
+                };
+                if is_scroll {
+                    return Propagation::Proceed;
+                }
 
-        let spinner = gtk::Spinner::builder().build();
-        spinner.hide();
-        spinner.set_valign(gtk::Align::Center);
+                move_page_or_open_next_book(
+                    get_move_page_number_by_order(dy > 0.0, &settings),
+                    &window,
+                    &image_container_list,
+                    &drawing_area,
+                    &pages_bar,
+                    &status_bar,
+                    &settings,
+                    &pages_info,
+                    &spinner,
+                    &open_file_history_menu_arc_ref,
+                    &db_manager_arc_ref,
+                );
+                Propagation::Stop
+            }
+        ));
+        drawing_area.add_controller(scroll_controller);
 
-        let drawing_area = gtk::DrawingArea::builder()
-            .hexpand_set(true)
-            .vexpand_set(true)
-            .halign(gtk::Align::Fill)
-            .valign(gtk::Align::Fill)
-            .build();
-        drawing_area.set_draw_func(glib::clone!(
+        let swipe_gesture = gtk::GestureSwipe::builder().touch_only(true).build();
+        let _ = swipe_gesture.connect_swipe(glib::clone!(
+            #[strong]
+            window,
             #[strong]
             image_container_list,
             #[strong]
             pages_info,
             #[strong]
             settings,
-            move |area: &DrawingArea, ctx: &cairo::Context, _width: i32, _height: i32| {
-                if (*image_container_list.lock().unwrap()).is_empty() {
+            #[strong]
+            drawing_area,
+            #[strong]
+            pages_bar,
+            #[strong]
+            status_bar,
+            #[strong]
+            spinner,
+            #[strong]
+            open_file_history_menu_arc_ref,
+            #[strong]
+            db_manager_arc_ref,
+            #[strong(rename_to = view_state)]
+            self.view_state,
+            move |_gesture: &gtk::GestureSwipe, velocity_x: f64, velocity_y: f64| {
+                if is_zoomed(&view_state)
+                    || *view_state.is_page_turned_by_gesture.lock().unwrap()
+                    || velocity_x.abs() < SWIPE_VELOCITY_THRESHOLD
+                    || velocity_x.abs() < velocity_y.abs()
+                {
                     return;
                 }
+                *view_state.is_page_turned_by_gesture.lock().unwrap() = true;
+                reset_slide(&view_state, &drawing_area);
 
-                if *settings.is_dual_mode.lock().unwrap() {
-                    draw_dual_page(
-                        &*image_container_list.lock().unwrap(),
-                        &pages_info,
-                        &settings,
-                        area,
-                        ctx,
-                    );
-                } else {
-                    draw_single_page(
-                        &*image_container_list.lock().unwrap(),
-                        &pages_info,
-                        area,
-                        ctx,
-                    );
+                // a swipe to the right pulls the page on the left side.
+                let n = get_move_page_number_by_side(velocity_x > 0.0, &settings, &pages_info);
+                move_page_or_open_next_book(
+                    n,
+                    &window,
+                    &image_container_list,
+                    &drawing_area,
+                    &pages_bar,
+                    &status_bar,
+                    &settings,
+                    &pages_info,
+                    &spinner,
+                    &open_file_history_menu_arc_ref,
+                    &db_manager_arc_ref,
+                );
+            }
+        ));
+        drawing_area.add_controller(swipe_gesture);
+
+        let drag_gesture = gtk::GestureDrag::builder().touch_only(true).build();
+        let _ = drag_gesture.connect_drag_begin(glib::clone!(
+            #[strong(rename_to = view_state)]
+            self.view_state,
+            move |_gesture: &gtk::GestureDrag, _x: f64, _y: f64| {
+                *view_state.is_page_turned_by_gesture.lock().unwrap() = false;
+            }
+        ));
+        let _ = drag_gesture.connect_drag_update(glib::clone!(
+            #[strong]
+            image_container_list,
+            #[strong]
+            drawing_area,
+            #[strong(rename_to = view_state)]
+            self.view_state,
+            move |_gesture: &gtk::GestureDrag, offset_x: f64, offset_y: f64| {
+                // the view pans a zoomed page by itself.
+                if is_zoomed(&view_state)
+                    || offset_x.abs() < offset_y.abs()
+                    || image_container_list.lock().unwrap().is_empty()
+                {
+                    return;
                 }
+                *view_state.slide_offset_x.lock().unwrap() = offset_x;
+                drawing_area.queue_draw();
             }
         ));
-
-        let _ = drawing_area.connect_resize(glib::clone!(
+        let _ = drag_gesture.connect_drag_end(glib::clone!(
+            #[strong]
+            window,
             #[strong]
             image_container_list,
             #[strong]
             pages_info,
             #[strong]
             settings,
-            move |_drawing_area: &DrawingArea, width: i32, height: i32| {
-                if (*image_container_list.lock().unwrap()).is_empty() {
+            #[strong]
+            drawing_area,
+            #[strong]
+            pages_bar,
+            #[strong]
+            status_bar,
+            #[strong]
+            spinner,
+            #[strong]
+            open_file_history_menu_arc_ref,
+            #[strong]
+            db_manager_arc_ref,
+            #[strong(rename_to = view_state)]
+            self.view_state,
+            move |_gesture: &gtk::GestureDrag, offset_x: f64, _offset_y: f64| {
+                let slide_offset_x = *view_state.slide_offset_x.lock().unwrap();
+                reset_slide(&view_state, &drawing_area);
+                if slide_offset_x == 0.0 || *view_state.is_page_turned_by_gesture.lock().unwrap() {
                     return;
                 }
 
-                let index = pages_info.current_page_index.lock().unwrap().clone();
-                if *settings.is_dual_mode.lock().unwrap() {
-                    scale_page_for_dual(&image_container_list, index, width, height);
-                } else {
-                    scale_page_for_single(&image_container_list, index, width, height);
+                let width = f64::from(drawing_area.width());
+                if offset_x.abs() < width * SLIDE_DISTANCE_RATIO {
+                    return;
                 }
+                *view_state.is_page_turned_by_gesture.lock().unwrap() = true;
+
+                let n = get_move_page_number_by_side(offset_x > 0.0, &settings, &pages_info);
+                move_page_or_open_next_book(
+                    n,
+                    &window,
+                    &image_container_list,
+                    &drawing_area,
+                    &pages_bar,
+                    &status_bar,
+                    &settings,
+                    &pages_info,
+                    &spinner,
+                    &open_file_history_menu_arc_ref,
+                    &db_manager_arc_ref,
+                );
             }
         ));
+        drawing_area.add_controller(drag_gesture);
 
-        let event_controller_key = EventControllerKey::builder().build();
-        let _ = event_controller_key.connect_key_pressed(glib::clone!(
+        let zoom_gesture = gtk::GestureZoom::new();
+        let zoom_level_at_begin = Arc::new(Mutex::new(1.0f64));
+        let _ = zoom_gesture.connect_begin(glib::clone!(
             #[strong]
-            app,
+            zoom_level_at_begin,
+            #[strong]
+            drawing_area,
+            #[strong(rename_to = view_state)]
+            self.view_state,
+            move |_gesture: &gtk::GestureZoom, _sequence: Option<&gdk::EventSequence>| {
+                *zoom_level_at_begin.lock().unwrap() = *view_state.zoom_level.lock().unwrap();
+                reset_slide(&view_state, &drawing_area);
+            }
+        ));
+        let _ = zoom_gesture.connect_scale_changed(glib::clone!(
+            #[strong]
+            zoom_level_at_begin,
+            #[strong]
+            drawing_area,
+            #[strong(rename_to = view_state)]
+            self.view_state,
+            #[strong(rename_to = view_window)]
+            self.view_window,
+            move |_gesture: &gtk::GestureZoom, scale: f64| {
+                let zoom_level = *zoom_level_at_begin.lock().unwrap() * scale;
+                set_zoom_level(zoom_level, &view_state, &drawing_area, &view_window);
+            }
+        ));
+        drawing_area.add_controller(zoom_gesture);
+
+        // two finger horizontal scroll on a touchpad turns a page once per gesture.
+        let touchpad_scroll_controller =
+            gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::HORIZONTAL);
+        let _ = touchpad_scroll_controller.connect_scroll_begin(glib::clone!(
+            #[strong(rename_to = view_state)]
+            self.view_state,
+            move |_controller: &gtk::EventControllerScroll| {
+                *view_state.touchpad_scroll_dx.lock().unwrap() = 0.0;
+                *view_state.is_page_turned_by_gesture.lock().unwrap() = false;
+            }
+        ));
+        let _ = touchpad_scroll_controller.connect_scroll(glib::clone!(
             #[strong]
             window,
             #[strong]
@@ -1636,126 +5987,91 @@ impl MainWindow {
             #[strong]
             pages_bar,
             #[strong]
-            pages_info,
+            status_bar,
             #[strong]
             spinner,
             #[strong]
             open_file_history_menu_arc_ref,
             #[strong]
             db_manager_arc_ref,
-            move |_event_controller_key: &EventControllerKey,
-                  keyval: gdk::Key,
-                  _keycode: u32,
-                  state: gdk::ModifierType| {
-                if state == gdk::ModifierType::ALT_MASK && keyval == gdk::Key::Return {
-                    fullscreen(&window, &pages_bar);
-                    return Propagation::Stop;
+            #[strong(rename_to = view_state)]
+            self.view_state,
+            move |_controller: &gtk::EventControllerScroll, dx: f64, _dy: f64| {
+                if dx == 0.0 || is_zoomed(&view_state) {
+                    return Propagation::Proceed;
                 }
-
-                if state == gdk::ModifierType::ALT_MASK && keyval == gdk::Key::F4 {
-                    app.quit();
+                if *view_state.is_page_turned_by_gesture.lock().unwrap() {
+                    return Propagation::Stop;
                 }
 
-                if state == gdk::ModifierType::CONTROL_MASK && keyval == gdk::Key::o {
-                    open_file_action_with_dialog(
-                        &app,
-                        &window,
-                        &image_container_list,
-                        &drawing_area,
-                        &pages_bar,
-                        &settings,
-                        &pages_info,
-                        &spinner,
-                        &open_file_history_menu_arc_ref,
-                        &db_manager_arc_ref,
-                    );
+                let scroll_dx = {
+                    let mut touchpad_scroll_dx = view_state.touchpad_scroll_dx.lock().unwrap();
+                    *touchpad_scroll_dx += dx;
+                    *touchpad_scroll_dx
+                };
+                if scroll_dx.abs() < TOUCHPAD_SWIPE_THRESHOLD {
                     return Propagation::Stop;
                 }
+                *view_state.is_page_turned_by_gesture.lock().unwrap() = true;
 
-                let is_pressed_ctrl = state == gdk::ModifierType::CONTROL_MASK;
-                let mut is_move = false;
-                let mut additional_val = 0;
-                match keyval {
-                    gdk::Key::q => {
-                        if is_pressed_ctrl {
-                            app.quit();
-                        }
-                    }
-                    gdk::Key::Left => {
-                        if *settings.is_dual_mode.lock().unwrap() {
-                            additional_val = get_move_page_number(true, &pages_info);
-                        } else {
-                            additional_val = 1;
-                        }
-                        is_move = true;
-                    }
-                    gdk::Key::h => {
-                        if *settings.is_dual_mode.lock().unwrap() {
-                            additional_val = get_move_page_number(true, &pages_info);
-                        } else {
-                            additional_val = 1;
-                        }
-                        is_move = true;
-                    }
-                    gdk::Key::b => {
-                        if is_pressed_ctrl {
-                            if *settings.is_dual_mode.lock().unwrap() {
-                                additional_val = get_move_page_number(true, &pages_info);
-                            } else {
-                                additional_val = 1;
-                            }
-                            is_move = true;
-                        }
-                    }
-                    gdk::Key::Right => {
-                        if *settings.is_dual_mode.lock().unwrap() {
-                            additional_val = get_move_page_number(false, &pages_info);
-                        } else {
-                            additional_val = -1;
-                        }
-                        is_move = true;
-                    }
-                    gdk::Key::l => {
-                        if *settings.is_dual_mode.lock().unwrap() {
-                            additional_val = get_move_page_number(false, &pages_info);
-                        } else {
-                            additional_val = -1;
-                        }
-                        is_move = true;
-                    }
-                    gdk::Key::f => {
-                        if is_pressed_ctrl {
-                            if *settings.is_dual_mode.lock().unwrap() {
-                                additional_val = get_move_page_number(true, &pages_info);
-                            } else {
-                                additional_val = -1;
-                            }
-                            is_move = true;
-                        }
-                    }
-                    _ => is_move = false,
-                }
-                if is_move {
-                    move_page(
-                        additional_val,
-                        &settings,
-                        &drawing_area,
-                        &pages_bar,
-                        &image_container_list,
-                        &pages_info,
-                        &db_manager_arc_ref,
-                    );
-                }
-                Propagation::Proceed
+                // scrolling to the right moves the content to the left.
+                let n = get_move_page_number_by_side(scroll_dx < 0.0, &settings, &pages_info);
+                move_page_or_open_next_book(
+                    n,
+                    &window,
+                    &image_container_list,
+                    &drawing_area,
+                    &pages_bar,
+                    &status_bar,
+                    &settings,
+                    &pages_info,
+                    &spinner,
+                    &open_file_history_menu_arc_ref,
+                    &db_manager_arc_ref,
+                );
+                Propagation::Stop
             }
         ));
-        self.window.add_controller(event_controller_key);
+        drawing_area.add_controller(touchpad_scroll_controller);
 
         self.view_window.set_hexpand(true);
         self.view_window.set_vexpand(true);
         self.view_window.set_halign(gtk::Align::Fill);
         self.view_window.set_valign(gtk::Align::Fill);
 
+        let page_overview = PageOverview::new();
+        page_overview.connect_page_activated(glib::clone!(
+            #[strong]
+            image_container_list,
+            #[strong]
+            pages_info,
+            #[strong]
+            settings,
+            #[weak]
+            drawing_area,
+            #[weak]
+            pages_bar,
+            #[strong]
+            status_bar,
+            #[strong(rename_to = db_manager_arc)]
+            app_state.db_manager,
+            #[weak]
+            stack,
+            move |page_index: usize| {
+                stack.set_visible_child_name("reader");
+                jump_to_page(
+                    page_index,
+                    &settings,
+                    &drawing_area,
+                    &pages_bar,
+                    &status_bar,
+                    &image_container_list,
+                    &pages_info,
+                    &db_manager_arc,
+                );
+            }
+        ));
+
         let drawing_area_ref = &drawing_area;
         let pages_bar_ref = &pages_bar;
         let spinner_ref = &spinner;
@@ -1765,32 +6081,161 @@ impl MainWindow {
             pages_info,
             drawing_area_ref,
             pages_bar_ref,
+            &status_bar,
             settings,
             spinner_ref,
             open_file_history_menu_arc_ref,
             db_manager_arc_ref,
+            &stack,
+            &page_overview,
+            key_bindings,
+        );
+        self.window.add_action_entries(action_entry);
+        self.window.add_action_entries([
+            create_loupe_action_entry(&self.view_state, drawing_area_ref),
+            create_slideshow_action_entry(
+                image_container_list,
+                drawing_area_ref,
+                pages_bar_ref,
+                &status_bar,
+                settings,
+                pages_info,
+                spinner_ref,
+                open_file_history_menu_arc_ref,
+                db_manager_arc_ref,
+                &self.view_state,
+                &self.view_window,
+            ),
+        ]);
+        connect_settings_changed(
+            app,
+            window,
+            image_container_list,
+            pages_info,
+            drawing_area_ref,
+            pages_bar_ref,
+            &status_bar,
+            settings,
+            open_file_history_menu_arc_ref,
+            db_manager_arc_ref,
         );
-        app.add_action_entries(action_entry);
-        app.set_accels_for_action("app.quit", &["<Control>Q", "<Alt>F4"]);
-        app.set_accels_for_action("app.file_open", &["<Control>O"]);
+        sync_compare_action_state(window, pages_info);
         self.view_window.set_child(Some(drawing_area_ref));
 
+        stack.add_named(&self.view_window, Some("reader"));
+        stack.add_named(&page_overview.scrolled_window, Some("overview"));
+
+        // in fullscreen the menu bar is shown over the page instead of resizing it.
+        let header_menu_bar = gtk::PopoverMenuBar::from_model(app.menubar().as_ref());
+        header_menu_bar.add_css_class("background");
+        let header = gtk::Revealer::new();
+        header.set_transition_type(gtk::RevealerTransitionType::SlideDown);
+        header.set_valign(gtk::Align::Start);
+        header.set_child(Some(&header_menu_bar));
+        let _ = window.connect_fullscreened_notify(glib::clone!(
+            #[weak]
+            header,
+            move |_window: &gtk::ApplicationWindow| {
+                header.set_reveal_child(false);
+            }
+        ));
+
         let overlay = gtk::Overlay::new();
-        overlay.set_child(Some(&self.view_window));
+        overlay.set_child(Some(&stack));
         overlay.add_overlay(spinner_ref);
         overlay.add_overlay(pages_bar_ref);
+        overlay.add_overlay(&status_bar.page_osd);
+        overlay.add_overlay(&header);
+
+        let overlay_motion_controller = gtk::EventControllerMotion::new();
+        let _ = overlay_motion_controller.connect_motion(glib::clone!(
+            #[weak]
+            window,
+            #[strong]
+            image_container_list,
+            #[weak]
+            pages_bar,
+            #[strong]
+            pages_info,
+            #[weak]
+            header,
+            #[strong(rename_to = view_state)]
+            self.view_state,
+            move |controller: &gtk::EventControllerMotion, _x: f64, y: f64| {
+                let Some(widget) = controller.widget() else {
+                    return;
+                };
+
+                if window.is_fullscreen() {
+                    window.set_cursor(None);
+                    hide_cursor_later(&window, &view_state);
+
+                    // hidden again once the pointer leaves the height of the menu bar.
+                    if y < TOP_EDGE_HEIGHT {
+                        header.set_reveal_child(true);
+                    } else if y > f64::from(header.height()).max(TOP_EDGE_HEIGHT) {
+                        header.set_reveal_child(false);
+                    }
+                }
+
+                // the slider appears when the pointer reaches the bottom edge.
+                if y < f64::from(widget.height()) - BOTTOM_EDGE_HEIGHT
+                    || pages_bar.is_visible()
+                    || image_container_list.lock().unwrap().is_empty()
+                {
+                    return;
+                }
+                pages_bar.show();
+                hide_pages_bar_later(&pages_bar, &pages_info);
+            }
+        ));
+        overlay.add_controller(overlay_motion_controller);
 
         self.v_box.set_halign(gtk::Align::Fill);
         self.v_box.set_valign(gtk::Align::Fill);
         self.v_box.set_hexpand(true);
         self.v_box.set_vexpand(true);
         self.v_box.append(&overlay);
+        self.v_box.append(&status_bar.widget);
 
         self.window.set_application(Some(app));
         // self.window.set_child(Some(&self.view_window));
         // self.window.set_child(Some(&self.v_box));
         self.window.set_child(Some(&self.v_box));
 
+        let _ = self.window.connect_close_request(glib::clone!(
+            #[strong]
+            settings,
+            move |window: &gtk::ApplicationWindow| {
+                // a single state is kept, so it is saved by the last window to be closed.
+                let is_last_window = window
+                    .application()
+                    .is_some_and(|app| app.windows().len() == 1);
+                if is_last_window {
+                    save_window_state(window.upcast_ref());
+                    settings.flush();
+                }
+                Propagation::Proceed
+            }
+        ));
+
+        // the other windows start empty, so they do not open the same book again.
+        if is_first_window {
+            restore_window_state(window, pages_bar_ref, settings);
+            restore_last_book(
+                window,
+                image_container_list,
+                drawing_area_ref,
+                pages_bar_ref,
+                &status_bar,
+                settings,
+                pages_info,
+                spinner_ref,
+                open_file_history_menu_arc_ref,
+                db_manager_arc_ref,
+            );
+        }
+
         Ok(())
     }
 
@@ -1799,9 +6244,57 @@ impl MainWindow {
     }
 }
 
-pub fn activate(app: &Application) {
-    let main = MainWindow::new(app);
-    match main.init(app, 1024, 768) {
+// shared by all windows, each window has its own book and pages.
+#[derive(Clone)]
+pub struct AppState {
+    settings: Arc<Settings>,
+    db_manager: Arc<Mutex<file_history::DbManager>>,
+    open_file_history_menu: Arc<Mutex<gio::Menu>>,
+    key_bindings: Rc<Vec<KeyBinding>>,
+}
+
+fn create_app_action_entries(app_state: &AppState) -> Vec<gio::ActionEntry<gtk::Application>> {
+    let new_window_action_entry: gio::ActionEntry<gtk::Application> =
+        gio::ActionEntry::builder("new_window")
+            .activate(glib::clone!(
+                #[strong]
+                app_state,
+                move |app: &gtk::Application,
+                      _action: &gio::SimpleAction,
+                      _user_data: Option<&glib::Variant>| {
+                    open_window(app, &app_state);
+                }
+            ))
+            .build();
+
+    let quit_action_entry: gio::ActionEntry<gtk::Application> = gio::ActionEntry::builder("quit")
+        .activate(glib::clone!(
+            #[strong(rename_to = settings)]
+            app_state.settings,
+            move |app: &gtk::Application,
+                  _action: &gio::SimpleAction,
+                  _user_data: Option<&glib::Variant>| {
+                // quit does not emit close-request, the focused window is kept.
+                if let Some(window) = app.active_window() {
+                    save_window_state(&window);
+                }
+                settings.flush();
+                app.quit();
+            }
+        ))
+        .build();
+
+    vec![new_window_action_entry, quit_action_entry]
+}
+
+fn open_window(app: &Application, app_state: &AppState) {
+    let main = MainWindow::new(app_state);
+    match main.init(
+        app,
+        app_state,
+        settings::DEFAULT_WINDOW_WIDTH,
+        settings::DEFAULT_WINDOW_HEIGHT,
+    ) {
         Ok(_) => {
             main.run();
         }
@@ -1810,3 +6303,53 @@ pub fn activate(app: &Application) {
         }
     }
 }
+
+pub fn startup(app: &Application) {
+    let settings = Arc::new(Settings::load());
+
+    let xdg_config_path = utils::get_xdg_config_home();
+    let sqlite_name = "simple_comics_viewer.db";
+    let sqlite_path = xdg_config_path.clone() + "/" + sqlite_name;
+    utils::create_config_dir(&xdg_config_path);
+    let db_manager = file_history::DbManager::new(&sqlite_path);
+    db_manager.init();
+    let db_manager_arc = Arc::new(Mutex::new(db_manager));
+
+    let menu_ui_src = include_str!("menu.ui");
+    let builder = gtk::Builder::new();
+    builder
+        .add_from_string(menu_ui_src)
+        .expect("failed add ui file to builder");
+
+    let open_file_history_menu: gio::Menu = builder
+        .object("file-history")
+        .expect("failed get file-history section");
+    let open_file_history_menu_arc = Arc::new(Mutex::new(open_file_history_menu));
+    set_open_file_history_menu(
+        &open_file_history_menu_arc,
+        &db_manager_arc,
+        *settings.history_size.lock().unwrap(),
+    );
+
+    let menu_model = builder
+        .object::<gio::MenuModel>("menu")
+        .expect("failed load menu");
+    app.set_menubar(Some(&menu_model));
+
+    let key_bindings = Rc::new(key_bindings::load_key_bindings());
+    key_bindings::set_accels_for_actions(app, &key_bindings);
+
+    let app_state = AppState {
+        settings,
+        db_manager: db_manager_arc,
+        open_file_history_menu: open_file_history_menu_arc,
+        key_bindings,
+    };
+    app.add_action_entries(create_app_action_entries(&app_state));
+    // activated by the preferences with the change, every window listens to it.
+    app.add_action(&gio::SimpleAction::new(
+        "settings_changed",
+        Some(VariantTy::STRING),
+    ));
+    app.connect_activate(move |app: &Application| open_window(app, &app_state));
+}
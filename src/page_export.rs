@@ -0,0 +1,199 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use gtk::gdk;
+use gtk::gdk_pixbuf::{Colorspace, InterpType, Pixbuf};
+use gtk::prelude::{IsA, WidgetExt};
+use gtk4 as gtk;
+
+use crate::page_layout;
+use crate::types::PageDirection;
+
+pub enum ExportFormat {
+    Png,
+    Jpeg,
+}
+
+impl ExportFormat {
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|v| v.to_str())
+            .map(|v| v.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("jpg") | Some("jpeg") => ExportFormat::Jpeg,
+            _ => ExportFormat::Png,
+        }
+    }
+}
+
+// first is the page shown first in reading order, second is the next one. the pages keep
+// their size and are placed like on the screen, in a view just as large as the spread.
+pub fn compose_spread(
+    first: &Pixbuf,
+    second: Option<&Pixbuf>,
+    page_direction: PageDirection,
+    gutter_width: i32,
+    is_aligned_at_spine: bool,
+) -> Option<Pixbuf> {
+    let Some(second) = second else {
+        return Some(first.clone());
+    };
+
+    let (left, right) = match page_direction {
+        PageDirection::RightToLeft => (second, first),
+        PageDirection::LeftToRight => (first, second),
+    };
+
+    let width = left.width() + gutter_width + right.width();
+    let height = left.height().max(right.height());
+    let margin = page_layout::calc_margin_for_dual(
+        left,
+        right,
+        width,
+        height,
+        gutter_width,
+        is_aligned_at_spine,
+    );
+
+    let spread = Pixbuf::new(Colorspace::Rgb, true, 8, width, height)?;
+    spread.fill(0x00000000);
+    left.copy_area(
+        0,
+        0,
+        left.width(),
+        left.height(),
+        &spread,
+        margin.left_margin,
+        margin.top_margin_for_left,
+    );
+    right.copy_area(
+        0,
+        0,
+        right.width(),
+        right.height(),
+        &spread,
+        margin.left_margin_for_right,
+        margin.top_margin_for_right,
+    );
+
+    Some(spread)
+}
+
+// jpeg has no alpha channel, so transparent area is filled by white.
+fn flatten_alpha(pixbuf: &Pixbuf) -> Option<Pixbuf> {
+    if !pixbuf.has_alpha() {
+        return Some(pixbuf.clone());
+    }
+
+    let width = pixbuf.width();
+    let height = pixbuf.height();
+    let flattened = Pixbuf::new(Colorspace::Rgb, false, 8, width, height)?;
+    flattened.fill(0xFFFFFFFF);
+    pixbuf.composite(
+        &flattened,
+        0,
+        0,
+        width,
+        height,
+        0.0,
+        0.0,
+        1.0,
+        1.0,
+        InterpType::Nearest,
+        255,
+    );
+
+    Some(flattened)
+}
+
+pub fn save_pixbuf_to_file(pixbuf: &Pixbuf, path: &Path) -> Result<()> {
+    match ExportFormat::from_path(path) {
+        ExportFormat::Png => pixbuf.savev(path, "png", &[])?,
+        ExportFormat::Jpeg => {
+            let Some(flattened) = flatten_alpha(pixbuf) else {
+                return Err(anyhow::anyhow!("failed flatten alpha channel"));
+            };
+            flattened.savev(path, "jpeg", &[("quality", "95")])?
+        }
+    }
+
+    Ok(())
+}
+
+pub fn copy_pixbuf_to_clipboard(widget: &impl IsA<gtk::Widget>, pixbuf: &Pixbuf) {
+    let texture = gdk::Texture::for_pixbuf(pixbuf);
+    widget.clipboard().set_texture(&texture);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_page(width: i32, height: i32, rgba: u32) -> Pixbuf {
+        let pixbuf = Pixbuf::new(Colorspace::Rgb, true, 8, width, height).unwrap();
+        pixbuf.fill(rgba);
+        pixbuf
+    }
+
+    fn pixel(pixbuf: &Pixbuf, x: i32, y: i32) -> [u8; 4] {
+        let bytes = pixbuf.read_pixel_bytes();
+        let offset = (y * pixbuf.rowstride() + x * pixbuf.n_channels()) as usize;
+        [
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ]
+    }
+
+    const RED: u32 = 0xFF0000FF;
+    const BLUE: u32 = 0x0000FFFF;
+
+    #[test]
+    fn single_page_is_kept_as_it_is() {
+        let page = make_page(3, 5, RED);
+        let spread = compose_spread(&page, None, PageDirection::RightToLeft, 2, false).unwrap();
+        assert_eq!((spread.width(), spread.height()), (3, 5));
+    }
+
+    #[test]
+    fn pages_keep_their_size_and_are_centred() {
+        let first = make_page(2, 4, RED);
+        let second = make_page(4, 8, BLUE);
+        let spread =
+            compose_spread(&first, Some(&second), PageDirection::LeftToRight, 0, false).unwrap();
+        assert_eq!((spread.width(), spread.height()), (6, 8));
+        assert_eq!(pixel(&spread, 0, 1), [0, 0, 0, 0]);
+        assert_eq!(pixel(&spread, 0, 2), [255, 0, 0, 255]);
+        assert_eq!(pixel(&spread, 1, 5), [255, 0, 0, 255]);
+        assert_eq!(pixel(&spread, 1, 6), [0, 0, 0, 0]);
+        assert_eq!(pixel(&spread, 2, 0), [0, 0, 255, 255]);
+        assert_eq!(pixel(&spread, 5, 7), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn pages_aligned_at_spine_share_the_top_edge() {
+        let first = make_page(2, 4, RED);
+        let second = make_page(4, 8, BLUE);
+        let spread =
+            compose_spread(&first, Some(&second), PageDirection::LeftToRight, 0, true).unwrap();
+        assert_eq!(pixel(&spread, 0, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(&spread, 0, 4), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn first_page_is_on_the_right_from_right_to_left() {
+        let first = make_page(4, 4, RED);
+        let second = make_page(4, 4, BLUE);
+        let spread =
+            compose_spread(&first, Some(&second), PageDirection::RightToLeft, 2, false).unwrap();
+        assert_eq!((spread.width(), spread.height()), (10, 4));
+        assert_eq!(pixel(&spread, 0, 0), [0, 0, 255, 255]);
+        assert_eq!(pixel(&spread, 4, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(&spread, 5, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(&spread, 9, 0), [255, 0, 0, 255]);
+    }
+}
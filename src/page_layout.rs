@@ -0,0 +1,103 @@
+use gtk4 as gtk;
+
+// where the pages are placed in the drawing area, also used to put a spread together for export.
+
+#[derive(Default)]
+pub struct MarginData {
+    pub left_margin: i32,
+    pub top_margin: i32,
+}
+
+#[derive(Default)]
+pub struct MarginDataForDual {
+    pub left_margin: i32,
+    pub left_margin_for_right: i32,
+    pub top_margin_for_left: i32,
+    pub top_margin_for_right: i32,
+}
+
+pub fn calc_margin_for_single(
+    pixbuf_data: &gtk::gdk_pixbuf::Pixbuf,
+    target_width: i32,
+    target_height: i32,
+) -> MarginData {
+    let pic_height = pixbuf_data.height();
+    let pic_width = pixbuf_data.width();
+
+    let width_diff = target_width - pic_width;
+    let left_margin = if width_diff < 0 || width_diff == 0 {
+        0
+    } else {
+        width_diff / 2
+    };
+
+    let height_diff = target_height - pic_height;
+    let top_margin = if height_diff < 0 || height_diff == 0 {
+        0
+    } else {
+        height_diff / 2
+    };
+
+    MarginData {
+        left_margin,
+        top_margin,
+    }
+}
+
+// the pages of a spread are centred together, or they meet at the middle of the view with the
+// same top edge, so pages of different sizes look like an open book.
+pub fn calc_margin_for_dual(
+    left: &gtk::gdk_pixbuf::Pixbuf,
+    right: &gtk::gdk_pixbuf::Pixbuf,
+    target_width: i32,
+    target_height: i32,
+    gutter_width: i32,
+    is_aligned_at_spine: bool,
+) -> MarginDataForDual {
+    let left_height = left.height();
+    let left_width = left.width();
+    let right_height = right.height();
+    let right_width = right.width();
+
+    if is_aligned_at_spine {
+        // the spine moves off the middle only when a page would be cut off.
+        let spine = (target_width / 2)
+            .min(target_width - right_width - (gutter_width - gutter_width / 2))
+            .max(left_width + gutter_width / 2);
+        let top_margin = (target_height - left_height.max(right_height)).max(0) / 2;
+        return MarginDataForDual {
+            left_margin: spine - gutter_width / 2 - left_width,
+            left_margin_for_right: spine + (gutter_width - gutter_width / 2),
+            top_margin_for_left: top_margin,
+            top_margin_for_right: top_margin,
+        };
+    }
+
+    let width_diff = target_width - (left_width + gutter_width + right_width);
+    let left_margin = if width_diff <= 0 || left_width >= target_width / 2 {
+        0
+    } else {
+        width_diff / 2
+    };
+
+    let left_height_diff = target_height - left_height;
+    let top_margin_for_left = if left_height_diff <= 0 {
+        0
+    } else {
+        left_height_diff / 2
+    };
+
+    let right_height_diff = target_height - right_height;
+    let top_margin_for_right = if right_height_diff <= 0 {
+        0
+    } else {
+        right_height_diff / 2
+    };
+
+    MarginDataForDual {
+        left_margin,
+        left_margin_for_right: left_margin + left_width + gutter_width,
+        top_margin_for_left,
+        top_margin_for_right,
+    }
+}
@@ -0,0 +1,381 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use gtk::gdk_pixbuf::{Colorspace, InterpType, Pixbuf};
+use gtk::glib::object::Cast;
+use gtk::prelude::{BoxExt, CastNone, ListItemExt, ListModelExt, WidgetExt};
+use gtk::{gdk, gio, glib};
+use gtk4 as gtk;
+use gtk4::glib::variant::ToVariant;
+
+use crate::auto_crop::{self, CropBox};
+use crate::image_container::{self, ImageContainer, ImageContainerEx};
+use crate::page_transform::PageTransform;
+use crate::types::PageDirection;
+
+const THUMBNAIL_WIDTH: i32 = 120;
+const THUMBNAIL_HEIGHT: i32 = 170;
+
+type ImageContainerList = Arc<Mutex<Vec<ImageContainer>>>;
+
+// how a page is shown in the reader, so its thumbnail is turned and cut off the same way.
+// the margins are found in the thumbnail itself when auto crop has no box for the page.
+#[derive(Default, Clone, Copy, PartialEq)]
+pub struct PageView {
+    pub transform: PageTransform,
+    pub crop_box: Option<CropBox>,
+    pub auto_crop_tolerance: Option<u8>,
+}
+
+struct ThumbnailData {
+    bytes: glib::Bytes,
+    has_alpha: bool,
+    width: i32,
+    height: i32,
+    rowstride: i32,
+}
+
+// thumbnails of the open book, it does not hold the grid view, so the factory can keep it.
+#[derive(Clone)]
+struct ThumbnailCache {
+    image_container_list: Rc<RefCell<Option<ImageContainerList>>>,
+    page_views: Rc<RefCell<Vec<PageView>>>,
+    thumbnails: Rc<RefCell<HashMap<usize, gdk::Texture>>>,
+    // pictures of the pages which are bound now, only they get thumbnails.
+    bound_pictures: Rc<RefCell<HashMap<usize, gtk::Picture>>>,
+    bound_indexes: Arc<Mutex<HashSet<usize>>>,
+    pending_indexes: Rc<RefCell<HashSet<usize>>>,
+    generation: Arc<Mutex<u64>>,
+}
+
+impl ThumbnailCache {
+    fn bind(&self, index: usize, picture: &gtk::Picture) {
+        self.bound_pictures
+            .borrow_mut()
+            .insert(index, picture.clone());
+        self.bound_indexes.lock().unwrap().insert(index);
+        match self.thumbnails.borrow().get(&index) {
+            Some(texture) => picture.set_paintable(Some(texture)),
+            None => picture.set_paintable(None::<&gdk::Paintable>),
+        }
+        self.request_thumbnail(index);
+    }
+
+    // the position of an item is gone when it is removed, so the picture is looked up.
+    fn unbind(&self, picture: &gtk::Picture) {
+        let mut bound_pictures = self.bound_pictures.borrow_mut();
+        let mut bound_indexes = self.bound_indexes.lock().unwrap();
+        bound_pictures.retain(|index, v| {
+            let is_bound = v != picture;
+            if !is_bound {
+                bound_indexes.remove(index);
+            }
+            is_bound
+        });
+    }
+
+    fn get_page_view(&self, index: usize) -> PageView {
+        self.page_views
+            .borrow()
+            .get(index)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    // the thumbnail is made on a thread of the gio pool and is set when it is still bound.
+    fn request_thumbnail(&self, index: usize) {
+        if self.thumbnails.borrow().contains_key(&index)
+            || !self.pending_indexes.borrow_mut().insert(index)
+        {
+            return;
+        }
+        let Some(image_container_list) = self.image_container_list.borrow().clone() else {
+            return;
+        };
+        let page_view = self.get_page_view(index);
+        let generation = *self.generation.lock().unwrap();
+
+        let generation_arc = Arc::clone(&self.generation);
+        let bound_indexes = Arc::clone(&self.bound_indexes);
+        let task = gio::spawn_blocking(move || {
+            // the page was scrolled away or another book was opened while it was queued.
+            if *generation_arc.lock().unwrap() != generation
+                || !bound_indexes.lock().unwrap().contains(&index)
+            {
+                return None;
+            }
+            create_thumbnail(&image_container_list, index, page_view)
+        });
+
+        let thumbnail_cache = self.clone();
+        glib::spawn_future_local(async move {
+            let thumbnail = task.await.ok().flatten();
+            if *thumbnail_cache.generation.lock().unwrap() != generation {
+                return;
+            }
+            thumbnail_cache.pending_indexes.borrow_mut().remove(&index);
+            // the page was turned or cropped while its thumbnail was made.
+            if thumbnail_cache.get_page_view(index) != page_view {
+                if thumbnail_cache.bound_pictures.borrow().contains_key(&index) {
+                    thumbnail_cache.request_thumbnail(index);
+                }
+                return;
+            }
+            let Some(thumbnail) = thumbnail else {
+                return;
+            };
+
+            let pixbuf = Pixbuf::from_bytes(
+                &thumbnail.bytes,
+                Colorspace::Rgb,
+                thumbnail.has_alpha,
+                8,
+                thumbnail.width,
+                thumbnail.height,
+                thumbnail.rowstride,
+            );
+            let texture = gdk::Texture::for_pixbuf(&pixbuf);
+            if let Some(picture) = thumbnail_cache.bound_pictures.borrow().get(&index) {
+                picture.set_paintable(Some(&texture));
+            }
+            thumbnail_cache
+                .thumbnails
+                .borrow_mut()
+                .insert(index, texture);
+        });
+    }
+}
+
+#[derive(Clone)]
+pub struct PageOverview {
+    pub scrolled_window: gtk::ScrolledWindow,
+    grid_view: gtk::GridView,
+    selection: gtk::SingleSelection,
+    page_numbers: gtk::StringList,
+    thumbnail_cache: ThumbnailCache,
+    book_key: Rc<RefCell<Option<(String, usize)>>>,
+}
+
+impl PageOverview {
+    pub fn new() -> Self {
+        let thumbnail_cache = ThumbnailCache {
+            image_container_list: Rc::new(RefCell::new(None)),
+            page_views: Rc::new(RefCell::new(vec![])),
+            thumbnails: Rc::new(RefCell::new(HashMap::new())),
+            bound_pictures: Rc::new(RefCell::new(HashMap::new())),
+            bound_indexes: Arc::new(Mutex::new(HashSet::new())),
+            pending_indexes: Rc::new(RefCell::new(HashSet::new())),
+            generation: Arc::new(Mutex::new(0)),
+        };
+
+        let factory = gtk::SignalListItemFactory::new();
+        factory.connect_setup(|_factory, list_item| {
+            let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
+                return;
+            };
+            let picture = gtk::Picture::new();
+            picture.set_can_shrink(true);
+            picture.set_size_request(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT);
+
+            let label = gtk::Label::new(None);
+            let v_box = gtk::Box::new(gtk::Orientation::Vertical, 2);
+            v_box.append(&picture);
+            v_box.append(&label);
+            list_item.set_child(Some(&v_box));
+        });
+        factory.connect_bind(glib::clone!(
+            #[strong]
+            thumbnail_cache,
+            move |_factory, list_item| {
+                let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
+                    return;
+                };
+                let index = list_item.position() as usize;
+                let Some(picture) = list_item
+                    .child()
+                    .and_then(|v| v.first_child())
+                    .and_downcast::<gtk::Picture>()
+                else {
+                    return;
+                };
+                if let Some(label) = picture.next_sibling().and_downcast::<gtk::Label>() {
+                    label.set_text(&(index + 1).to_string());
+                }
+                thumbnail_cache.bind(index, &picture);
+            }
+        ));
+        factory.connect_unbind(glib::clone!(
+            #[strong]
+            thumbnail_cache,
+            move |_factory, list_item| {
+                let Some(picture) = list_item
+                    .downcast_ref::<gtk::ListItem>()
+                    .and_then(|v| v.child())
+                    .and_then(|v| v.first_child())
+                    .and_downcast::<gtk::Picture>()
+                else {
+                    return;
+                };
+                thumbnail_cache.unbind(&picture);
+            }
+        ));
+
+        let page_numbers = gtk::StringList::new(&[]);
+        let selection = gtk::SingleSelection::new(Some(page_numbers.clone()));
+        let grid_view = gtk::GridView::new(Some(selection.clone()), Some(factory));
+        grid_view.set_single_click_activate(true);
+        grid_view.set_max_columns(32);
+
+        let scrolled_window = gtk::ScrolledWindow::new();
+        scrolled_window.set_hexpand(true);
+        scrolled_window.set_vexpand(true);
+        scrolled_window.set_child(Some(&grid_view));
+
+        PageOverview {
+            scrolled_window,
+            grid_view,
+            selection,
+            page_numbers,
+            thumbnail_cache,
+            book_key: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    pub fn connect_page_activated<F: Fn(usize) + 'static>(&self, f: F) {
+        let _ = self
+            .grid_view
+            .connect_activate(move |_grid_view, position| f(position as usize));
+    }
+
+    pub fn set_page_direction(&self, page_direction: PageDirection) {
+        let text_direction = match page_direction {
+            PageDirection::RightToLeft => gtk::TextDirection::Rtl,
+            PageDirection::LeftToRight => gtk::TextDirection::Ltr,
+        };
+        self.grid_view.set_direction(text_direction);
+    }
+
+    pub fn set_current_page(&self, page_index: usize) {
+        if page_index >= self.page_numbers.n_items() as usize {
+            return;
+        }
+        self.selection.set_selected(page_index as u32);
+        let _ = self.grid_view.activate_action(
+            "list.scroll-to-item",
+            Some(&(page_index as u32).to_variant()),
+        );
+        self.grid_view.grab_focus();
+    }
+
+    // the model is made again only when another book is opened, a thumbnail is made when its
+    // page is bound, so only the pages which are scrolled into view are rendered.
+    pub fn update(
+        &self,
+        image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+        book_name: &str,
+        page_views: Vec<PageView>,
+    ) {
+        let thumbnail_cache = &self.thumbnail_cache;
+        let max_size = image_container_list.lock().unwrap().len();
+        let book_key = (book_name.to_owned(), max_size);
+        if self.book_key.borrow().as_ref() == Some(&book_key) {
+            // a page turned or cropped since the last time is rendered again.
+            let changed_indexes: Vec<usize> = page_views
+                .iter()
+                .zip(thumbnail_cache.page_views.borrow().iter())
+                .enumerate()
+                .filter(|(_, (new, old))| new != old)
+                .map(|(i, _)| i)
+                .collect();
+            thumbnail_cache.page_views.replace(page_views);
+            for index in changed_indexes {
+                thumbnail_cache.thumbnails.borrow_mut().remove(&index);
+                if thumbnail_cache.bound_pictures.borrow().contains_key(&index) {
+                    thumbnail_cache.request_thumbnail(index);
+                }
+            }
+            return;
+        }
+        self.book_key.replace(Some(book_key));
+
+        *thumbnail_cache.generation.lock().unwrap() += 1;
+        thumbnail_cache
+            .image_container_list
+            .replace(Some(Arc::clone(image_container_list)));
+        thumbnail_cache.page_views.replace(page_views);
+        thumbnail_cache.thumbnails.borrow_mut().clear();
+        thumbnail_cache.pending_indexes.borrow_mut().clear();
+
+        let page_numbers: Vec<String> = (1..=max_size).map(|v| v.to_string()).collect();
+        let page_numbers: Vec<&str> = page_numbers.iter().map(|v| v.as_str()).collect();
+        self.page_numbers
+            .splice(0, self.page_numbers.n_items(), &page_numbers);
+    }
+}
+
+fn create_thumbnail(
+    image_container_list: &Arc<Mutex<Vec<ImageContainer>>>,
+    index: usize,
+    page_view: PageView,
+) -> Option<ThumbnailData> {
+    let pixbuf = image_container_list
+        .lock()
+        .unwrap()
+        .get(index)?
+        .get_orig_pixbuf_data()?;
+
+    // the page is made small first, it fits either way round so a quarter turn keeps enough
+    // pixels, and the margins are cut off from it before the last scale.
+    let reduced_size = f64::from(THUMBNAIL_WIDTH.max(THUMBNAIL_HEIGHT) * 2);
+    let (width, height) = image_container::calc_fit_size(
+        f64::from(pixbuf.width()),
+        f64::from(pixbuf.height()),
+        reduced_size,
+        reduced_size,
+    );
+    let reduced = pixbuf.scale_simple(
+        (width.ceil() as i32).max(1),
+        (height.ceil() as i32).max(1),
+        InterpType::Bilinear,
+    )?;
+    let transformed = image_container::apply_page_transform(&reduced, page_view.transform)?;
+
+    let crop_box = page_view.crop_box.or_else(|| {
+        let tolerance = page_view.auto_crop_tolerance?;
+        let bytes = transformed.read_pixel_bytes();
+        Some(auto_crop::detect_crop_box(
+            &auto_crop::PixelData {
+                pixels: &bytes,
+                width: transformed.width() as usize,
+                height: transformed.height() as usize,
+                rowstride: transformed.rowstride() as usize,
+                n_channels: transformed.n_channels() as usize,
+            },
+            tolerance,
+        ))
+    });
+    let cropped = image_container::crop_pixbuf(&transformed, crop_box.unwrap_or_default());
+
+    let (width, height) = image_container::calc_fit_size(
+        f64::from(cropped.width()),
+        f64::from(cropped.height()),
+        f64::from(THUMBNAIL_WIDTH),
+        f64::from(THUMBNAIL_HEIGHT),
+    );
+    let scaled = cropped.scale_simple(
+        (width.ceil() as i32).max(1),
+        (height.ceil() as i32).max(1),
+        InterpType::Bilinear,
+    )?;
+
+    Some(ThumbnailData {
+        bytes: scaled.read_pixel_bytes(),
+        has_alpha: scaled.has_alpha(),
+        width: scaled.width(),
+        height: scaled.height(),
+        rowstride: scaled.rowstride(),
+    })
+}
@@ -0,0 +1,264 @@
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+
+use gtk::gdk_pixbuf::Pixbuf;
+use gtk::glib::object::Cast;
+use gtk::prelude::{
+    BoxExt, CastNone, CheckButtonExt, GdkCairoContextExt, IsA, PrintOperationExt, WidgetExt,
+};
+use gtk4 as gtk;
+
+use crate::image_container;
+use crate::page_export;
+use crate::types::PageDirection;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PrintSheet {
+    first: usize,
+    second: Option<usize>,
+}
+
+struct PrintState {
+    is_spread: bool,
+    sheets: Vec<PrintSheet>,
+}
+
+pub struct PrintJob {
+    // one entry for each page of the book, None for a page which could not be loaded.
+    pub pixbufs: Vec<Option<Pixbuf>>,
+    pub page_direction: PageDirection,
+    pub current_page_index: usize,
+    pub is_spread: bool,
+    pub spread_gutter_width: i32,
+    pub is_spread_aligned_at_spine: bool,
+}
+
+fn create_print_sheets(
+    page_count: usize,
+    is_spread: bool,
+    current_page_index: usize,
+) -> Vec<PrintSheet> {
+    if !is_spread {
+        return (0..page_count)
+            .map(|i| PrintSheet {
+                first: i,
+                second: None,
+            })
+            .collect();
+    }
+
+    let mut sheets: Vec<PrintSheet> = vec![];
+    let mut index = 0;
+
+    // keep the same pairing as the spread shown in the window.
+    if current_page_index % 2 == 1 && page_count > 0 {
+        sheets.push(PrintSheet {
+            first: 0,
+            second: None,
+        });
+        index = 1;
+    }
+
+    while index < page_count {
+        let second = if index + 1 < page_count {
+            Some(index + 1)
+        } else {
+            None
+        };
+        sheets.push(PrintSheet {
+            first: index,
+            second,
+        });
+        index += 2;
+    }
+
+    sheets
+}
+
+fn find_sheet_index(sheets: &[PrintSheet], page_index: usize) -> i32 {
+    sheets
+        .iter()
+        .position(|v| v.first == page_index || v.second == Some(page_index))
+        .unwrap_or(0) as i32
+}
+
+fn draw_pixbuf_fit_to_paper(print_context: &gtk::PrintContext, pixbuf: &Pixbuf) {
+    let ctx = print_context.cairo_context();
+    let paper_width = print_context.width();
+    let paper_height = print_context.height();
+
+    let width = f64::from(pixbuf.width());
+    let height = f64::from(pixbuf.height());
+    let (result_width, result_height) =
+        image_container::calc_fit_size(width, height, paper_width, paper_height);
+    let scale = result_width / width;
+
+    ctx.translate(
+        (paper_width - result_width) / 2.0,
+        (paper_height - result_height) / 2.0,
+    );
+    ctx.scale(scale, scale);
+    ctx.set_source_pixbuf(pixbuf, 0.0, 0.0);
+    let _ = ctx.paint();
+}
+
+pub fn run_print_operation(
+    window: &impl IsA<gtk::Window>,
+    job: PrintJob,
+    export_filename: Option<&Path>,
+) {
+    let print_operation = gtk::PrintOperation::new();
+    print_operation.set_job_name("Simple Comics Viewer");
+    print_operation.set_custom_tab_label(Some("Comics"));
+
+    let sheets = create_print_sheets(job.pixbufs.len(), job.is_spread, job.current_page_index);
+    print_operation.set_n_pages(sheets.len().max(1) as i32);
+    print_operation.set_current_page(find_sheet_index(&sheets, job.current_page_index));
+
+    let state = Rc::new(RefCell::new(PrintState {
+        is_spread: job.is_spread,
+        sheets,
+    }));
+    let job = Rc::new(job);
+
+    let is_spread_default = job.is_spread;
+    print_operation.connect_create_custom_widget(move |_print_operation| {
+        let check_button = gtk::CheckButton::with_label("Print as spreads");
+        check_button.set_active(is_spread_default);
+        // the page range of the general tab is given in sheets, not in book pages.
+        let note_label =
+            gtk::Label::new(Some("With spreads, page ranges count sheets of two pages."));
+        note_label.set_halign(gtk::Align::Start);
+        note_label.add_css_class("dim-label");
+        let custom_box = gtk::Box::new(gtk::Orientation::Vertical, 6);
+        custom_box.set_margin_top(12);
+        custom_box.set_margin_start(12);
+        custom_box.append(&check_button);
+        custom_box.append(&note_label);
+        Some(custom_box.upcast::<gtk::glib::Object>())
+    });
+
+    print_operation.connect_custom_widget_apply(gtk::glib::clone!(
+        #[strong]
+        state,
+        move |_print_operation, widget| {
+            let Some(check_button) = widget
+                .downcast_ref::<gtk::Box>()
+                .and_then(|v| v.first_child())
+                .and_downcast::<gtk::CheckButton>()
+            else {
+                return;
+            };
+            state.borrow_mut().is_spread = check_button.is_active();
+        }
+    ));
+
+    print_operation.connect_begin_print(gtk::glib::clone!(
+        #[strong]
+        state,
+        #[strong]
+        job,
+        move |print_operation, _print_context| {
+            let mut state_ref = state.borrow_mut();
+            state_ref.sheets = create_print_sheets(
+                job.pixbufs.len(),
+                state_ref.is_spread,
+                job.current_page_index,
+            );
+            print_operation.set_n_pages(state_ref.sheets.len().max(1) as i32);
+            print_operation
+                .set_current_page(find_sheet_index(&state_ref.sheets, job.current_page_index));
+        }
+    ));
+
+    print_operation.connect_draw_page(gtk::glib::clone!(
+        #[strong]
+        state,
+        #[strong]
+        job,
+        move |_print_operation, print_context, page_nr| {
+            let state_ref = state.borrow();
+            let Some(sheet) = state_ref.sheets.get(page_nr as usize) else {
+                return;
+            };
+            let get_pixbuf = |v: usize| job.pixbufs.get(v).and_then(|v| v.as_ref());
+            // a page which could not be loaded leaves the other page of the sheet alone.
+            let (first, second) = match (get_pixbuf(sheet.first), sheet.second.and_then(get_pixbuf))
+            {
+                (Some(first), second) => (first, second),
+                (None, Some(second)) => (second, None),
+                (None, None) => return,
+            };
+
+            let Some(pixbuf) = page_export::compose_spread(
+                first,
+                second,
+                job.page_direction,
+                job.spread_gutter_width,
+                job.is_spread_aligned_at_spine,
+            ) else {
+                return;
+            };
+            draw_pixbuf_fit_to_paper(print_context, &pixbuf);
+        }
+    ));
+
+    let action = if let Some(path) = export_filename {
+        print_operation.set_export_filename(path);
+        gtk::PrintOperationAction::Export
+    } else {
+        gtk::PrintOperationAction::PrintDialog
+    };
+
+    if let Err(e) = print_operation.run(action, Some(window)) {
+        eprintln!("{}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheet(first: usize, second: Option<usize>) -> PrintSheet {
+        PrintSheet { first, second }
+    }
+
+    #[test]
+    fn single_pages_are_one_sheet_each() {
+        assert_eq!(
+            create_print_sheets(3, false, 1),
+            [sheet(0, None), sheet(1, None), sheet(2, None)]
+        );
+        assert!(create_print_sheets(0, false, 0).is_empty());
+    }
+
+    #[test]
+    fn spreads_pair_pages_from_the_first_one() {
+        assert_eq!(
+            create_print_sheets(5, true, 2),
+            [sheet(0, Some(1)), sheet(2, Some(3)), sheet(4, None)]
+        );
+        assert_eq!(create_print_sheets(1, true, 0), [sheet(0, None)]);
+        assert!(create_print_sheets(0, true, 1).is_empty());
+    }
+
+    #[test]
+    fn spreads_keep_the_pairing_of_an_odd_current_page() {
+        assert_eq!(
+            create_print_sheets(4, true, 1),
+            [sheet(0, None), sheet(1, Some(2)), sheet(3, None)]
+        );
+    }
+
+    #[test]
+    fn sheet_of_a_page_is_found() {
+        let sheets = create_print_sheets(5, true, 1);
+        assert_eq!(find_sheet_index(&sheets, 0), 0);
+        assert_eq!(find_sheet_index(&sheets, 1), 1);
+        assert_eq!(find_sheet_index(&sheets, 2), 1);
+        assert_eq!(find_sheet_index(&sheets, 4), 2);
+        // a page out of the book falls back to the first sheet.
+        assert_eq!(find_sheet_index(&sheets, 9), 0);
+    }
+}
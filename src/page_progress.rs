@@ -0,0 +1,203 @@
+// the slider value is the fraction of the book read up to the first shown page,
+// it is full when the last page is shown in both single and dual mode.
+pub fn calc_progress_fraction(page_index: usize, max_size: usize, is_dual: bool) -> f64 {
+    if max_size == 0 {
+        return 0.0;
+    }
+
+    let shown_pages = if is_dual { 2 } else { 1 };
+    if page_index + shown_pages >= max_size {
+        return 1.0;
+    }
+
+    (page_index + 1) as f64 / max_size as f64
+}
+
+// spreads always start at an even page, so the pairs do not change on a jump.
+pub fn get_spread_start(page_index: usize, is_dual: bool) -> usize {
+    if is_dual {
+        page_index - page_index % 2
+    } else {
+        page_index
+    }
+}
+
+pub fn get_last_page_index(max_size: usize, is_dual: bool) -> usize {
+    get_spread_start(max_size.saturating_sub(1), is_dual)
+}
+
+pub fn get_page_index_from_fraction(fraction: f64, max_size: usize, is_dual: bool) -> usize {
+    if max_size == 0 {
+        return 0;
+    }
+
+    let page_number = (fraction.clamp(0.0, 1.0) * max_size as f64).round() as usize;
+    get_spread_start(page_number.clamp(1, max_size) - 1, is_dual)
+}
+
+// a chapter starts where the directory of the page changes in an archive,
+// the first page is not counted as it would be a mark at the start of the slider.
+pub fn find_chapter_starts(file_names: &[&str]) -> Vec<usize> {
+    let get_dir_name = |file_name: &str| {
+        file_name
+            .trim_end_matches('/')
+            .rsplit_once('/')
+            .map(|(dir_name, _)| dir_name.to_string())
+            .unwrap_or_default()
+    };
+
+    let mut chapter_starts = vec![];
+    let mut previous_dir_name: Option<String> = None;
+    for (i, file_name) in file_names.iter().enumerate() {
+        let dir_name = get_dir_name(file_name);
+        if previous_dir_name.as_ref().is_some_and(|v| *v != dir_name) {
+            chapter_starts.push(i);
+        }
+        previous_dir_name = Some(dir_name);
+    }
+
+    chapter_starts
+}
+
+pub fn calc_chapter_mark_value(chapter_start: usize, max_size: usize) -> f64 {
+    calc_progress_fraction(chapter_start, max_size, false)
+}
+
+// the compared book follows the page of the first book, moved by the offset,
+// nothing is shown on its side when the offset moves it out of the book.
+pub fn get_compare_page_index(
+    page_index: usize,
+    page_offset: i64,
+    max_size: usize,
+) -> Option<usize> {
+    let compare_page_index = page_index as i64 + page_offset;
+    if compare_page_index < 0 || compare_page_index >= max_size as i64 {
+        return None;
+    }
+
+    Some(compare_page_index as usize)
+}
+
+// the offset is kept within the compared book, so a page of it is shown at some page.
+pub fn clamp_compare_page_offset(page_offset: i64, max_size: usize) -> i64 {
+    let limit = max_size.saturating_sub(1) as i64;
+    page_offset.clamp(-limit, limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_of_single_mode() {
+        assert_eq!(calc_progress_fraction(0, 4, false), 0.25);
+        assert_eq!(calc_progress_fraction(1, 4, false), 0.5);
+        assert_eq!(calc_progress_fraction(3, 4, false), 1.0);
+    }
+
+    #[test]
+    fn progress_of_dual_mode() {
+        assert_eq!(calc_progress_fraction(0, 6, true), 1.0 / 6.0);
+        assert_eq!(calc_progress_fraction(2, 6, true), 0.5);
+        assert_eq!(calc_progress_fraction(4, 6, true), 1.0);
+        // the last page of an odd book is shown alone.
+        assert_eq!(calc_progress_fraction(4, 5, true), 1.0);
+    }
+
+    #[test]
+    fn progress_of_empty_or_single_page_book() {
+        assert_eq!(calc_progress_fraction(0, 0, false), 0.0);
+        assert_eq!(calc_progress_fraction(0, 0, true), 0.0);
+        assert_eq!(calc_progress_fraction(0, 1, false), 1.0);
+        assert_eq!(calc_progress_fraction(0, 1, true), 1.0);
+    }
+
+    #[test]
+    fn page_index_round_trips_through_fraction() {
+        for max_size in 1..20 {
+            for page_index in 0..max_size {
+                let fraction = calc_progress_fraction(page_index, max_size, false);
+                assert_eq!(
+                    get_page_index_from_fraction(fraction, max_size, false),
+                    page_index
+                );
+            }
+            for page_index in (0..max_size).step_by(2) {
+                let fraction = calc_progress_fraction(page_index, max_size, true);
+                assert_eq!(
+                    get_page_index_from_fraction(fraction, max_size, true),
+                    page_index
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn spreads_start_at_even_pages() {
+        assert_eq!(get_spread_start(3, true), 2);
+        assert_eq!(get_spread_start(4, true), 4);
+        assert_eq!(get_spread_start(3, false), 3);
+        assert_eq!(get_last_page_index(6, true), 4);
+        // the last page of an odd book is shown alone.
+        assert_eq!(get_last_page_index(5, true), 4);
+        assert_eq!(get_last_page_index(0, true), 0);
+    }
+
+    #[test]
+    fn page_index_from_out_of_range_fraction() {
+        assert_eq!(get_page_index_from_fraction(-1.0, 10, false), 0);
+        assert_eq!(get_page_index_from_fraction(2.0, 10, false), 9);
+        assert_eq!(get_page_index_from_fraction(1.0, 10, true), 8);
+        assert_eq!(get_page_index_from_fraction(0.5, 0, false), 0);
+    }
+
+    #[test]
+    fn chapters_start_at_directory_changes() {
+        let file_names = [
+            "vol1/ch1/001.jpg",
+            "vol1/ch1/002.jpg",
+            "vol1/ch2/001.jpg",
+            "vol1/ch2/002.jpg",
+            "vol1/ch3/001.jpg",
+        ];
+        assert_eq!(find_chapter_starts(&file_names), vec![2, 4]);
+    }
+
+    #[test]
+    fn no_chapters_in_flat_archive() {
+        assert!(find_chapter_starts(&["001.jpg", "002.jpg", "003.jpg"]).is_empty());
+        assert!(find_chapter_starts(&["book/001.jpg", "book/002.jpg"]).is_empty());
+        assert!(find_chapter_starts(&[]).is_empty());
+    }
+
+    #[test]
+    fn chapter_mark_is_at_the_first_page_of_the_chapter() {
+        assert_eq!(calc_chapter_mark_value(2, 4), 0.75);
+        assert_eq!(
+            get_page_index_from_fraction(calc_chapter_mark_value(2, 4), 4, false),
+            2
+        );
+    }
+
+    #[test]
+    fn compare_page_follows_page_with_offset() {
+        assert_eq!(get_compare_page_index(0, 0, 10), Some(0));
+        assert_eq!(get_compare_page_index(3, 2, 10), Some(5));
+        assert_eq!(get_compare_page_index(3, -2, 10), Some(1));
+    }
+
+    #[test]
+    fn compare_page_out_of_book() {
+        assert_eq!(get_compare_page_index(1, -2, 10), None);
+        assert_eq!(get_compare_page_index(8, 2, 10), None);
+        assert_eq!(get_compare_page_index(0, 0, 0), None);
+    }
+
+    #[test]
+    fn compare_page_offset_stays_in_book() {
+        assert_eq!(clamp_compare_page_offset(3, 10), 3);
+        assert_eq!(clamp_compare_page_offset(12, 10), 9);
+        assert_eq!(clamp_compare_page_offset(-12, 10), -9);
+        assert_eq!(clamp_compare_page_offset(1, 0), 0);
+    }
+}
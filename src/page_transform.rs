@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+
+// a page is rotated clockwise first and then flipped horizontally,
+// a vertical flip is the same as a horizontal flip with a half turn.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct PageTransform {
+    pub rotation: i32,
+    pub is_flipped: bool,
+}
+
+fn normalize_rotation(rotation: i32) -> i32 {
+    rotation.rem_euclid(360) / 90 * 90
+}
+
+impl PageTransform {
+    pub fn from_rotation(rotation: i32) -> Self {
+        PageTransform {
+            rotation: normalize_rotation(rotation),
+            is_flipped: false,
+        }
+    }
+
+    pub fn is_identity(self) -> bool {
+        self == PageTransform::default()
+    }
+
+    // the rotation is seen on the screen, so it goes backward on a flipped page.
+    pub fn rotate(self, degrees: i32) -> Self {
+        let rotation = if self.is_flipped {
+            self.rotation - degrees
+        } else {
+            self.rotation + degrees
+        };
+        PageTransform {
+            rotation: normalize_rotation(rotation),
+            is_flipped: self.is_flipped,
+        }
+    }
+
+    pub fn flip_horizontally(self) -> Self {
+        PageTransform {
+            rotation: self.rotation,
+            is_flipped: !self.is_flipped,
+        }
+    }
+
+    pub fn flip_vertically(self) -> Self {
+        self.flip_horizontally().rotate(180)
+    }
+
+    // self is applied first and then the other.
+    pub fn then(self, other: PageTransform) -> Self {
+        let transform = self.rotate(other.rotation);
+        if other.is_flipped {
+            transform.flip_horizontally()
+        } else {
+            transform
+        }
+    }
+
+    // two bits for quarter turns and one for the flip.
+    pub fn to_code(self) -> i64 {
+        i64::from(self.rotation / 90) | if self.is_flipped { 4 } else { 0 }
+    }
+
+    pub fn from_code(code: i64) -> Self {
+        PageTransform {
+            rotation: normalize_rotation(((code & 3) * 90) as i32),
+            is_flipped: code & 4 != 0,
+        }
+    }
+}
+
+// transforms of single pages are saved as "page_index:code" separated by commas.
+pub fn encode_page_transforms(page_transforms: &BTreeMap<usize, PageTransform>) -> String {
+    page_transforms
+        .iter()
+        .filter(|(_, v)| !v.is_identity())
+        .map(|(page_index, v)| format!("{}:{}", page_index, v.to_code()))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+pub fn decode_page_transforms(text: &str) -> BTreeMap<usize, PageTransform> {
+    text.split(',')
+        .filter_map(|v| {
+            let (page_index, code) = v.split_once(':')?;
+            let page_index = page_index.trim().parse::<usize>().ok()?;
+            let code = code.trim().parse::<i64>().ok()?;
+            Some((page_index, PageTransform::from_code(code)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotations_wrap_around() {
+        let transform = PageTransform::default().rotate(90).rotate(180);
+        assert_eq!(transform, PageTransform::from_rotation(270));
+        assert!(transform.rotate(90).is_identity());
+        assert_eq!(PageTransform::default().rotate(-90).rotation, 270);
+    }
+
+    #[test]
+    fn flipping_twice_is_identity() {
+        let transform = PageTransform::default();
+        assert!(
+            transform
+                .flip_horizontally()
+                .flip_horizontally()
+                .is_identity()
+        );
+        assert!(transform.flip_vertically().flip_vertically().is_identity());
+        // both flips are a half turn.
+        assert_eq!(
+            transform.flip_horizontally().flip_vertically(),
+            PageTransform::from_rotation(180)
+        );
+    }
+
+    #[test]
+    fn rotation_after_flip_turns_on_the_screen() {
+        let transform = PageTransform::default().flip_horizontally().rotate(90);
+        assert_eq!(transform.rotation, 270);
+        assert!(transform.rotate(-90).flip_horizontally().is_identity());
+    }
+
+    #[test]
+    fn then_applies_both_transforms() {
+        let book = PageTransform::default().rotate(90);
+        let page = PageTransform::default().flip_horizontally().rotate(90);
+        assert_eq!(book.then(page), book.flip_horizontally().rotate(90));
+        assert_eq!(book.then(PageTransform::default()), book);
+        assert_eq!(PageTransform::default().then(page), page);
+    }
+
+    #[test]
+    fn code_round_trips() {
+        for rotation in [0, 90, 180, 270] {
+            for is_flipped in [false, true] {
+                let transform = PageTransform {
+                    rotation,
+                    is_flipped,
+                };
+                assert_eq!(PageTransform::from_code(transform.to_code()), transform);
+            }
+        }
+    }
+
+    #[test]
+    fn page_transforms_round_trip() {
+        let mut page_transforms = BTreeMap::new();
+        page_transforms.insert(0, PageTransform::from_rotation(90));
+        page_transforms.insert(12, PageTransform::default().flip_horizontally());
+        page_transforms.insert(3, PageTransform::default());
+
+        let text = encode_page_transforms(&page_transforms);
+        assert_eq!(text, "0:1,12:4");
+        page_transforms.remove(&3);
+        assert_eq!(decode_page_transforms(&text), page_transforms);
+        assert!(decode_page_transforms("").is_empty());
+        assert!(decode_page_transforms("x:1,2").is_empty());
+    }
+}
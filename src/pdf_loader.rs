@@ -1,9 +1,11 @@
-use mupdf::{Document, Pixmap};
+use std::sync::mpsc;
 
-use crate::utils;
+use mupdf::{Document, Pixmap};
 
 pub struct PdfPixmap {
     pub pixmap: Pixmap,
+    // clockwise degrees rendered into the pixmap.
+    pub rotation: i32,
 }
 
 unsafe impl Send for PdfPixmap {}
@@ -11,18 +13,17 @@ unsafe impl Sync for PdfPixmap {}
 
 pub fn load_pdf(
     file_path: &str,
+    ppi: f32,
     pdf_pixmaps: &std::sync::Arc<std::sync::Mutex<Vec<PdfPixmap>>>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let document = Document::open(file_path)?;
 
-    let ppi = utils::get_dpi();
-
     for page_result in document.pages()? {
         let page = page_result?;
         // let boundbox = page.bounds()?;
         // let display_list = page.to_display_list(false)?;
         // let device = mupdf::Device::from_display_list(&display_list)?;
-        let zoom = (ppi / 96.0) as f32;
+        let zoom = ppi / 96.0;
         let mut ctm = mupdf::Matrix::new_scale(zoom, zoom);
         ctm.rotate(0.0);
         let cs = mupdf::Colorspace::device_rgb();
@@ -30,11 +31,111 @@ pub fn load_pdf(
         // page.run(&device, &ctm)?;
 
         let pixmap = page.to_pixmap(&ctm, &cs, true, true)?;
-        pdf_pixmaps
-            .lock()
-            .unwrap()
-            .push(PdfPixmap { pixmap: pixmap });
+        pdf_pixmaps.lock().unwrap().push(PdfPixmap {
+            pixmap: pixmap,
+            rotation: 0,
+        });
     }
 
     Ok(())
 }
+
+#[derive(Clone, PartialEq)]
+pub struct PdfPageRequest {
+    pub file_path: String,
+    pub page_index: usize,
+    pub rotation: i32,
+    pub ppi: f32,
+}
+
+pub struct RenderedPdfPage {
+    pub request: PdfPageRequest,
+    // none when the page could not be rendered, it is not requested again.
+    pub pdf_pixmap: Option<PdfPixmap>,
+}
+
+// a rotated page is rendered again by mupdf, so its text and lines stay sharp.
+// one thread renders the requested pages and keeps the last document open for the next ones.
+pub struct PdfPageRenderer {
+    request_sender: mpsc::Sender<PdfPageRequest>,
+    rendered_receiver: mpsc::Receiver<RenderedPdfPage>,
+    // requests which are not rendered yet, they are not sent twice.
+    pending_requests: Vec<PdfPageRequest>,
+}
+
+impl PdfPageRenderer {
+    pub fn new() -> Self {
+        let (request_sender, request_receiver) = mpsc::channel::<PdfPageRequest>();
+        let (rendered_sender, rendered_receiver) = mpsc::channel::<RenderedPdfPage>();
+        let _ = std::thread::spawn(move || {
+            let mut document: Option<(String, Document)> = None;
+            // it ends when the renderer is dropped with its window.
+            for request in request_receiver {
+                let pdf_pixmap = render_pdf_page(&mut document, &request)
+                    .inspect_err(|e| eprintln!("{}", e))
+                    .ok();
+                if rendered_sender
+                    .send(RenderedPdfPage {
+                        request,
+                        pdf_pixmap,
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        PdfPageRenderer {
+            request_sender,
+            rendered_receiver,
+            pending_requests: vec![],
+        }
+    }
+
+    pub fn request(&mut self, request: PdfPageRequest) {
+        if self.pending_requests.contains(&request) {
+            return;
+        }
+
+        if self.request_sender.send(request.clone()).is_ok() {
+            self.pending_requests.push(request);
+        }
+    }
+
+    pub fn take_rendered_pages(&mut self) -> Vec<RenderedPdfPage> {
+        let rendered_pages: Vec<RenderedPdfPage> = self.rendered_receiver.try_iter().collect();
+        self.pending_requests
+            .retain(|v| !rendered_pages.iter().any(|rendered| rendered.request == *v));
+        rendered_pages
+    }
+
+    pub fn is_rendering(&self) -> bool {
+        !self.pending_requests.is_empty()
+    }
+}
+
+fn render_pdf_page(
+    document: &mut Option<(String, Document)>,
+    request: &PdfPageRequest,
+) -> Result<PdfPixmap, Box<dyn std::error::Error>> {
+    let document = match document {
+        Some((file_path, document)) if *file_path == request.file_path => &*document,
+        _ => {
+            let opened = Document::open(&request.file_path)?;
+            &document.insert((request.file_path.clone(), opened)).1
+        }
+    };
+    let page = document.load_page(request.page_index as i32)?;
+
+    let zoom = request.ppi / 96.0;
+    let mut ctm = mupdf::Matrix::new_scale(zoom, zoom);
+    ctm.rotate(request.rotation as f32);
+    let cs = mupdf::Colorspace::device_rgb();
+
+    let pixmap = page.to_pixmap(&ctm, &cs, true, true)?;
+    Ok(PdfPixmap {
+        pixmap,
+        rotation: request.rotation,
+    })
+}
@@ -0,0 +1,495 @@
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use gtk::prelude::{
+    BoxExt, CheckButtonExt, ColorChooserExt, GridExt, GtkWindowExt, IsA, WidgetExt,
+};
+use gtk::{gdk, glib};
+use gtk4 as gtk;
+
+use crate::image_scaler;
+use crate::settings::{self, Settings};
+use crate::types::{BackgroundMode, FitMode, Interpolation, PageDirection, PageTransition};
+
+// what has been changed, so the caller knows how to apply it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SettingsChange {
+    Layout,
+    Scaling,
+    Background,
+    HistorySize,
+    ChapterMarks,
+    Other,
+}
+
+// the change is sent to every window as the parameter of an app action.
+impl SettingsChange {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Layout => "layout",
+            Self::Scaling => "scaling",
+            Self::Background => "background",
+            Self::HistorySize => "history_size",
+            Self::ChapterMarks => "chapter_marks",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl FromStr for SettingsChange {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "layout" => Ok(SettingsChange::Layout),
+            "scaling" => Ok(SettingsChange::Scaling),
+            "background" => Ok(SettingsChange::Background),
+            "history_size" => Ok(SettingsChange::HistorySize),
+            "chapter_marks" => Ok(SettingsChange::ChapterMarks),
+            "other" => Ok(SettingsChange::Other),
+            _ => Err(()),
+        }
+    }
+}
+
+const PAGE_DIRECTIONS: &[(PageDirection, &str)] = &[
+    (PageDirection::RightToLeft, "Right to Left"),
+    (PageDirection::LeftToRight, "Left to Right"),
+];
+
+const FIT_MODES: &[(FitMode, &str)] = &[
+    (FitMode::Auto, "Auto"),
+    (FitMode::Page, "Fit Page"),
+    (FitMode::Width, "Fit Width"),
+    (FitMode::Height, "Fit Height"),
+    (FitMode::Original, "Original Size"),
+];
+
+const BACKGROUND_MODES: &[(BackgroundMode, &str)] = &[
+    (BackgroundMode::Solid, "Solid Colour"),
+    (BackgroundMode::PageBorder, "Page Border Colour"),
+    (BackgroundMode::Blurred, "Blurred Page"),
+];
+
+const PAGE_TRANSITIONS: &[(PageTransition, &str)] = &[
+    (PageTransition::None, "None"),
+    (PageTransition::Slide, "Slide"),
+    (PageTransition::Crossfade, "Crossfade"),
+];
+
+const INTERPOLATIONS: &[(Interpolation, &str)] = &[
+    (Interpolation::Nearest, "Nearest"),
+    (Interpolation::Tiles, "Tiles"),
+    (Interpolation::Bilinear, "Bilinear"),
+    (Interpolation::Hyper, "Hyper"),
+    (Interpolation::Lanczos3, "Lanczos-3"),
+];
+
+fn create_drop_down<T: Copy + PartialEq>(items: &[(T, &str)], selected: T) -> gtk::DropDown {
+    let labels: Vec<&str> = items.iter().map(|(_, label)| *label).collect();
+    let drop_down = gtk::DropDown::from_strings(&labels);
+    let position = items.iter().position(|(v, _)| *v == selected).unwrap_or(0);
+    drop_down.set_selected(position as u32);
+    drop_down
+}
+
+fn get_selected<T: Copy>(items: &[(T, &str)], drop_down: &gtk::DropDown) -> Option<T> {
+    items.get(drop_down.selected() as usize).map(|(v, _)| *v)
+}
+
+fn attach_row(grid: &gtk::Grid, row: i32, label: &str, widget: &impl IsA<gtk::Widget>) {
+    let label = gtk::Label::new(Some(label));
+    label.set_halign(gtk::Align::Start);
+    grid.attach(&label, 0, row, 1, 1);
+    grid.attach(widget, 1, row, 1, 1);
+}
+
+// every change is applied at once and saved a moment later, there is no apply button.
+pub fn show_preferences_window<F: Fn(SettingsChange) + 'static>(
+    parent: &impl IsA<gtk::Window>,
+    settings: &Arc<Settings>,
+    on_changed: F,
+) {
+    let on_changed = Rc::new(move |change: SettingsChange| on_changed(change));
+
+    let grid = gtk::Grid::new();
+    grid.set_row_spacing(8);
+    grid.set_column_spacing(12);
+    grid.set_margin_top(12);
+    grid.set_margin_bottom(12);
+    grid.set_margin_start(12);
+    grid.set_margin_end(12);
+
+    let page_direction_drop_down = create_drop_down(
+        PAGE_DIRECTIONS,
+        *settings.default_page_direction.lock().unwrap(),
+    );
+    page_direction_drop_down.connect_selected_notify(glib::clone!(
+        #[strong]
+        settings,
+        #[strong]
+        on_changed,
+        move |drop_down: &gtk::DropDown| {
+            let Some(page_direction) = get_selected(PAGE_DIRECTIONS, drop_down) else {
+                return;
+            };
+            *settings.default_page_direction.lock().unwrap() = page_direction;
+            settings.save_later();
+            on_changed(SettingsChange::Other);
+        }
+    ));
+    attach_row(
+        &grid,
+        0,
+        "Default page direction",
+        &page_direction_drop_down,
+    );
+
+    let layout_drop_down = gtk::DropDown::from_strings(&["Dual Page", "Single Page"]);
+    let is_dual_mode = *settings.is_dual_mode.lock().unwrap();
+    layout_drop_down.set_selected(if is_dual_mode { 0 } else { 1 });
+    layout_drop_down.connect_selected_notify(glib::clone!(
+        #[strong]
+        settings,
+        #[strong]
+        on_changed,
+        move |drop_down: &gtk::DropDown| {
+            *settings.is_dual_mode.lock().unwrap() = drop_down.selected() == 0;
+            settings.save_later();
+            on_changed(SettingsChange::Layout);
+        }
+    ));
+    attach_row(&grid, 1, "Layout", &layout_drop_down);
+
+    let fit_mode_drop_down = create_drop_down(FIT_MODES, *settings.fit_mode.lock().unwrap());
+    fit_mode_drop_down.connect_selected_notify(glib::clone!(
+        #[strong]
+        settings,
+        #[strong]
+        on_changed,
+        move |drop_down: &gtk::DropDown| {
+            let Some(fit_mode) = get_selected(FIT_MODES, drop_down) else {
+                return;
+            };
+            *settings.fit_mode.lock().unwrap() = fit_mode;
+            settings.save_later();
+            on_changed(SettingsChange::Scaling);
+        }
+    ));
+    attach_row(&grid, 2, "Fit mode", &fit_mode_drop_down);
+
+    let background_color = *settings.background_color.lock().unwrap();
+    let background_check_button = gtk::CheckButton::with_label("Custom");
+    background_check_button.set_active(background_color.is_some());
+    let background_color_button = gtk::ColorButton::new();
+    background_color_button.set_rgba(&background_color.unwrap_or(gdk::RGBA::BLACK));
+    background_color_button.set_sensitive(background_color.is_some());
+    let background_box = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+    background_box.append(&background_check_button);
+    background_box.append(&background_color_button);
+    background_check_button.connect_toggled(glib::clone!(
+        #[strong]
+        settings,
+        #[strong]
+        on_changed,
+        #[weak]
+        background_color_button,
+        move |check_button: &gtk::CheckButton| {
+            let is_active = check_button.is_active();
+            background_color_button.set_sensitive(is_active);
+            *settings.background_color.lock().unwrap() = if is_active {
+                Some(background_color_button.rgba())
+            } else {
+                None
+            };
+            settings.save_later();
+            on_changed(SettingsChange::Background);
+        }
+    ));
+    background_color_button.connect_color_set(glib::clone!(
+        #[strong]
+        settings,
+        #[strong]
+        on_changed,
+        move |color_button: &gtk::ColorButton| {
+            *settings.background_color.lock().unwrap() = Some(color_button.rgba());
+            settings.save_later();
+            on_changed(SettingsChange::Background);
+        }
+    ));
+    attach_row(&grid, 3, "Background colour", &background_box);
+
+    let history_size_spin_button =
+        gtk::SpinButton::with_range(1.0, settings::MAX_HISTORY_SIZE as f64, 1.0);
+    history_size_spin_button.set_value(*settings.history_size.lock().unwrap() as f64);
+    history_size_spin_button.connect_value_changed(glib::clone!(
+        #[strong]
+        settings,
+        #[strong]
+        on_changed,
+        move |spin_button: &gtk::SpinButton| {
+            *settings.history_size.lock().unwrap() = spin_button.value_as_int().max(1) as usize;
+            settings.save_later();
+            on_changed(SettingsChange::HistorySize);
+        }
+    ));
+    attach_row(&grid, 4, "History size", &history_size_spin_button);
+
+    let interpolation_drop_down =
+        create_drop_down(INTERPOLATIONS, *settings.interpolation.lock().unwrap());
+    interpolation_drop_down.connect_selected_notify(glib::clone!(
+        #[strong]
+        settings,
+        #[strong]
+        on_changed,
+        move |drop_down: &gtk::DropDown| {
+            let Some(interpolation) = get_selected(INTERPOLATIONS, drop_down) else {
+                return;
+            };
+            *settings.interpolation.lock().unwrap() = interpolation;
+            settings.save_later();
+            on_changed(SettingsChange::Scaling);
+        }
+    ));
+    attach_row(&grid, 5, "Interpolation quality", &interpolation_drop_down);
+
+    let prefetch_count_spin_button =
+        gtk::SpinButton::with_range(0.0, settings::MAX_PREFETCH_COUNT as f64, 1.0);
+    prefetch_count_spin_button.set_value(*settings.prefetch_count.lock().unwrap() as f64);
+    prefetch_count_spin_button.connect_value_changed(glib::clone!(
+        #[strong]
+        settings,
+        #[strong]
+        on_changed,
+        move |spin_button: &gtk::SpinButton| {
+            *settings.prefetch_count.lock().unwrap() = spin_button.value_as_int().max(0) as usize;
+            settings.save_later();
+            on_changed(SettingsChange::Other);
+        }
+    ));
+    attach_row(&grid, 6, "Pages to prefetch", &prefetch_count_spin_button);
+
+    let restore_session_switch = gtk::Switch::new();
+    restore_session_switch.set_halign(gtk::Align::Start);
+    restore_session_switch.set_active(*settings.is_restore_session.lock().unwrap());
+    restore_session_switch.connect_active_notify(glib::clone!(
+        #[strong]
+        settings,
+        #[strong]
+        on_changed,
+        move |switch: &gtk::Switch| {
+            *settings.is_restore_session.lock().unwrap() = switch.is_active();
+            settings.save_later();
+            on_changed(SettingsChange::Other);
+        }
+    ));
+    attach_row(
+        &grid,
+        7,
+        "Restore last session on launch",
+        &restore_session_switch,
+    );
+
+    let chapter_marks_switch = gtk::Switch::new();
+    chapter_marks_switch.set_halign(gtk::Align::Start);
+    chapter_marks_switch.set_active(*settings.is_show_chapter_marks.lock().unwrap());
+    chapter_marks_switch.connect_active_notify(glib::clone!(
+        #[strong]
+        settings,
+        #[strong]
+        on_changed,
+        move |switch: &gtk::Switch| {
+            *settings.is_show_chapter_marks.lock().unwrap() = switch.is_active();
+            settings.save_later();
+            on_changed(SettingsChange::ChapterMarks);
+        }
+    ));
+    attach_row(
+        &grid,
+        8,
+        "Chapter marks on the slider",
+        &chapter_marks_switch,
+    );
+
+    let auto_crop_tolerance_spin_button = gtk::SpinButton::with_range(0.0, 128.0, 1.0);
+    auto_crop_tolerance_spin_button.set_value(*settings.auto_crop_tolerance.lock().unwrap() as f64);
+    auto_crop_tolerance_spin_button.connect_value_changed(glib::clone!(
+        #[strong]
+        settings,
+        #[strong]
+        on_changed,
+        move |spin_button: &gtk::SpinButton| {
+            *settings.auto_crop_tolerance.lock().unwrap() =
+                spin_button.value_as_int().clamp(0, 128) as u8;
+            settings.save_later();
+            on_changed(SettingsChange::Scaling);
+        }
+    ));
+    attach_row(
+        &grid,
+        9,
+        "Auto crop tolerance",
+        &auto_crop_tolerance_spin_button,
+    );
+
+    let sharpen_spin_button =
+        gtk::SpinButton::with_range(0.0, image_scaler::MAX_SHARPEN_AMOUNT, 0.1);
+    sharpen_spin_button.set_digits(1);
+    sharpen_spin_button.set_value(*settings.sharpen_amount.lock().unwrap());
+    sharpen_spin_button.connect_value_changed(glib::clone!(
+        #[strong]
+        settings,
+        #[strong]
+        on_changed,
+        move |spin_button: &gtk::SpinButton| {
+            *settings.sharpen_amount.lock().unwrap() = spin_button
+                .value()
+                .clamp(0.0, image_scaler::MAX_SHARPEN_AMOUNT);
+            settings.save_later();
+            on_changed(SettingsChange::Scaling);
+        }
+    ));
+    attach_row(&grid, 10, "Sharpen after downscaling", &sharpen_spin_button);
+
+    let slideshow_interval_spin_button = gtk::SpinButton::with_range(
+        settings::MIN_SLIDESHOW_INTERVAL,
+        settings::MAX_SLIDESHOW_INTERVAL,
+        0.5,
+    );
+    slideshow_interval_spin_button.set_digits(1);
+    slideshow_interval_spin_button.set_value(*settings.slideshow_interval.lock().unwrap());
+    slideshow_interval_spin_button.connect_value_changed(glib::clone!(
+        #[strong]
+        settings,
+        move |spin_button: &gtk::SpinButton| {
+            *settings.slideshow_interval.lock().unwrap() = spin_button.value().clamp(
+                settings::MIN_SLIDESHOW_INTERVAL,
+                settings::MAX_SLIDESHOW_INTERVAL,
+            );
+            settings.save_later();
+        }
+    ));
+    attach_row(
+        &grid,
+        11,
+        "Slideshow interval (seconds)",
+        &slideshow_interval_spin_button,
+    );
+
+    let slideshow_scroll_speed_spin_button = gtk::SpinButton::with_range(
+        settings::MIN_SLIDESHOW_SCROLL_SPEED,
+        settings::MAX_SLIDESHOW_SCROLL_SPEED,
+        10.0,
+    );
+    slideshow_scroll_speed_spin_button.set_value(*settings.slideshow_scroll_speed.lock().unwrap());
+    slideshow_scroll_speed_spin_button.connect_value_changed(glib::clone!(
+        #[strong]
+        settings,
+        move |spin_button: &gtk::SpinButton| {
+            *settings.slideshow_scroll_speed.lock().unwrap() = spin_button.value().clamp(
+                settings::MIN_SLIDESHOW_SCROLL_SPEED,
+                settings::MAX_SLIDESHOW_SCROLL_SPEED,
+            );
+            settings.save_later();
+        }
+    ));
+    attach_row(
+        &grid,
+        12,
+        "Slideshow scroll speed (pixels per second)",
+        &slideshow_scroll_speed_spin_button,
+    );
+
+    let page_transition_drop_down =
+        create_drop_down(PAGE_TRANSITIONS, *settings.page_transition.lock().unwrap());
+    page_transition_drop_down.connect_selected_notify(glib::clone!(
+        #[strong]
+        settings,
+        move |drop_down: &gtk::DropDown| {
+            let Some(page_transition) = get_selected(PAGE_TRANSITIONS, drop_down) else {
+                return;
+            };
+            *settings.page_transition.lock().unwrap() = page_transition;
+            settings.save_later();
+        }
+    ));
+    attach_row(&grid, 13, "Page transition", &page_transition_drop_down);
+
+    let background_mode_drop_down =
+        create_drop_down(BACKGROUND_MODES, *settings.background_mode.lock().unwrap());
+    background_mode_drop_down.connect_selected_notify(glib::clone!(
+        #[strong]
+        settings,
+        #[strong]
+        on_changed,
+        move |drop_down: &gtk::DropDown| {
+            let Some(background_mode) = get_selected(BACKGROUND_MODES, drop_down) else {
+                return;
+            };
+            *settings.background_mode.lock().unwrap() = background_mode;
+            settings.save_later();
+            on_changed(SettingsChange::Background);
+        }
+    ));
+    attach_row(&grid, 14, "Background", &background_mode_drop_down);
+
+    let spread_gutter_spin_button =
+        gtk::SpinButton::with_range(0.0, settings::MAX_SPREAD_GUTTER as f64, 1.0);
+    spread_gutter_spin_button.set_value(*settings.spread_gutter.lock().unwrap() as f64);
+    spread_gutter_spin_button.connect_value_changed(glib::clone!(
+        #[strong]
+        settings,
+        #[strong]
+        on_changed,
+        move |spin_button: &gtk::SpinButton| {
+            *settings.spread_gutter.lock().unwrap() = spin_button.value_as_int().max(0) as usize;
+            settings.save_later();
+            on_changed(SettingsChange::Scaling);
+        }
+    ));
+    attach_row(
+        &grid,
+        15,
+        "Gap between pages (pixels)",
+        &spread_gutter_spin_button,
+    );
+
+    let spread_aligned_at_spine_switch = gtk::Switch::new();
+    spread_aligned_at_spine_switch.set_halign(gtk::Align::Start);
+    spread_aligned_at_spine_switch.set_active(*settings.is_spread_aligned_at_spine.lock().unwrap());
+    spread_aligned_at_spine_switch.connect_active_notify(glib::clone!(
+        #[strong]
+        settings,
+        #[strong]
+        on_changed,
+        move |switch: &gtk::Switch| {
+            *settings.is_spread_aligned_at_spine.lock().unwrap() = switch.is_active();
+            settings.save_later();
+            on_changed(SettingsChange::Layout);
+        }
+    ));
+    attach_row(
+        &grid,
+        16,
+        "Align spreads at the spine",
+        &spread_aligned_at_spine_switch,
+    );
+
+    let window = gtk::Window::builder()
+        .title("Preferences")
+        .transient_for(parent)
+        .destroy_with_parent(true)
+        .resizable(false)
+        .child(&grid)
+        .build();
+    window.connect_close_request(glib::clone!(
+        #[strong]
+        settings,
+        move |_window| {
+            settings.flush();
+            glib::Propagation::Proceed
+        }
+    ));
+    window.present();
+}
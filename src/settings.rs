@@ -0,0 +1,538 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use gtk::{gdk, glib};
+use gtk4 as gtk;
+
+use crate::auto_crop;
+use crate::image_filter::ImageAdjustments;
+use crate::image_scaler;
+use crate::types::{
+    BackgroundMode, FitMode, Interpolation, PageDirection, PageTransition, WheelAction,
+};
+use crate::utils;
+
+const SETTINGS_FILE_NAME: &str = "settings.ini";
+const GENERAL_GROUP: &str = "general";
+const MOUSE_GROUP: &str = "mouse";
+const WINDOW_GROUP: &str = "window";
+const ADJUSTMENTS_GROUP: &str = "adjustments";
+
+pub const DEFAULT_CLICK_ZONE_RATIO: f64 = 1.0 / 3.0;
+pub const DEFAULT_HISTORY_SIZE: usize = 10;
+pub const MAX_HISTORY_SIZE: usize = 100;
+pub const DEFAULT_PREFETCH_COUNT: usize = 2;
+pub const MAX_PREFETCH_COUNT: usize = 10;
+pub const MAX_SPREAD_GUTTER: usize = 200;
+pub const DEFAULT_SLIDESHOW_INTERVAL: f64 = 5.0;
+pub const MIN_SLIDESHOW_INTERVAL: f64 = 1.0;
+pub const MAX_SLIDESHOW_INTERVAL: f64 = 600.0;
+pub const DEFAULT_SLIDESHOW_SCROLL_SPEED: f64 = 120.0;
+pub const MIN_SLIDESHOW_SCROLL_SPEED: f64 = 10.0;
+pub const MAX_SLIDESHOW_SCROLL_SPEED: f64 = 2000.0;
+pub const DEFAULT_WINDOW_WIDTH: i32 = 1024;
+pub const DEFAULT_WINDOW_HEIGHT: i32 = 768;
+const SAVE_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+#[derive(Default)]
+pub struct Settings {
+    pub is_dual_mode: Arc<Mutex<bool>>,
+    pub is_auto_open_next_book: Arc<Mutex<bool>>,
+    pub click_zone_ratio: Arc<Mutex<f64>>,
+    pub is_click_to_turn: Arc<Mutex<bool>>,
+    pub is_center_click_toggle_ui: Arc<Mutex<bool>>,
+    pub wheel_action: Arc<Mutex<WheelAction>>,
+    pub default_page_direction: Arc<Mutex<PageDirection>>,
+    pub fit_mode: Arc<Mutex<FitMode>>,
+    // None paints nothing, so the theme background is shown.
+    pub background_color: Arc<Mutex<Option<gdk::RGBA>>>,
+    pub background_mode: Arc<Mutex<BackgroundMode>>,
+    // logical pixels between the pages of a spread.
+    pub spread_gutter: Arc<Mutex<usize>>,
+    // the pages of a spread meet in the middle of the window, as in an open book.
+    pub is_spread_aligned_at_spine: Arc<Mutex<bool>>,
+    pub history_size: Arc<Mutex<usize>>,
+    pub interpolation: Arc<Mutex<Interpolation>>,
+    // unsharp mask after downscaling, 0 is off.
+    pub sharpen_amount: Arc<Mutex<f64>>,
+    pub prefetch_count: Arc<Mutex<usize>>,
+    pub is_restore_session: Arc<Mutex<bool>>,
+    pub is_show_chapter_marks: Arc<Mutex<bool>>,
+    pub is_show_status_bar: Arc<Mutex<bool>>,
+    pub is_auto_crop: Arc<Mutex<bool>>,
+    // largest difference of a colour channel which is still taken as the border.
+    pub auto_crop_tolerance: Arc<Mutex<u8>>,
+    // used by books without adjustments of their own.
+    pub image_adjustments: Arc<Mutex<ImageAdjustments>>,
+    // seconds between page turns of the slideshow.
+    pub slideshow_interval: Arc<Mutex<f64>>,
+    // logical pixels per second, for pages taller than the window.
+    pub slideshow_scroll_speed: Arc<Mutex<f64>>,
+    pub page_transition: Arc<Mutex<PageTransition>>,
+    // a save which waits for the changes to settle.
+    save_source_id: Arc<Mutex<Option<glib::SourceId>>>,
+}
+
+fn get_bool(key_file: Option<&glib::KeyFile>, group: &str, key: &str, default: bool) -> bool {
+    key_file
+        .and_then(|v| v.boolean(group, key).ok())
+        .unwrap_or(default)
+}
+
+fn get_usize(key_file: Option<&glib::KeyFile>, group: &str, key: &str, default: usize) -> usize {
+    key_file
+        .and_then(|v| v.integer(group, key).ok())
+        .and_then(|v| usize::try_from(v).ok())
+        .unwrap_or(default)
+}
+
+fn get_double(key_file: Option<&glib::KeyFile>, group: &str, key: &str, default: f64) -> f64 {
+    key_file
+        .and_then(|v| v.double(group, key).ok())
+        .unwrap_or(default)
+}
+
+// a value which is not a number is replaced by the default.
+fn get_clamped_double(
+    key_file: Option<&glib::KeyFile>,
+    group: &str,
+    key: &str,
+    default: f64,
+    min: f64,
+    max: f64,
+) -> f64 {
+    let value = get_double(key_file, group, key, default);
+    if value.is_finite() {
+        value.clamp(min, max)
+    } else {
+        default
+    }
+}
+
+fn get_parsed<T: FromStr + Default>(key_file: Option<&glib::KeyFile>, group: &str, key: &str) -> T {
+    key_file
+        .and_then(|v| v.string(group, key).ok())
+        .and_then(|v| T::from_str(&v).ok())
+        .unwrap_or_default()
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let key_file = utils::load_config_key_file(SETTINGS_FILE_NAME);
+        let key_file = key_file.as_ref();
+
+        let is_dual_mode = key_file
+            .and_then(|v| v.string(GENERAL_GROUP, "layout").ok())
+            .is_none_or(|v| v != "single");
+        let background_color = key_file
+            .and_then(|v| v.string(GENERAL_GROUP, "background_color").ok())
+            .and_then(|v| gdk::RGBA::parse(v.as_str()).ok());
+        let click_zone_ratio = key_file
+            .and_then(|v| v.double(MOUSE_GROUP, "click_zone_ratio").ok())
+            .unwrap_or(DEFAULT_CLICK_ZONE_RATIO)
+            .clamp(0.0, 0.5);
+        let default_adjustments = ImageAdjustments::default();
+        let image_adjustments = ImageAdjustments {
+            brightness: get_double(
+                key_file,
+                ADJUSTMENTS_GROUP,
+                "brightness",
+                default_adjustments.brightness,
+            ),
+            contrast: get_double(
+                key_file,
+                ADJUSTMENTS_GROUP,
+                "contrast",
+                default_adjustments.contrast,
+            ),
+            gamma: get_double(
+                key_file,
+                ADJUSTMENTS_GROUP,
+                "gamma",
+                default_adjustments.gamma,
+            ),
+            saturation: get_double(
+                key_file,
+                ADJUSTMENTS_GROUP,
+                "saturation",
+                default_adjustments.saturation,
+            ),
+            sepia: get_double(
+                key_file,
+                ADJUSTMENTS_GROUP,
+                "sepia",
+                default_adjustments.sepia,
+            ),
+            is_inverted: get_bool(key_file, ADJUSTMENTS_GROUP, "invert", false),
+        }
+        .clamped();
+
+        Settings {
+            is_dual_mode: Arc::new(Mutex::new(is_dual_mode)),
+            is_auto_open_next_book: Arc::new(Mutex::new(get_bool(
+                key_file,
+                GENERAL_GROUP,
+                "auto_open_next_book",
+                false,
+            ))),
+            click_zone_ratio: Arc::new(Mutex::new(click_zone_ratio)),
+            is_click_to_turn: Arc::new(Mutex::new(get_bool(
+                key_file,
+                MOUSE_GROUP,
+                "click_to_turn",
+                true,
+            ))),
+            is_center_click_toggle_ui: Arc::new(Mutex::new(get_bool(
+                key_file,
+                MOUSE_GROUP,
+                "center_click_toggle_ui",
+                true,
+            ))),
+            wheel_action: Arc::new(Mutex::new(get_parsed(key_file, MOUSE_GROUP, "wheel"))),
+            default_page_direction: Arc::new(Mutex::new(get_parsed(
+                key_file,
+                GENERAL_GROUP,
+                "page_direction",
+            ))),
+            fit_mode: Arc::new(Mutex::new(get_parsed(key_file, GENERAL_GROUP, "fit_mode"))),
+            background_color: Arc::new(Mutex::new(background_color)),
+            background_mode: Arc::new(Mutex::new(get_parsed(
+                key_file,
+                GENERAL_GROUP,
+                "background",
+            ))),
+            spread_gutter: Arc::new(Mutex::new(
+                get_usize(key_file, GENERAL_GROUP, "spread_gutter", 0).min(MAX_SPREAD_GUTTER),
+            )),
+            is_spread_aligned_at_spine: Arc::new(Mutex::new(get_bool(
+                key_file,
+                GENERAL_GROUP,
+                "align_spread_at_spine",
+                false,
+            ))),
+            history_size: Arc::new(Mutex::new(
+                get_usize(
+                    key_file,
+                    GENERAL_GROUP,
+                    "history_size",
+                    DEFAULT_HISTORY_SIZE,
+                )
+                .clamp(1, MAX_HISTORY_SIZE),
+            )),
+            interpolation: Arc::new(Mutex::new(get_parsed(
+                key_file,
+                GENERAL_GROUP,
+                "interpolation",
+            ))),
+            sharpen_amount: Arc::new(Mutex::new(get_clamped_double(
+                key_file,
+                GENERAL_GROUP,
+                "sharpen",
+                0.0,
+                0.0,
+                image_scaler::MAX_SHARPEN_AMOUNT,
+            ))),
+            prefetch_count: Arc::new(Mutex::new(
+                get_usize(
+                    key_file,
+                    GENERAL_GROUP,
+                    "prefetch_count",
+                    DEFAULT_PREFETCH_COUNT,
+                )
+                .min(MAX_PREFETCH_COUNT),
+            )),
+            is_restore_session: Arc::new(Mutex::new(get_bool(
+                key_file,
+                GENERAL_GROUP,
+                "restore_session",
+                false,
+            ))),
+            is_show_chapter_marks: Arc::new(Mutex::new(get_bool(
+                key_file,
+                GENERAL_GROUP,
+                "chapter_marks",
+                true,
+            ))),
+            is_show_status_bar: Arc::new(Mutex::new(get_bool(
+                key_file,
+                GENERAL_GROUP,
+                "status_bar",
+                false,
+            ))),
+            is_auto_crop: Arc::new(Mutex::new(get_bool(
+                key_file,
+                GENERAL_GROUP,
+                "auto_crop",
+                false,
+            ))),
+            auto_crop_tolerance: Arc::new(Mutex::new(
+                get_usize(
+                    key_file,
+                    GENERAL_GROUP,
+                    "auto_crop_tolerance",
+                    auto_crop::DEFAULT_TOLERANCE as usize,
+                )
+                .min(u8::MAX as usize) as u8,
+            )),
+            image_adjustments: Arc::new(Mutex::new(image_adjustments)),
+            slideshow_interval: Arc::new(Mutex::new(get_clamped_double(
+                key_file,
+                GENERAL_GROUP,
+                "slideshow_interval",
+                DEFAULT_SLIDESHOW_INTERVAL,
+                MIN_SLIDESHOW_INTERVAL,
+                MAX_SLIDESHOW_INTERVAL,
+            ))),
+            slideshow_scroll_speed: Arc::new(Mutex::new(get_clamped_double(
+                key_file,
+                GENERAL_GROUP,
+                "slideshow_scroll_speed",
+                DEFAULT_SLIDESHOW_SCROLL_SPEED,
+                MIN_SLIDESHOW_SCROLL_SPEED,
+                MAX_SLIDESHOW_SCROLL_SPEED,
+            ))),
+            page_transition: Arc::new(Mutex::new(get_parsed(
+                key_file,
+                GENERAL_GROUP,
+                "page_transition",
+            ))),
+            save_source_id: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    // a spin button or a slider changes many times a second, so the file is written once
+    // after the last change.
+    pub fn save_later(self: &Arc<Self>) {
+        if let Some(source_id) = self.save_source_id.lock().unwrap().take() {
+            source_id.remove();
+        }
+
+        let settings = Arc::clone(self);
+        let source_id = glib::timeout_add_local_once(SAVE_DELAY, move || {
+            // the source is gone once this returns, so it must not be removed later.
+            *settings.save_source_id.lock().unwrap() = None;
+            settings.save();
+        });
+        *self.save_source_id.lock().unwrap() = Some(source_id);
+    }
+
+    // writes the save which is still waiting, before the window or the app is closed.
+    pub fn flush(&self) {
+        if let Some(source_id) = self.save_source_id.lock().unwrap().take() {
+            source_id.remove();
+            self.save();
+        }
+    }
+
+    // other groups and comments in the file are kept.
+    pub fn save(&self) {
+        let key_file = utils::load_config_key_file(SETTINGS_FILE_NAME).unwrap_or_default();
+
+        let layout = if *self.is_dual_mode.lock().unwrap() {
+            "dual"
+        } else {
+            "single"
+        };
+        key_file.set_string(GENERAL_GROUP, "layout", layout);
+        key_file.set_string(
+            GENERAL_GROUP,
+            "page_direction",
+            self.default_page_direction.lock().unwrap().as_str(),
+        );
+        key_file.set_string(
+            GENERAL_GROUP,
+            "fit_mode",
+            self.fit_mode.lock().unwrap().as_str(),
+        );
+        let background_color = self
+            .background_color
+            .lock()
+            .unwrap()
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        key_file.set_string(GENERAL_GROUP, "background_color", &background_color);
+        key_file.set_string(
+            GENERAL_GROUP,
+            "background",
+            self.background_mode.lock().unwrap().as_str(),
+        );
+        key_file.set_integer(
+            GENERAL_GROUP,
+            "spread_gutter",
+            *self.spread_gutter.lock().unwrap() as i32,
+        );
+        key_file.set_boolean(
+            GENERAL_GROUP,
+            "align_spread_at_spine",
+            *self.is_spread_aligned_at_spine.lock().unwrap(),
+        );
+        key_file.set_integer(
+            GENERAL_GROUP,
+            "history_size",
+            *self.history_size.lock().unwrap() as i32,
+        );
+        key_file.set_string(
+            GENERAL_GROUP,
+            "interpolation",
+            self.interpolation.lock().unwrap().as_str(),
+        );
+        key_file.set_double(
+            GENERAL_GROUP,
+            "sharpen",
+            *self.sharpen_amount.lock().unwrap(),
+        );
+        key_file.set_integer(
+            GENERAL_GROUP,
+            "prefetch_count",
+            *self.prefetch_count.lock().unwrap() as i32,
+        );
+        key_file.set_boolean(
+            GENERAL_GROUP,
+            "restore_session",
+            *self.is_restore_session.lock().unwrap(),
+        );
+        key_file.set_boolean(
+            GENERAL_GROUP,
+            "chapter_marks",
+            *self.is_show_chapter_marks.lock().unwrap(),
+        );
+        key_file.set_boolean(
+            GENERAL_GROUP,
+            "status_bar",
+            *self.is_show_status_bar.lock().unwrap(),
+        );
+        key_file.set_boolean(
+            GENERAL_GROUP,
+            "auto_crop",
+            *self.is_auto_crop.lock().unwrap(),
+        );
+        key_file.set_integer(
+            GENERAL_GROUP,
+            "auto_crop_tolerance",
+            *self.auto_crop_tolerance.lock().unwrap() as i32,
+        );
+        key_file.set_boolean(
+            GENERAL_GROUP,
+            "auto_open_next_book",
+            *self.is_auto_open_next_book.lock().unwrap(),
+        );
+        key_file.set_double(
+            GENERAL_GROUP,
+            "slideshow_interval",
+            *self.slideshow_interval.lock().unwrap(),
+        );
+        key_file.set_double(
+            GENERAL_GROUP,
+            "slideshow_scroll_speed",
+            *self.slideshow_scroll_speed.lock().unwrap(),
+        );
+        key_file.set_string(
+            GENERAL_GROUP,
+            "page_transition",
+            self.page_transition.lock().unwrap().as_str(),
+        );
+
+        key_file.set_double(
+            MOUSE_GROUP,
+            "click_zone_ratio",
+            *self.click_zone_ratio.lock().unwrap(),
+        );
+        key_file.set_boolean(
+            MOUSE_GROUP,
+            "click_to_turn",
+            *self.is_click_to_turn.lock().unwrap(),
+        );
+        key_file.set_boolean(
+            MOUSE_GROUP,
+            "center_click_toggle_ui",
+            *self.is_center_click_toggle_ui.lock().unwrap(),
+        );
+        key_file.set_string(
+            MOUSE_GROUP,
+            "wheel",
+            self.wheel_action.lock().unwrap().as_str(),
+        );
+
+        let image_adjustments = *self.image_adjustments.lock().unwrap();
+        key_file.set_double(
+            ADJUSTMENTS_GROUP,
+            "brightness",
+            image_adjustments.brightness,
+        );
+        key_file.set_double(ADJUSTMENTS_GROUP, "contrast", image_adjustments.contrast);
+        key_file.set_double(ADJUSTMENTS_GROUP, "gamma", image_adjustments.gamma);
+        key_file.set_double(
+            ADJUSTMENTS_GROUP,
+            "saturation",
+            image_adjustments.saturation,
+        );
+        key_file.set_double(ADJUSTMENTS_GROUP, "sepia", image_adjustments.sepia);
+        key_file.set_boolean(ADJUSTMENTS_GROUP, "invert", image_adjustments.is_inverted);
+
+        utils::save_config_key_file(&key_file, SETTINGS_FILE_NAME);
+    }
+}
+
+// the window of the last session, it is saved on every close
+// and restored only when restore_session is enabled.
+pub struct WindowState {
+    pub width: i32,
+    pub height: i32,
+    pub is_maximized: bool,
+    pub is_fullscreen: bool,
+    // connector name such as "DP-1", monitor indices change with hotplug.
+    pub monitor: Option<String>,
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        WindowState {
+            width: DEFAULT_WINDOW_WIDTH,
+            height: DEFAULT_WINDOW_HEIGHT,
+            is_maximized: false,
+            is_fullscreen: false,
+            monitor: None,
+        }
+    }
+}
+
+impl WindowState {
+    pub fn load() -> Self {
+        let key_file = utils::load_config_key_file(SETTINGS_FILE_NAME);
+        let key_file = key_file.as_ref();
+        let get_size = |key: &str, default: i32| {
+            key_file
+                .and_then(|v| v.integer(WINDOW_GROUP, key).ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default)
+        };
+
+        WindowState {
+            width: get_size("width", DEFAULT_WINDOW_WIDTH),
+            height: get_size("height", DEFAULT_WINDOW_HEIGHT),
+            is_maximized: get_bool(key_file, WINDOW_GROUP, "maximized", false),
+            is_fullscreen: get_bool(key_file, WINDOW_GROUP, "fullscreen", false),
+            monitor: key_file
+                .and_then(|v| v.string(WINDOW_GROUP, "monitor").ok())
+                .map(|v| v.to_string())
+                .filter(|v| !v.is_empty()),
+        }
+    }
+
+    pub fn save(&self) {
+        let key_file = utils::load_config_key_file(SETTINGS_FILE_NAME).unwrap_or_default();
+
+        key_file.set_integer(WINDOW_GROUP, "width", self.width);
+        key_file.set_integer(WINDOW_GROUP, "height", self.height);
+        key_file.set_boolean(WINDOW_GROUP, "maximized", self.is_maximized);
+        key_file.set_boolean(WINDOW_GROUP, "fullscreen", self.is_fullscreen);
+        key_file.set_string(
+            WINDOW_GROUP,
+            "monitor",
+            self.monitor.as_deref().unwrap_or_default(),
+        );
+
+        utils::save_config_key_file(&key_file, SETTINGS_FILE_NAME);
+    }
+}
@@ -0,0 +1,167 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk::glib;
+use gtk::pango;
+use gtk::prelude::{BoxExt, CastNone, WidgetExt};
+use gtk4 as gtk;
+
+use crate::types::PageDirection;
+
+// an empty item is hidden, so nothing is shown while no book is open.
+#[derive(Default)]
+pub struct StatusInfo {
+    pub file_name: Option<String>,
+    pub page_text: Option<String>,
+    pub page_sizes: Vec<(i32, i32)>,
+    pub entry_names: Vec<String>,
+    pub zoom_percent: Option<f64>,
+    pub page_direction: Option<PageDirection>,
+}
+
+#[derive(Clone)]
+pub struct StatusBar {
+    pub widget: gtk::Box,
+    file_name_label: gtk::Label,
+    page_label: gtk::Label,
+    size_label: gtk::Label,
+    entry_name_label: gtk::Label,
+    zoom_label: gtk::Label,
+    page_direction_label: gtk::Label,
+    // shown over the page on every page turn while in fullscreen.
+    pub page_osd: gtk::Revealer,
+    page_osd_hide_source_id: Rc<RefCell<Option<glib::SourceId>>>,
+}
+
+fn create_label(ellipsize_mode: pango::EllipsizeMode) -> gtk::Label {
+    let label = gtk::Label::new(None);
+    label.set_xalign(0.0);
+    label.set_ellipsize(ellipsize_mode);
+    label.set_single_line_mode(true);
+    label
+}
+
+fn set_label_text(label: &gtk::Label, text: &str) {
+    label.set_text(text);
+    label.set_visible(!text.is_empty());
+}
+
+fn format_page_sizes(page_sizes: &[(i32, i32)]) -> String {
+    page_sizes
+        .iter()
+        .map(|(width, height)| format!("{}×{}", width, height))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+impl StatusBar {
+    pub fn new() -> Self {
+        let file_name_label = create_label(pango::EllipsizeMode::Middle);
+        file_name_label.set_hexpand(true);
+        let page_label = create_label(pango::EllipsizeMode::None);
+        page_label.add_css_class("numeric");
+        let size_label = create_label(pango::EllipsizeMode::None);
+        size_label.add_css_class("numeric");
+        let entry_name_label = create_label(pango::EllipsizeMode::Start);
+        entry_name_label.set_max_width_chars(40);
+        let zoom_label = create_label(pango::EllipsizeMode::None);
+        zoom_label.add_css_class("numeric");
+        let page_direction_label = create_label(pango::EllipsizeMode::None);
+
+        let widget = gtk::Box::new(gtk::Orientation::Horizontal, 16);
+        widget.set_margin_start(8);
+        widget.set_margin_end(8);
+        widget.set_margin_top(2);
+        widget.set_margin_bottom(2);
+        widget.append(&file_name_label);
+        widget.append(&entry_name_label);
+        widget.append(&size_label);
+        widget.append(&zoom_label);
+        widget.append(&page_direction_label);
+        widget.append(&page_label);
+
+        let page_osd_label = gtk::Label::new(None);
+        page_osd_label.add_css_class("osd");
+        page_osd_label.add_css_class("numeric");
+        page_osd_label.set_margin_top(24);
+        let page_osd = gtk::Revealer::new();
+        page_osd.set_transition_type(gtk::RevealerTransitionType::Crossfade);
+        page_osd.set_halign(gtk::Align::Center);
+        page_osd.set_valign(gtk::Align::Start);
+        page_osd.set_can_target(false);
+        page_osd.set_child(Some(&page_osd_label));
+
+        let status_bar = StatusBar {
+            widget,
+            file_name_label,
+            page_label,
+            size_label,
+            entry_name_label,
+            zoom_label,
+            page_direction_label,
+            page_osd,
+            page_osd_hide_source_id: Rc::new(RefCell::new(None)),
+        };
+        status_bar.update(&StatusInfo::default());
+        status_bar
+    }
+
+    pub fn update(&self, status_info: &StatusInfo) {
+        set_label_text(
+            &self.file_name_label,
+            status_info.file_name.as_deref().unwrap_or_default(),
+        );
+        set_label_text(
+            &self.page_label,
+            status_info.page_text.as_deref().unwrap_or_default(),
+        );
+        set_label_text(
+            &self.size_label,
+            &format_page_sizes(&status_info.page_sizes),
+        );
+        set_label_text(&self.entry_name_label, &status_info.entry_names.join(", "));
+        let zoom_text = status_info
+            .zoom_percent
+            .map(|v| format!("{:.0}%", v))
+            .unwrap_or_default();
+        set_label_text(&self.zoom_label, &zoom_text);
+        let page_direction_text = match status_info.page_direction {
+            Some(PageDirection::RightToLeft) => "Right to Left",
+            Some(PageDirection::LeftToRight) => "Left to Right",
+            None => "",
+        };
+        set_label_text(&self.page_direction_label, page_direction_text);
+    }
+
+    pub fn show_page_osd(&self, text: &str) {
+        let Some(label) = self.page_osd.child().and_downcast::<gtk::Label>() else {
+            return;
+        };
+
+        label.set_text(text);
+        self.page_osd.set_reveal_child(true);
+        self.hide_page_osd_later();
+    }
+
+    // a later page turn starts the time again, so the number stays while pages are turned.
+    fn hide_page_osd_later(&self) {
+        if let Some(source_id) = self.page_osd_hide_source_id.borrow_mut().take() {
+            source_id.remove();
+        }
+
+        let page_osd_hide_source_id = Rc::clone(&self.page_osd_hide_source_id);
+        let source_id = glib::timeout_add_local_once(
+            std::time::Duration::from_secs(2),
+            glib::clone!(
+                #[weak(rename_to = page_osd)]
+                self.page_osd,
+                move || {
+                    // the source is gone once this returns, so it must not be removed later.
+                    *page_osd_hide_source_id.borrow_mut() = None;
+                    page_osd.set_reveal_child(false);
+                }
+            ),
+        );
+        *self.page_osd_hide_source_id.borrow_mut() = Some(source_id);
+    }
+}
@@ -1,7 +1,10 @@
 use std::str::FromStr;
 
+use gtk::gdk_pixbuf::InterpType;
+use gtk4 as gtk;
+
 #[repr(i64)]
-#[derive(Default, Clone, Copy, Debug)]
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
 pub enum PageDirection {
     #[default]
     RightToLeft = 0,
@@ -40,3 +43,186 @@ impl PageDirection {
         }
     }
 }
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum WheelAction {
+    // scroll while the page overflows the view, otherwise turn pages.
+    #[default]
+    Auto,
+    Page,
+    Scroll,
+}
+
+impl FromStr for WheelAction {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(WheelAction::Auto),
+            "page" => Ok(WheelAction::Page),
+            "scroll" => Ok(WheelAction::Scroll),
+            _ => Err(()),
+        }
+    }
+}
+
+impl WheelAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Page => "page",
+            Self::Scroll => "scroll",
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum FitMode {
+    // portrait pages fit the height and landscape pages fit the width in single mode.
+    #[default]
+    Auto,
+    Page,
+    Width,
+    Height,
+    Original,
+}
+
+impl FromStr for FitMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(FitMode::Auto),
+            "page" => Ok(FitMode::Page),
+            "width" => Ok(FitMode::Width),
+            "height" => Ok(FitMode::Height),
+            "original" => Ok(FitMode::Original),
+            _ => Err(()),
+        }
+    }
+}
+
+impl FitMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Page => "page",
+            Self::Width => "width",
+            Self::Height => "height",
+            Self::Original => "original",
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation {
+    Nearest,
+    Tiles,
+    #[default]
+    Bilinear,
+    Hyper,
+    // done by image_scaler, gdk-pixbuf has no lanczos filter.
+    Lanczos3,
+}
+
+impl FromStr for Interpolation {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nearest" => Ok(Interpolation::Nearest),
+            "tiles" => Ok(Interpolation::Tiles),
+            "bilinear" => Ok(Interpolation::Bilinear),
+            "hyper" => Ok(Interpolation::Hyper),
+            "lanczos3" => Ok(Interpolation::Lanczos3),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Interpolation {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Nearest => "nearest",
+            Self::Tiles => "tiles",
+            Self::Bilinear => "bilinear",
+            Self::Hyper => "hyper",
+            Self::Lanczos3 => "lanczos3",
+        }
+    }
+
+    pub fn as_interp_type(self) -> Option<InterpType> {
+        match self {
+            Self::Nearest => Some(InterpType::Nearest),
+            Self::Tiles => Some(InterpType::Tiles),
+            Self::Bilinear => Some(InterpType::Bilinear),
+            Self::Hyper => Some(InterpType::Hyper),
+            Self::Lanczos3 => None,
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum PageTransition {
+    #[default]
+    None,
+    // the pages move in reading direction.
+    Slide,
+    Crossfade,
+}
+
+impl FromStr for PageTransition {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(PageTransition::None),
+            "slide" => Ok(PageTransition::Slide),
+            "crossfade" => Ok(PageTransition::Crossfade),
+            _ => Err(()),
+        }
+    }
+}
+
+impl PageTransition {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Slide => "slide",
+            Self::Crossfade => "crossfade",
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum BackgroundMode {
+    // the background colour, or the theme background when it is not set.
+    #[default]
+    Solid,
+    // the colour of the page edges, so the page seems to fill the window.
+    PageBorder,
+    Blurred,
+}
+
+impl FromStr for BackgroundMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "solid" => Ok(BackgroundMode::Solid),
+            "page_border" => Ok(BackgroundMode::PageBorder),
+            "blurred" => Ok(BackgroundMode::Blurred),
+            _ => Err(()),
+        }
+    }
+}
+
+impl BackgroundMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Solid => "solid",
+            Self::PageBorder => "page_border",
+            Self::Blurred => "blurred",
+        }
+    }
+}
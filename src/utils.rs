@@ -1,5 +1,6 @@
 use gtk::gio;
-use gtk::prelude::Cast;
+use gtk::glib;
+use gtk::prelude::{Cast, IsA, NativeExt, WidgetExt};
 use gtk4 as gtk;
 
 use gtk::gdk::prelude::DisplayExt;
@@ -37,42 +38,48 @@ where
     }
 }
 
-pub fn get_dpi() -> f32 {
-    let mut ppi = 144.0f32;
-    let display = gtk::gdk::Display::default().unwrap();
-    let monitors = display.monitors();
-    for monitor in monitors.into_iter() {
-        let pre_m = monitor.expect("faild get monitor from monitors iter.");
-        let m = pre_m
-            .downcast::<gtk::gdk::Monitor>()
-            .expect("failed downcast from object to Monitor");
-        let geometry = m.geometry();
-        let width_pixel = geometry.width();
-        let height_pixel = geometry.height();
-
-        let width_mm = m.width_mm();
-        let height_mm = m.height_mm();
-
-        let inch = 25.4f32;
-
-        if width_pixel < 1 || width_mm < 1 {
-            break;
-        }
-
-        if height_pixel < 1 || height_mm < 1 {
-            break;
-        }
+// pdf pages are never rendered for less than this, so they stay sharp when zoomed.
+const MIN_PPI: f32 = 144.0;
+const MM_PER_INCH: f32 = 25.4;
+
+// pixels per inch in device pixels, None when the monitor does not tell its size.
+pub fn get_monitor_ppi(monitor: &gtk::gdk::Monitor) -> Option<f32> {
+    let geometry = monitor.geometry();
+    let scale_factor = monitor.scale_factor();
+    let width_pixel = (geometry.width() * scale_factor) as f32;
+    let height_pixel = (geometry.height() * scale_factor) as f32;
+    let width_mm = monitor.width_mm() as f32;
+    let height_mm = monitor.height_mm() as f32;
+    if width_pixel < 1.0 || height_pixel < 1.0 || width_mm < 1.0 || height_mm < 1.0 {
+        return None;
+    }
 
-        let diagonal_mm = (((width_mm ^ 2) + (height_mm ^ 2)) as f32).sqrt();
-        let diagonal_pixel = (((width_pixel ^ 2) + (height_pixel ^ 2)) as f32).sqrt();
-        let tmp_ppi = diagonal_pixel / (diagonal_mm / inch);
+    let diagonal_inch = width_mm.hypot(height_mm) / MM_PER_INCH;
+    Some(width_pixel.hypot(height_pixel) / diagonal_inch)
+}
 
-        if tmp_ppi > ppi {
-            ppi = tmp_ppi;
-        }
-    }
+// the sharpest of all monitors, used before a window is shown.
+pub fn get_dpi() -> f32 {
+    let Some(display) = gtk::gdk::Display::default() else {
+        return MIN_PPI;
+    };
+    display
+        .monitors()
+        .into_iter()
+        .filter_map(|v| v.ok()?.downcast::<gtk::gdk::Monitor>().ok())
+        .filter_map(|v| get_monitor_ppi(&v))
+        .fold(MIN_PPI, f32::max)
+}
 
-    ppi
+// the monitor the widget is on, so a page is rendered for the screen it is read on.
+pub fn get_dpi_for_widget(widget: &impl IsA<gtk::Widget>) -> f32 {
+    widget
+        .native()
+        .and_then(|v| v.surface())
+        .and_then(|v| widget.display().monitor_at_surface(&v))
+        .and_then(|v| get_monitor_ppi(&v))
+        .map(|v| v.max(MIN_PPI))
+        .unwrap_or_else(get_dpi)
 }
 
 pub enum FileType {
@@ -129,6 +136,19 @@ pub fn detect_file_type_from_file(file: &gio::File) -> FileType {
     detect_file_type_from_bytes(&tmp)
 }
 
+pub fn detect_file_type_from_path(path: &std::path::Path) -> FileType {
+    let Ok(mut f) = std::fs::File::open(path) else {
+        return FileType::NONE;
+    };
+
+    let mut buf = [0u8; 5];
+    if std::io::Read::read_exact(&mut f, &mut buf).is_err() {
+        return FileType::NONE;
+    }
+
+    detect_file_type_from_bytes(&buf)
+}
+
 pub fn create_config_dir(dir: &str) {
     let pathbuf = std::path::PathBuf::from(dir);
     let v = pathbuf.try_exists().unwrap();
@@ -137,3 +157,28 @@ pub fn create_config_dir(dir: &str) {
         let _ = std::fs::create_dir_all(pathbuf).unwrap();
     }
 }
+
+// a missing file is not an error, the defaults are used then.
+pub fn load_config_key_file(file_name: &str) -> Option<glib::KeyFile> {
+    let path = get_xdg_config_home() + "/" + file_name;
+    let key_file = glib::KeyFile::new();
+    match key_file.load_from_file(&path, glib::KeyFileFlags::NONE) {
+        Ok(_) => Some(key_file),
+        Err(e) => {
+            if !e.matches(glib::FileError::Noent) {
+                eprintln!("failed load {}: {}", path, e);
+            }
+            None
+        }
+    }
+}
+
+pub fn save_config_key_file(key_file: &glib::KeyFile, file_name: &str) {
+    let dir = get_xdg_config_home();
+    create_config_dir(&dir);
+
+    let path = dir + "/" + file_name;
+    if let Err(e) = key_file.save_to_file(&path) {
+        eprintln!("failed save {}: {}", path, e);
+    }
+}